@@ -223,7 +223,7 @@ async function initializeForm () {
     const invalidLabel = document.getElementById('web-app-name-invalid')
 
     const currentName = this.value || this.getAttribute('placeholder')
-    const existingNames = Object.values(sites).map(site => site.config.name || site.manifest.name || site.manifest.short_name || new URL(site.manifest.scope).host)
+    const existingNames = Object.values(sites).map(site => site.config.name_override || site.config.name || site.manifest.name || site.manifest.short_name || new URL(site.manifest.scope).host)
 
     // If the name is already used for existing sites, this will cause problems
     if (existingNames.includes(currentName)) {
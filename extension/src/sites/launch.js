@@ -22,7 +22,7 @@ async function createInstanceList () {
 
   // Create a list element for every instance with handler that launches it
   for (const site of sites) {
-    const name = sanitizeString(site.config.name || site.manifest.name || site.manifest.short_name)
+    const name = sanitizeString(site.config.name_override || site.config.name || site.manifest.name || site.manifest.short_name)
     const url = settingsLaunchCurrentUrl ? documentUrl : undefined
 
     const siteElement = document.createElement('button')
@@ -188,7 +188,7 @@ async function createSiteList () {
     // Create grid view item
     const gridItem = gridTemplateElement.content.firstElementChild.cloneNode(true)
 
-    const siteName = sanitizeString(site.config.name || site.manifest.name || site.manifest.short_name) || new URL(site.manifest.scope).host
+    const siteName = sanitizeString(site.config.name_override || site.config.name || site.manifest.name || site.manifest.short_name) || new URL(site.manifest.scope).host
     const siteDescription = sanitizeString(site.config.description || site.manifest.description) || ''
     const siteIcon = site.config.icon_url || getIcon(buildIconList(site.manifest.icons), 64)
 
@@ -408,7 +408,7 @@ async function createSiteList () {
 
         const currentName = this.value || this.getAttribute('placeholder')
         const existingSites = sites.filter(elem => site.ulid !== elem.ulid)
-        const existingNames = existingSites.map(site => site.config.name || site.manifest.name || site.manifest.short_name || new URL(site.manifest.scope).host)
+        const existingNames = existingSites.map(site => site.config.name_override || site.config.name || site.manifest.name || site.manifest.short_name || new URL(site.manifest.scope).host)
 
         // If the name is already used for existing sites, this will cause problems
         if (existingNames.includes(currentName)) {
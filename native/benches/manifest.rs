@@ -0,0 +1,84 @@
+//! Benchmarks for manifest parsing and icon selection.
+//!
+//! Manifests are parsed on every install and update, and icons are selected
+//! for each required size, so both need to stay fast for interactive use.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use firefoxpwa::components::site::Site;
+use firefoxpwa::integrations::normalize_icons;
+use web_app_manifest::types::ImageSize;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+const ICONS: [usize; 3] = [1, 10, 100];
+
+/// Representative manifests of large and complex web apps.
+const MANIFESTS: [(&str, &str); 2] = [
+    ("social", include_str!("../fuzz/corpus/manifest_parser/social.json")),
+    ("video", include_str!("../fuzz/corpus/manifest_parser/video.json")),
+];
+
+const FORMATS: [(&str, &str); 4] = [
+    ("png", "image/png"),
+    ("webp", "image/webp"),
+    ("ico", "image/x-icon"),
+    ("svg", "image/svg+xml"),
+];
+
+/// Generates a manifest with icons of various sizes, formats and purposes.
+fn generate(count: usize) -> String {
+    let icons: Vec<_> = (0..count)
+        .map(|index| {
+            let (extension, mime) = FORMATS[index % FORMATS.len()];
+            let sizes = match extension {
+                "svg" => "any".to_owned(),
+                _ => {
+                    let size = 16 * (index % 32 + 1);
+                    format!("{size}x{size}")
+                }
+            };
+            let purpose = if index % 5 == 0 { "maskable" } else { "any" };
+
+            serde_json::json!({
+                "src": format!("/icons/{index}.{extension}"),
+                "sizes": sizes,
+                "type": mime,
+                "purpose": purpose,
+            })
+        })
+        .collect();
+
+    serde_json::json!({ "name": "Example", "start_url": "/", "icons": icons }).to_string()
+}
+
+fn bench_icons(c: &mut Criterion) {
+    let config = common::config();
+    let size = ImageSize::Fixed(256, 256);
+
+    let mut group = c.benchmark_group("normalize_icons");
+    for count in ICONS {
+        let manifest = Site::parse_manifest(&config, &generate(count)).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &manifest, |b, manifest| {
+            b.iter(|| normalize_icons(black_box(&manifest.icons), black_box(&size)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let config = common::config();
+
+    let mut group = c.benchmark_group("parse_manifest");
+    for (name, json) in MANIFESTS {
+        group.bench_with_input(BenchmarkId::from_parameter(name), json, |b, json| {
+            b.iter(|| Site::parse_manifest(black_box(&config), black_box(json)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_icons, bench_parse);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+//! Benchmarks for loading and writing the storage file.
+//!
+//! Storages are generated with a different number of profiles, each with the same number
+//! of web apps, to detect regressions as the storage schema grows.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use firefoxpwa::components::profile::ProfileBuilder;
+use firefoxpwa::directories::ProjectDirs;
+use firefoxpwa::storage::Storage;
+use tempfile::TempDir;
+use ulid::Ulid;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+const PROFILES: [usize; 3] = [10, 100, 1000];
+const SITES_PER_PROFILE: usize = 10;
+
+fn generate(profiles: usize) -> Storage {
+    let template = common::site(Ulid::nil());
+
+    let mut storage = Storage::default();
+    for index in 0..profiles {
+        let mut profile = ProfileBuilder::new().name(format!("Profile {index}")).build();
+
+        for _ in 0..SITES_PER_PROFILE {
+            let mut site = template.clone();
+            site.ulid = Ulid::new();
+            site.profile = profile.ulid;
+
+            profile.sites.push(site.ulid);
+            storage.sites.insert(site.ulid, site);
+        }
+
+        storage.profiles.insert(profile.ulid, profile);
+    }
+
+    storage
+}
+
+fn bench_storage(c: &mut Criterion) {
+    let temp = TempDir::new().unwrap();
+    let dirs = ProjectDirs::new_with_base(temp.path()).unwrap();
+
+    let mut group = c.benchmark_group("storage");
+    group.sample_size(20);
+
+    for profiles in PROFILES {
+        let storage = generate(profiles);
+
+        group.bench_with_input(BenchmarkId::new("write", profiles), &storage, |b, storage| {
+            b.iter(|| black_box(storage).write(&dirs).unwrap())
+        });
+
+        storage.write(&dirs).unwrap();
+        group.bench_function(BenchmarkId::new("load", profiles), |b| {
+            b.iter(|| black_box(Storage::load(&dirs).unwrap()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_storage);
+criterion_main!(benches);
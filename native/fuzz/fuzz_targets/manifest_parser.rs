@@ -0,0 +1,38 @@
+//! Fuzzes parsing and processing of web app manifests.
+//!
+//! Run with `cargo fuzz run manifest_parser` from the `native` directory.
+
+#![no_main]
+
+use std::sync::LazyLock;
+
+use firefoxpwa::components::site::{Site, SiteConfig, SiteConfigBuilder};
+use libfuzzer_sys::fuzz_target;
+
+/// Maximum number of icons that a parsed manifest may contain.
+const MAX_ICONS: usize = 10_000;
+
+static CONFIG: LazyLock<SiteConfig> = LazyLock::new(|| {
+    SiteConfigBuilder::new()
+        .manifest_url("https://example.com/app/manifest.json")
+        .document_url("https://example.com/app/")
+        .build()
+        .unwrap()
+});
+
+fuzz_target!(|data: &[u8]| {
+    // Manifests are always decoded as UTF-8 before they are parsed
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let Ok(manifest) = Site::parse_manifest(&CONFIG, json) else {
+        return;
+    };
+
+    assert!(manifest.icons.len() <= MAX_ICONS, "Parsed {} icons", manifest.icons.len());
+
+    // Successfully parsed manifests must survive a round-trip through their serialized form
+    let serialized = serde_json::to_string(&manifest).expect("Failed to serialize manifest");
+    Site::parse_manifest(&CONFIG, &serialized).expect("Failed to parse serialized manifest");
+});
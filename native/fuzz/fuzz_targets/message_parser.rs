@@ -0,0 +1,63 @@
+//! Fuzzes the decoder of native messaging messages received from the browser.
+//!
+//! Run with `cargo fuzz run message_parser` from the `native` directory.
+
+#![no_main]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use firefoxpwa::connector::{
+    ConnectionError, ConnectorRequest, get_max_message_size, read_message,
+};
+use libfuzzer_sys::fuzz_target;
+
+/// Size of the largest single allocation since it was last reset.
+static LARGEST: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocator that records the size of the largest single allocation.
+struct TrackingAllocator;
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LARGEST.fetch_max(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LARGEST.fetch_max(new_size, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+fuzz_target!(|data: &[u8]| {
+    let limit = get_max_message_size();
+    LARGEST.store(0, Ordering::Relaxed);
+
+    let mut input = data;
+    let result = read_message(&mut input);
+
+    // With the default maximum input length of libFuzzer, parsed JSON values stay
+    // well below the limit, so only a buffer allocated from the size prefix can exceed it
+    let largest = LARGEST.load(Ordering::Relaxed);
+    assert!(
+        largest <= limit,
+        "Allocated {largest} bytes, which exceeds the limit of {limit} bytes"
+    );
+
+    match result {
+        Ok(message) => {
+            // Requests are deserialized from the message, which may fail with a typed error
+            let _ = serde_json::from_value::<ConnectorRequest>(message);
+        }
+        Err(ConnectionError::Storage(_)) => panic!("Decoding a message must not access storage"),
+        Err(_) => {}
+    }
+});
@@ -1,14 +1,87 @@
+use std::env;
 use std::fs::OpenOptions;
-use std::process::exit;
+use std::path::{Path, PathBuf};
+use std::process::{ExitCode, exit};
 
-use anyhow::Result;
-use log::{LevelFilter, error};
+use anyhow::{Error, Result, bail};
+use cfg_if::cfg_if;
+use log::{LevelFilter, error, warn};
 use simplelog::{ColorChoice, CombinedLogger, Config, TermLogger, TerminalMode, WriteLogger};
+use tempfile::TempDir;
 
 #[rustfmt::skip]
-use firefoxpwa::{connector::Connection, directories::ProjectDirs};
+use firefoxpwa::{
+    connector::{self_test, Connection, ConnectionError, ConnectorResponse, PidFile},
+    console::defaults::Defaults,
+    directories::ProjectDirs,
+};
+
+/// Runs the connector self-test in a temporary user data directory.
+///
+/// Used by installers to verify that the connector works. Exits
+/// with code 0 on success, or prints the failing step and exits
+/// with code 1. System integration is disabled during the test,
+/// so it never creates shortcuts or registry entries on the host.
+fn run_self_test() -> Result<()> {
+    // Static builds ignore the user data variable, so the test would modify the real user data
+    if ProjectDirs::static_only() {
+        bail!("Self-test is not supported when directories are set at build time");
+    }
+
+    let temp = TempDir::new()?;
+
+    // Commands obtain directories on their own, so they need to be changed globally
+    // SAFETY: This runs at the start of the program, before any other threads exist
+    unsafe {
+        env::set_var("FFPWA_USERDATA", temp.path());
+        env::set_var("FIREFOXPWA_NO_INTEGRATIONS", "1");
+    }
+
+    let dirs = ProjectDirs::new()?;
+    let result = self_test(&dirs);
+    drop(temp);
+
+    if let Err(error) = result {
+        eprintln!("Self-test failed: {error:?}");
+        exit(1);
+    }
+
+    println!("Self-test passed");
+    Ok(())
+}
+
+/// Listens for connections on a Unix domain socket instead of stdin and stdout.
+fn listen(dirs: &ProjectDirs, debugmode: bool, socket: &Path) -> Result<(), ConnectionError> {
+    cfg_if! {
+        if #[cfg(platform_windows)] {
+            let _ = (dirs, debugmode, socket);
+            let message = "Socket mode is only supported on Unix systems".into();
+            Err(ConnectionError::Protocol { message })
+        } else {
+            Connection::listen(dirs, debugmode, socket)
+        }
+    }
+}
+
+fn main() -> Result<ExitCode> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.iter().any(|arg| arg == "--self-test") {
+        run_self_test()?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let value =
+        |name: &str| args.iter().position(|arg| arg == name).and_then(|index| args.get(index + 1));
+
+    // The PID file is written after the logger is initialized, so warnings are logged
+    let pidfile = value("--pid-file").map(PathBuf::from);
+    let socket = value("--socket").map(PathBuf::from);
+
+    // Defaults from the config file also apply to operations started by the extension
+    // SAFETY: The program is still single-threaded at this point
+    unsafe { Defaults::load(None)?.apply() };
 
-fn main() -> Result<()> {
     let dirs = ProjectDirs::new()?;
 
     let debugmode = dirs.userdata.join("DEBUG").exists();
@@ -22,10 +95,29 @@ fn main() -> Result<()> {
         WriteLogger::new(loglevel, Config::default(), logfile),
     ])?;
 
-    if let Err(error) = Connection::start(&dirs, debugmode) {
-        error!("{error:?}");
-        exit(1);
+    // Removed when dropped at the end of `main`, so failures must return instead of exiting
+    let _pidfile = pidfile.as_deref().map(PidFile::create).transpose()?;
+
+    let result = match socket {
+        Some(socket) => listen(&dirs, debugmode, &socket),
+        None => match Connection::start(&dirs, debugmode) {
+            Ok(ConnectorResponse::Error(_)) => return Ok(ExitCode::FAILURE),
+            Ok(_) => Ok(()),
+            Err(error) => Err(error),
+        },
+    };
+
+    if let Err(error) = result {
+        match error {
+            ConnectionError::Timeout => warn!("{error}"),
+            ConnectionError::Storage(_) => {
+                error!("{:?}", Error::new(error));
+                error!("Run `firefoxpwa storage verify` to check the storage for problems");
+            }
+            error => error!("{:?}", Error::new(error)),
+        }
+        return Ok(ExitCode::FAILURE);
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -6,15 +6,25 @@ use log::{LevelFilter, error};
 use simplelog::{ColorChoice, Config, TermLogger, TerminalMode};
 
 #[rustfmt::skip]
-use firefoxpwa::console::{App, Run};
+use firefoxpwa::console::{App, Run, defaults::Defaults, error::exit_code};
 
 fn main() -> Result<()> {
     TermLogger::init(LevelFilter::Info, Config::default(), TerminalMode::Mixed, ColorChoice::Auto)?;
 
     let app = App::parse();
-    if let Err(error) = app.run() {
+
+    // Defaults from the config file need to be applied before dispatching the command
+    let result = Defaults::load(app.config.as_deref()).and_then(|mut defaults| {
+        defaults.override_cafile(app.cafile.as_deref());
+
+        // SAFETY: The program is still single-threaded at this point
+        unsafe { defaults.apply() };
+        app.run()
+    });
+
+    if let Err(error) = result {
         error!("{error:?}");
-        exit(1);
+        exit(exit_code(&error));
     }
 
     Ok(())
@@ -1,19 +1,21 @@
 use std::ffi::OsStr;
+use std::fmt;
 use std::fs::remove_file;
 use std::os::windows::process::ExitStatusExt;
-use std::path::PathBuf;
-use std::process::{Command, ExitStatus};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
 
 use anyhow::{Context, Result, bail};
 use cfg_if::cfg_if;
-use log::{info, warn};
+use log::{debug, info, warn};
 use tempfile::Builder;
+use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_CANCELLED, WAIT_TIMEOUT};
 use windows::Win32::System::Com::{
     COINIT_APARTMENTTHREADED,
     COINIT_DISABLE_OLE1DDE,
     CoInitializeEx,
 };
-use windows::Win32::System::Threading::{GetExitCodeProcess, INFINITE, WaitForSingleObject};
+use windows::Win32::System::Threading::{GetExitCodeProcess, TerminateProcess, WaitForSingleObject};
 use windows::Win32::UI::Shell::{
     SEE_MASK_NOASYNC,
     SEE_MASK_NOCLOSEPROCESS,
@@ -23,14 +25,57 @@ use windows::Win32::UI::Shell::{
 use windows::core::{HSTRING, PCWSTR, w};
 use windows_registry::LOCAL_MACHINE;
 
-#[inline]
-const fn get_download_url() -> &'static str {
-    #[allow(unused_imports)]
-    use const_format::formatcp;
+use crate::error::CommandError;
+use crate::utils::{
+    Cancellation,
+    Progress,
+    compute_checksum,
+    construct_download_client,
+    download,
+    ensure_free_space,
+    prepare_temp_dir,
+};
+
+/// Minimum 7-Zip version that can reliably extract the runtime archive.
+const MINIMUM_VERSION: &str = "16.00";
+
+/// Parse a dot-separated version string into comparable numeric components.
+fn parse_version(version: &str) -> Vec<u32> {
+    version.split('.').map(|part| part.trim().parse().unwrap_or(0)).collect()
+}
+
+/// 7-Zip version that is installed by default, without the dot.
+const DEFAULT_VERSION: &str = "2600";
 
-    #[allow(dead_code)]
-    const VERSION: &str = "2600";
+/// Get the 7-Zip version to install, without the dot, such as `2600`.
+///
+/// Can be overwritten by the `FIREFOXPWA_7ZIP_VERSION` variable, so a newer release can
+/// be installed before the default is updated. Returns whether the version was overwritten.
+fn get_download_version() -> Result<(String, bool)> {
+    match std::env::var("FIREFOXPWA_7ZIP_VERSION") {
+        Ok(version) if !version.is_empty() => {
+            if !version.chars().all(|char| char.is_ascii_digit()) {
+                bail!("Invalid 7-Zip version {version}, expected a version like {DEFAULT_VERSION}");
+            }
+            Ok((version, true))
+        }
+        _ => Ok((DEFAULT_VERSION.into(), false)),
+    }
+}
+
+/// Get the expected SHA-256 checksum of the 7-Zip installer.
+///
+/// Can be set by the `FIREFOXPWA_7ZIP_SHA256` variable. It is required when
+/// the version is overwritten in non-interactive sessions.
+fn get_download_checksum() -> Option<String> {
+    std::env::var("FIREFOXPWA_7ZIP_SHA256")
+        .ok()
+        .map(|checksum| checksum.trim().to_lowercase())
+        .filter(|checksum| !checksum.is_empty())
+}
 
+#[inline]
+fn get_download_url(version: &str) -> String {
     cfg_if! {
         if #[cfg(target_arch = "x86")] {
             const ARCHITECTURE: &str = "";
@@ -43,11 +88,90 @@ const fn get_download_url() -> &'static str {
         }
     }
 
-    formatcp!("https://7-zip.org/a/7z{VERSION}{ARCHITECTURE}.exe")
+    format!("https://7-zip.org/a/7z{version}{ARCHITECTURE}.exe")
+}
+
+/// An error that happened when running a process as administrator.
+#[derive(Debug)]
+pub enum AdminError {
+    /// User declined the User Account Control prompt.
+    AdminPermissionDenied,
+
+    /// The process did not finish in time and was terminated.
+    Timeout,
+
+    /// Any other error when executing the process.
+    Other(std::io::Error),
+}
+
+impl fmt::Display for AdminError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AdminPermissionDenied => write!(
+                f,
+                "User declined the administrator permission request. 7-Zip installation was cancelled."
+            ),
+            Self::Timeout => write!(
+                f,
+                "Timed out waiting for the administrator process. 7-Zip installation was cancelled."
+            ),
+            Self::Other(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for AdminError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::AdminPermissionDenied | Self::Timeout => None,
+            Self::Other(error) => Some(error),
+        }
+    }
+}
+
+impl From<windows::core::Error> for AdminError {
+    fn from(error: windows::core::Error) -> Self {
+        // Declining the UAC prompt is reported as a cancelled or denied operation
+        let code = error.code();
+        if code == ERROR_CANCELLED.to_hresult() || code == ERROR_ACCESS_DENIED.to_hresult() {
+            return Self::AdminPermissionDenied;
+        }
+
+        Self::Other(error.into())
+    }
+}
+
+/// Get the 7-Zip multithreading switch used when extracting archives.
+///
+/// Can be overwritten by the `FIREFOXPWA_EXTRACT_THREADS` variable with the number
+/// of threads. By default, 7-Zip decides the number of threads from available CPUs.
+fn get_threads_switch() -> String {
+    let threads: Option<u32> =
+        std::env::var("FIREFOXPWA_EXTRACT_THREADS").ok().and_then(|threads| threads.parse().ok());
+
+    match threads {
+        Some(threads @ 1..) => format!("-mmt{threads}"),
+        _ => "-mmt=on".into(),
+    }
+}
+
+/// Get the timeout for processes running as administrator in milliseconds.
+///
+/// Can be overwritten by the `FIREFOXPWA_ADMIN_TIMEOUT` variable in seconds.
+/// Prevents blocking forever if the User Account Control prompt is never answered.
+fn get_admin_timeout() -> u32 {
+    const DEFAULT_TIMEOUT: u32 = 300;
+
+    let seconds = std::env::var("FIREFOXPWA_ADMIN_TIMEOUT")
+        .ok()
+        .and_then(|timeout| timeout.parse().ok())
+        .unwrap_or(DEFAULT_TIMEOUT);
+
+    seconds.saturating_mul(1000)
 }
 
 #[inline]
-fn run_as_admin<S: AsRef<OsStr>>(cmd: S) -> std::io::Result<ExitStatus> {
+fn run_as_admin<S: AsRef<OsStr>>(cmd: S) -> Result<ExitStatus, AdminError> {
     unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED | COINIT_DISABLE_OLE1DDE).ok()? };
 
     let mut code = 1;
@@ -67,10 +191,14 @@ fn run_as_admin<S: AsRef<OsStr>>(cmd: S) -> std::io::Result<ExitStatus> {
         let process = { sei.hProcess };
 
         if process.is_invalid() {
-            return Err(std::io::Error::last_os_error());
+            return Err(AdminError::Other(std::io::Error::last_os_error()));
         };
 
-        WaitForSingleObject(process, INFINITE);
+        if WaitForSingleObject(process, get_admin_timeout()) == WAIT_TIMEOUT {
+            let _ = TerminateProcess(process, 1);
+            return Err(AdminError::Timeout);
+        }
+
         GetExitCodeProcess(process, &mut code)?;
     };
 
@@ -81,15 +209,22 @@ fn run_as_admin<S: AsRef<OsStr>>(cmd: S) -> std::io::Result<ExitStatus> {
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct _7Zip {
     pub version: Option<String>,
-    executable: Option<PathBuf>,
+    pub executable: Option<PathBuf>,
 }
 
 impl _7Zip {
     pub fn new() -> Result<Self> {
-        match Self::new_from_registry().context("Failed to search 7-Zip in registry")? {
-            registry if registry.version.is_some() => Ok(registry),
-            _ => Self::new_from_path().context("Failed to search 7-Zip in PATH variable"),
+        let registry = Self::new_from_registry().context("Failed to search 7-Zip in registry")?;
+        if registry.version.is_some() {
+            return Ok(registry);
         }
+
+        let winget = Self::new_from_winget().context("Failed to search 7-Zip in WinGet")?;
+        if winget.version.is_some() {
+            return Ok(winget);
+        }
+
+        Self::new_from_path().context("Failed to search 7-Zip in PATH variable")
     }
 
     fn new_from_registry() -> Result<Self> {
@@ -115,6 +250,37 @@ impl _7Zip {
         Ok(Self { version, executable })
     }
 
+    fn new_from_winget() -> Result<Self> {
+        const PACKAGE_ID: &str = "7zip.7-Zip";
+
+        // WinGet might not be available, which is the same as 7-Zip not being installed
+        let output = match Command::new("winget")
+            .args(["list", "--id", PACKAGE_ID, "--accept-source-agreements"])
+            .output()
+        {
+            Ok(output) if output.status.success() => output,
+            _ => return Ok(Self { version: None, executable: None }),
+        };
+
+        // The version is the column directly after the package ID in the table
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let version = stdout.lines().find_map(|line| {
+            let mut columns = line.split_whitespace().skip_while(|column| *column != PACKAGE_ID);
+            columns.nth(1).map(String::from)
+        });
+
+        let executable = std::env::var_os("ProgramFiles")
+            .map(|directory| PathBuf::from(directory).join("7-Zip").join("7z.exe"))
+            .filter(|executable| executable.is_file());
+
+        match (version, executable) {
+            (Some(version), Some(executable)) => {
+                Ok(Self { version: Some(version), executable: Some(executable) })
+            }
+            _ => Ok(Self { version: None, executable: None }),
+        }
+    }
+
     fn new_from_path() -> Result<Self> {
         let exe = std::env::var_os("PATH").and_then(|paths| {
             std::env::split_paths(&paths)
@@ -128,15 +294,63 @@ impl _7Zip {
                 .next()
         });
 
+        // The version is printed in the banner, for example `7-Zip 23.01 (x64) : Copyright ...`
+        // If it cannot be determined, the version is unknown and treated as too old
+        let version = |exe: &PathBuf| -> Option<String> {
+            let output = Command::new(exe).output().ok()?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let banner = stdout.lines().find(|line| line.starts_with("7-Zip"))?;
+            let version = banner.split_whitespace().find(|part| part.starts_with(char::is_numeric));
+            version.map(String::from)
+        };
+
         match exe {
-            Some(exe) => Ok(Self { version: Some("0.0.0".into()), executable: Some(exe) }),
+            Some(exe) => Ok(Self {
+                version: Some(version(&exe).unwrap_or_else(|| "0.0.0".into())),
+                executable: Some(exe),
+            }),
             None => Ok(Self { version: None, executable: None }),
         }
     }
 
-    pub fn install(self) -> Result<()> {
+    /// Check whether the installed 7-Zip is older than the minimum supported version.
+    ///
+    /// If 7-Zip is not installed at all, it is not considered outdated.
+    pub fn is_outdated(&self) -> bool {
+        let minimum = parse_version(MINIMUM_VERSION);
+        self.version.as_deref().is_some_and(|version| parse_version(version) < minimum)
+    }
+
+    /// Make sure the installed 7-Zip is not older than the minimum supported version.
+    ///
+    /// Returns a clear error if it is outdated, so the caller can offer to upgrade
+    /// it using the [`_7Zip::install`] function. If 7-Zip is not installed at all,
+    /// nothing is checked.
+    pub fn ensure_minimum(self) -> Result<Self> {
+        if let Some(version) = &self.version
+            && self.is_outdated()
+        {
+            bail!("7-Zip {version} is too old, please upgrade it to at least {MINIMUM_VERSION}");
+        }
+
+        Ok(self)
+    }
+
+    /// Download and run the 7-Zip installer.
+    ///
+    /// Installers of custom versions without a checksum in the `FIREFOXPWA_7ZIP_SHA256`
+    /// variable are passed to `accept_checksum` with their version and computed checksum.
+    /// The installer runs as administrator, so it is only executed if it returns `true`.
+    /// Each stage and the download percentage are reported to the progress callback.
+    pub fn install(
+        self,
+        cancellation: Option<&Cancellation>,
+        progress: Option<&Progress>,
+        accept_checksum: impl FnOnce(&str, &str) -> Result<bool>,
+    ) -> Result<()> {
         const TEMP_FILE_ERROR: &str = "Failed to create a temporary file";
         const DOWNLOAD_ERROR: &str = "Failed to download the 7-Zip installer";
+        const VERIFY_ERROR: &str = "Failed to verify the 7-Zip installer";
         const EXEC_ERROR: &str = "Failed to execute the 7-Zip installer";
         const CLEANUP_ERROR: &str = "Failed to clean up the 7-Zip installer";
 
@@ -146,23 +360,58 @@ impl _7Zip {
         warn!("7-Zip License: https://7-zip.org/license.txt");
         warn!("7-Zip Website: https://7-zip.org/");
 
+        const REQUIRED_SPACE: u64 = 16 * 1024 * 1024;
+
         let mut installer = Builder::new()
             .prefix("firefoxpwa-7zip-")
             .suffix(".exe")
-            .tempfile()
+            .tempfile_in(prepare_temp_dir(REQUIRED_SPACE)?)
             .context(TEMP_FILE_ERROR)?;
 
-        info!("Downloading the 7-Zip installer");
-        let mut response = reqwest::blocking::get(get_download_url()).context(DOWNLOAD_ERROR)?;
-        (response.copy_to(&mut installer.as_file_mut())).context(DOWNLOAD_ERROR)?;
+        let (version, overwritten) = get_download_version()?;
+        let expected = get_download_checksum();
+
+        Progress::stage(progress, "Downloading the 7-Zip installer")?;
+        let client = construct_download_client()?;
+        let url = get_download_url(&version);
+        download(&client, &url, installer.as_file_mut(), cancellation, progress)
+            .context(DOWNLOAD_ERROR)?;
+
+        // The installer runs as administrator, so a custom version must not be trusted silently
+        if expected.is_some() || overwritten {
+            Progress::stage(progress, "Verifying the 7-Zip installer")?;
+            let actual = compute_checksum(installer.path()).context(VERIFY_ERROR)?;
+
+            match expected {
+                Some(expected) if expected != actual => {
+                    bail!("7-Zip installer checksum mismatch: expected {expected}, got {actual}")
+                }
+                Some(_) => {}
+                None => {
+                    warn!("7-Zip {version} installer has SHA-256 checksum {actual}");
+                    warn!("Please compare it with the checksum published on the 7-Zip website");
+
+                    if !accept_checksum(&version, &actual)? {
+                        bail!(CommandError::Cancelled);
+                    }
+                }
+            }
+        }
+
         let (_, path) = installer.keep().context(DOWNLOAD_ERROR)?;
 
-        info!("Executing the 7-Zip installer");
+        Progress::stage(progress, "Executing the 7-Zip installer")?;
         warn!("Please follow the installer to install 7-Zip");
         warn!("You might need to accept the User Account Control prompt");
 
-        if !run_as_admin(&path).context(EXEC_ERROR)?.success() {
-            bail!(EXEC_ERROR)
+        match run_as_admin(&path) {
+            Ok(status) if status.success() => {}
+            Ok(_) => bail!(EXEC_ERROR),
+            Err(error @ (AdminError::AdminPermissionDenied | AdminError::Timeout)) => {
+                let _ = remove_file(&path);
+                return Err(error.into());
+            }
+            Err(error) => return Err(error).context(EXEC_ERROR),
         }
 
         remove_file(path).context(CLEANUP_ERROR)?;
@@ -171,13 +420,74 @@ impl _7Zip {
         Ok(())
     }
 
+    /// Extract the archive into the directory using multiple threads.
+    ///
+    /// Before extracting, the uncompressed size of the archive is compared with
+    /// free space in the directory, so extraction does not fail partway through.
+    pub fn extract(&self, archive: &str, directory: &Path) -> Result<ExitStatus> {
+        let size = self.uncompressed_size(archive).context("Failed to list the archive")?;
+        ensure_free_space(directory, size)?;
+
+        let output = format!("-o{}", directory.display());
+        self.run(vec!["x", &get_threads_switch(), archive, &output])
+    }
+
+    /// Get the total uncompressed size of all files in the archive.
+    ///
+    /// Uses the technical listing format, where each file has its own `Size` line.
+    pub fn uncompressed_size(&self, archive: &str) -> Result<u64> {
+        let executable = match &self.executable {
+            Some(executable) => executable,
+            None => bail!(CommandError::SevenZipNotInstalled),
+        };
+
+        let output =
+            Command::new(executable).args(["l", "-slt", archive]).stdin(Stdio::null()).output()?;
+        if !output.status.success() {
+            bail!("7-Zip exited with an error when listing the archive");
+        }
+
+        let size = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.strip_prefix("Size = "))
+            .filter_map(|size| size.trim().parse::<u64>().ok())
+            .sum();
+
+        Ok(size)
+    }
+
+    /// Run 7-Zip with the arguments and wait for it to finish.
+    ///
+    /// The output is captured and written to the log instead of the terminal,
+    /// because stdout is used for native messaging when running in the connector.
+    /// It is logged at the debug level, except for errors when 7-Zip fails.
     #[inline]
     pub fn run(&self, args: Vec<&str>) -> Result<ExitStatus> {
         let executable = match &self.executable {
             Some(executable) => executable,
-            None => bail!("7-Zip is currently not installed"),
+            None => bail!(CommandError::SevenZipNotInstalled),
         };
 
-        Ok(Command::new(executable).args(args).status()?)
+        let output = Command::new(executable).args(args).stdin(Stdio::null()).output()?;
+
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if !line.trim().is_empty() {
+                debug!("7-Zip: {line}");
+            }
+        }
+
+        for line in String::from_utf8_lossy(&output.stderr).lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if output.status.success() {
+                debug!("7-Zip: {line}");
+            } else {
+                warn!("7-Zip: {line}");
+            }
+        }
+
+        Ok(output.status)
     }
 }
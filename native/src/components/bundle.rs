@@ -0,0 +1,234 @@
+use std::fs::{File, copy as copy_file, create_dir_all, read_dir, remove_file};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+#[cfg(not(platform_windows))]
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use cfg_if::cfg_if;
+use fs_extra::dir::{CopyOptions, copy};
+use log::info;
+use serde::{Deserialize, Serialize};
+use tempfile::TempDir;
+use ulid::Ulid;
+
+use crate::components::runtime::Runtime;
+use crate::directories::ProjectDirs;
+use crate::storage::Storage;
+use crate::utils::temp_dir;
+
+const TEMP_DIR_ERROR: &str = "Failed to create a temporary directory";
+const COPY_ERROR: &str = "Failed to copy user data";
+const COMPRESS_ERROR: &str = "Failed to compress the bundle";
+const EXTRACT_ERROR: &str = "Failed to extract the bundle";
+const METADATA_ERROR: &str = "Failed to read bundle metadata";
+
+/// A version of the bundle format.
+///
+/// Must be increased whenever the bundle contents change in a way
+/// that older versions of the program cannot import.
+const BUNDLE_FORMAT: u32 = 1;
+
+/// Metadata stored in the root of the bundle.
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct BundleMetadata {
+    /// A version of the bundle format.
+    pub format: u32,
+
+    /// A version of the program that created the bundle.
+    pub version: String,
+
+    /// An operating system where the bundle was created.
+    pub platform: String,
+
+    /// A version of the runtime that was installed when creating the bundle.
+    pub runtime: Option<String>,
+}
+
+/// Export the whole user data directory into a single bundle.
+///
+/// The bundle contains the storage, all profiles and cached icons,
+/// but not the runtime, caches and logs, which can be recreated. On Windows,
+/// the bundle is compressed using 7-Zip, and on other platforms
+/// using the system `tar` command.
+///
+/// If a selection is provided, it is stored instead of the storage file,
+/// and only profiles with its web apps and icons of its web apps are exported.
+pub fn export(
+    dirs: &ProjectDirs,
+    target: &Path,
+    selection: Option<&Storage>,
+) -> Result<BundleMetadata> {
+    let staging = TempDir::new_in(temp_dir()).context(TEMP_DIR_ERROR)?;
+
+    let metadata = BundleMetadata {
+        format: BUNDLE_FORMAT,
+        version: env!("CARGO_PKG_VERSION").into(),
+        platform: std::env::consts::OS.into(),
+        runtime: Runtime::new(dirs)?.version,
+    };
+
+    let file = File::create(staging.path().join("bundle.json")).context(METADATA_ERROR)?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &metadata).context(METADATA_ERROR)?;
+
+    info!("Copying user data");
+    let userdata = staging.path().join("userdata");
+    create_dir_all(&userdata).context(COPY_ERROR)?;
+
+    for entry in read_dir(&dirs.userdata).context(COPY_ERROR)? {
+        let path = entry.context(COPY_ERROR)?.path();
+
+        // The runtime, its cache and logs are not needed to restore the installation
+        if path.file_name().is_some_and(|name| name == "runtime" || name == "cache")
+            || path.extension().is_some_and(|extension| extension == "log")
+        {
+            continue;
+        }
+
+        let name = path.file_name().unwrap_or_default();
+        if let Some(selection) = selection
+            && (name == "profiles" || name == "icons")
+        {
+            copy_selected(&path, &userdata.join(name), selection).context(COPY_ERROR)?;
+        } else if path.is_dir() {
+            copy(&path, &userdata, &CopyOptions::new()).context(COPY_ERROR)?;
+        } else {
+            copy_file(&path, userdata.join(name)).context(COPY_ERROR)?;
+        }
+    }
+
+    if let Some(selection) = selection {
+        let file = File::create(userdata.join("config.json")).context(COPY_ERROR)?;
+        serde_json::to_writer(BufWriter::new(file), selection).context(COPY_ERROR)?;
+    }
+
+    info!("Compressing the bundle");
+    if target.exists() {
+        remove_file(target).context(COMPRESS_ERROR)?;
+    }
+    compress(staging.path(), target).context(COMPRESS_ERROR)?;
+
+    Ok(metadata)
+}
+
+/// Copy only directories of profiles and web apps from the selection.
+///
+/// Profiles without any selected web apps are skipped, so the data
+/// of unrelated web apps is not included in the bundle.
+fn copy_selected(source: &Path, target: &Path, selection: &Storage) -> Result<()> {
+    create_dir_all(target)?;
+
+    for entry in read_dir(source)? {
+        let path = entry?.path();
+
+        let ulid = path.file_name().and_then(|name| name.to_str()).map(Ulid::from_string);
+        let Some(Ok(ulid)) = ulid else { continue };
+
+        let profile = selection.profiles.get(&ulid).filter(|profile| !profile.sites.is_empty());
+        if selection.sites.contains_key(&ulid) || profile.is_some() {
+            copy(&path, target, &CopyOptions::new())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Import the whole user data directory from a bundle.
+///
+/// Refuses bundles with an incompatible format or from a different
+/// operating system. Existing user data files are overwritten, but
+/// the installed runtime is kept.
+pub fn import(dirs: &ProjectDirs, source: &Path) -> Result<BundleMetadata> {
+    let staging = TempDir::new_in(temp_dir()).context(TEMP_DIR_ERROR)?;
+
+    info!("Extracting the bundle");
+    extract(source, staging.path()).context(EXTRACT_ERROR)?;
+
+    let file = File::open(staging.path().join("bundle.json")).context(METADATA_ERROR)?;
+    let metadata: BundleMetadata =
+        serde_json::from_reader(BufReader::new(file)).context(METADATA_ERROR)?;
+
+    if metadata.format != BUNDLE_FORMAT {
+        bail!(
+            "Bundle format {} is not supported, expected format {}",
+            metadata.format,
+            BUNDLE_FORMAT
+        );
+    }
+
+    if metadata.platform != std::env::consts::OS {
+        bail!("Bundle was created on {} and cannot be imported here", metadata.platform);
+    }
+
+    info!("Copying user data");
+    let mut options = CopyOptions::new();
+    options.content_only = true;
+    options.overwrite = true;
+
+    create_dir_all(&dirs.userdata).context(COPY_ERROR)?;
+    copy(staging.path().join("userdata"), &dirs.userdata, &options).context(COPY_ERROR)?;
+
+    Ok(metadata)
+}
+
+fn compress(source: &Path, target: &Path) -> Result<()> {
+    let success;
+
+    cfg_if! {
+        if #[cfg(platform_windows)] {
+            use crate::components::_7zip::_7Zip;
+
+            let source = source.join("*").display().to_string();
+            let target = target.display().to_string();
+
+            let _7zip = _7Zip::new()?.ensure_minimum()?;
+            success = _7zip.run(vec!["a", "-t7z", &target, &source])?.success();
+        } else {
+            success = Command::new("tar")
+                .arg("-czf")
+                .arg(target)
+                .arg("-C")
+                .arg(source)
+                .arg(".")
+                .status()?
+                .success();
+        }
+    }
+
+    if !success {
+        bail!("Archiver exited with an error");
+    }
+
+    Ok(())
+}
+
+fn extract(source: &Path, target: &Path) -> Result<()> {
+    let success;
+
+    cfg_if! {
+        if #[cfg(platform_windows)] {
+            use crate::components::_7zip::_7Zip;
+
+            let source = source.display().to_string();
+            let target = format!("-o{}", target.display());
+
+            let _7zip = _7Zip::new()?.ensure_minimum()?;
+            success = _7zip.run(vec!["x", &source, &target])?.success();
+        } else {
+            success = Command::new("tar")
+                .arg("-xzf")
+                .arg(source)
+                .arg("-C")
+                .arg(target)
+                .status()?
+                .success();
+        }
+    }
+
+    if !success {
+        bail!("Archiver exited with an error");
+    }
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 #[cfg(platform_windows)]
 pub mod _7zip;
 
+pub mod bundle;
 pub mod profile;
 pub mod runtime;
 pub mod site;
@@ -1,6 +1,9 @@
-use std::fs::{create_dir_all, remove_dir_all};
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::{self, create_dir_all, read_dir, read_to_string, remove_dir_all};
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use fs_extra::dir::{CopyOptions, copy};
 use log::info;
 use serde::{Deserialize, Serialize};
@@ -8,6 +11,29 @@ use ulid::Ulid;
 
 use crate::directories::ProjectDirs;
 
+const EXTENSIONS_READ_ERROR: &str = "Failed to read profile extensions";
+const EXTENSIONS_PARSE_ERROR: &str = "Failed to parse profile extensions";
+
+/// An extension installed in a profile.
+///
+/// Name, version and status are only known once the extension
+/// has been installed by the runtime. Extensions that were added,
+/// but the profile has not been launched since, only have an ID.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ProfileExtension {
+    /// An extension ID.
+    pub id: String,
+
+    /// An extension name.
+    pub name: Option<String>,
+
+    /// An extension version.
+    pub version: Option<String>,
+
+    /// Whether the extension is enabled.
+    pub active: Option<bool>,
+}
+
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
 pub struct Profile {
@@ -28,11 +54,70 @@ pub struct Profile {
     /// A profile description.
     pub description: Option<String>,
 
+    /// A template directory the profile was created from.
+    #[serde(default)]
+    pub template: Option<PathBuf>,
+
+    /// Custom profile tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
     /// A list of web app IDs installed within this profile.
     #[serde(default)]
     pub sites: Vec<Ulid>,
 }
 
+/// Builds a [`Profile`] with only the desired properties set.
+///
+/// All properties are optional. The profile ID is generated
+/// when the profile is built.
+#[derive(Debug, Default, Clone)]
+#[must_use = "the builder does nothing until `.build()` is called"]
+pub struct ProfileBuilder {
+    name: Option<String>,
+    description: Option<String>,
+    template: Option<PathBuf>,
+    tags: Vec<String>,
+}
+
+impl ProfileBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: impl Into<Option<String>>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<Option<String>>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn template(mut self, template: impl Into<Option<PathBuf>>) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn build(self) -> Profile {
+        Profile {
+            ulid: Ulid::new(),
+            name: self.name,
+            description: self.description,
+            template: self.template,
+            tags: self.tags,
+            sites: vec![],
+        }
+    }
+}
+
 impl Default for Profile {
     #[inline]
     fn default() -> Self {
@@ -40,15 +125,41 @@ impl Default for Profile {
             ulid: Ulid::nil(),
             name: Some("Default".into()),
             description: Some("Default profile for all web apps".into()),
+            template: None,
+            tags: vec![],
             sites: vec![],
         }
     }
 }
 
+impl fmt::Display for Profile {
+    /// Formats the profile as `<name> (id: <ulid>)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (id: {})", self.name.as_deref().unwrap_or("Unnamed"), self.ulid)
+    }
+}
+
 impl Profile {
     #[inline]
     pub fn new(name: Option<String>, description: Option<String>) -> Self {
-        Self { ulid: Ulid::new(), name, description, sites: vec![] }
+        ProfileBuilder::new().name(name).description(description).build()
+    }
+
+    /// Converts the profile into a stable public JSON representation.
+    ///
+    /// Unlike the storage format, this uses camelCase and does not
+    /// expose any internal fields. It is used for the `--json` output
+    /// of the console program.
+    ///
+    /// Fields: `id`, `name`, `description`, `tags`, `siteIds`.
+    pub fn to_public_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.ulid,
+            "name": self.name,
+            "description": self.description,
+            "tags": self.tags,
+            "siteIds": self.sites,
+        })
     }
 
     pub fn patch(&self, dirs: &ProjectDirs) -> Result<()> {
@@ -72,4 +183,129 @@ impl Profile {
         info!("Profile patched!");
         Ok(())
     }
+
+    /// Lists extensions installed in the profile by the user.
+    ///
+    /// Installed extensions are read from the runtime's `extensions.json` database,
+    /// while pending ones are read from XPI files in the `extensions` directory.
+    pub fn extensions(&self, dirs: &ProjectDirs) -> Result<Vec<ProfileExtension>> {
+        #[derive(Deserialize)]
+        struct Database {
+            #[serde(default)]
+            addons: Vec<DatabaseAddon>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct DatabaseAddon {
+            id: String,
+            version: Option<String>,
+            location: Option<String>,
+            active: Option<bool>,
+            default_locale: Option<DatabaseLocale>,
+        }
+
+        #[derive(Deserialize)]
+        struct DatabaseLocale {
+            name: Option<String>,
+        }
+
+        let profile = dirs.userdata.join("profiles").join(self.ulid.to_string());
+        let mut extensions = vec![];
+
+        // Only extensions from the profile location were installed by the user
+        let database = profile.join("extensions.json");
+        if database.exists() {
+            let data = read_to_string(database).context(EXTENSIONS_READ_ERROR)?;
+            let database: Database = serde_json::from_str(&data).context(EXTENSIONS_PARSE_ERROR)?;
+
+            extensions.extend(
+                database
+                    .addons
+                    .into_iter()
+                    .filter(|addon| addon.location.as_deref() == Some("app-profile"))
+                    .map(|addon| ProfileExtension {
+                        id: addon.id,
+                        name: addon.default_locale.and_then(|locale| locale.name),
+                        version: addon.version,
+                        active: addon.active,
+                    }),
+            );
+        }
+
+        // Extensions that have not been installed yet only exist as XPI files
+        let directory = profile.join("extensions");
+        if directory.exists() {
+            for entry in read_dir(directory).context(EXTENSIONS_READ_ERROR)? {
+                let path = entry.context(EXTENSIONS_READ_ERROR)?.path();
+
+                if path.extension() != Some(OsStr::new("xpi")) {
+                    continue;
+                }
+
+                let Some(id) = path.file_stem().and_then(OsStr::to_str) else {
+                    continue;
+                };
+
+                if !extensions.iter().any(|extension| extension.id == id) {
+                    extensions.push(ProfileExtension {
+                        id: id.into(),
+                        name: None,
+                        version: None,
+                        active: None,
+                    });
+                }
+            }
+        }
+
+        Ok(extensions)
+    }
+
+    /// Adds an extension XPI file to the profile.
+    ///
+    /// The runtime requires the file to be named after the extension ID,
+    /// and installs it the next time the profile is launched.
+    pub fn add_extension(&self, dirs: &ProjectDirs, xpi: &Path, id: &str) -> Result<()> {
+        validate_extension_id(id)?;
+
+        let profile = dirs.userdata.join("profiles").join(self.ulid.to_string());
+        let directory = profile.join("extensions");
+
+        info!("Adding the extension");
+        create_dir_all(&directory).context("Failed to create extensions directory")?;
+        fs::copy(xpi, directory.join(format!("{id}.xpi"))).context("Failed to copy extension")?;
+
+        info!("Extension added!");
+        Ok(())
+    }
+}
+
+/// Check whether the string is a valid extension ID, such as `name@domain` or `{uuid}`.
+///
+/// The ID is used as a file name, so this also ensures that it never contains
+/// any path separators that would place the extension outside the profile.
+#[must_use = "an invalid extension ID must not be used"]
+pub fn validate_extension_id(id: &str) -> Result<()> {
+    let email = |id: &str| match id.split_once('@') {
+        Some((name, domain)) => {
+            let valid = |char: char| char.is_ascii_alphanumeric() || "-._".contains(char);
+            !domain.is_empty() && name.chars().chain(domain.chars()).all(valid)
+        }
+        None => false,
+    };
+
+    let uuid = |id: &str| match id.strip_prefix('{').and_then(|id| id.strip_suffix('}')) {
+        Some(uuid) => {
+            let groups: Vec<&str> = uuid.split('-').collect();
+            groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+                && groups.iter().all(|group| group.bytes().all(|char| char.is_ascii_hexdigit()))
+        }
+        None => false,
+    };
+
+    if !email(id) && !uuid(id) {
+        bail!("Invalid extension ID: {id}, expected the name@domain or {{uuid}} format");
+    }
+
+    Ok(())
 }
@@ -12,6 +12,17 @@ use tempfile::{NamedTempFile, TempDir};
 
 use crate::components::site::Site;
 use crate::directories::ProjectDirs;
+#[cfg(not(feature = "immutable-runtime"))]
+use crate::utils::{
+    Cancellation,
+    Progress,
+    compute_checksum,
+    construct_download_client,
+    download,
+    ensure_free_space,
+    network_error,
+    prepare_temp_dir,
+};
 
 // TODO: Remove this constant and implement variable firefox path into user documentation
 pub const FFOX: &str = "/usr/lib/firefox/";
@@ -101,6 +112,30 @@ cfg_if! {
 const UNSUPPORTED_PLATFORM_ERROR: &str =
     "Cannot install runtime: Unsupported operating system or architecture!";
 
+/// Get a command for installing Firefox with the system package manager.
+///
+/// The distribution is detected from the `/etc/os-release` file, using
+/// both `ID` and `ID_LIKE` fields to also support derived distributions.
+#[cfg(platform_linux)]
+pub fn get_package_manager_command() -> Option<&'static str> {
+    let release = std::fs::read_to_string("/etc/os-release").ok()?;
+
+    release
+        .lines()
+        .filter_map(|line| line.strip_prefix("ID=").or_else(|| line.strip_prefix("ID_LIKE=")))
+        .flat_map(|value| value.trim_matches(['"', '\'']).split_whitespace())
+        .find_map(|id| match id.to_ascii_lowercase().as_str() {
+            "debian" | "ubuntu" | "linuxmint" | "pop" => Some("sudo apt install firefox"),
+            "fedora" | "rhel" | "centos" => Some("sudo dnf install firefox"),
+            "arch" | "manjaro" | "endeavouros" => Some("sudo pacman -S firefox"),
+            "opensuse" | "suse" => Some("sudo zypper install MozillaFirefox"),
+            "alpine" => Some("sudo apk add firefox"),
+            "void" => Some("sudo xbps-install -S firefox"),
+            "gentoo" => Some("sudo emerge www-client/firefox"),
+            _ => None,
+        })
+}
+
 fn remove_dir_contents<P: AsRef<Path>>(path: P) -> IoResult<()> {
     if !path.as_ref().exists() {
         return Ok(());
@@ -120,35 +155,122 @@ fn remove_dir_contents<P: AsRef<Path>>(path: P) -> IoResult<()> {
     Ok(())
 }
 
-#[inline]
-fn get_download_url() -> &'static str {
-    #[allow(unused_imports)]
-    use const_format::concatcp;
+/// Get the key under which the latest version of a runtime
+/// release channel is listed in Mozilla product details.
+fn get_channel_key(channel: &str) -> Result<&'static str> {
+    match channel {
+        "release" => Ok("LATEST_FIREFOX_VERSION"),
+        "beta" => Ok("LATEST_FIREFOX_DEVEL_VERSION"),
+        "esr" => Ok("FIREFOX_ESR"),
+        _ => Err(anyhow!("Unknown runtime channel: {channel}")),
+    }
+}
 
-    #[allow(dead_code)]
-    const BASE_DOWNLOAD_URL: &str = "https://download.mozilla.org/?product=firefox-latest-ssl&os=";
+#[inline]
+fn get_release_url(release: &str) -> String {
+    format!("https://archive.mozilla.org/pub/firefox/releases/{release}")
+}
 
+/// Get the path of the runtime archive relative to the release directory.
+///
+/// This is the same path that is listed in the release checksums file.
+#[inline]
+fn get_archive_path(release: &str) -> String {
     cfg_if! {
         if #[cfg(all(platform_windows, target_arch = "x86"))] {
-            concatcp!(BASE_DOWNLOAD_URL, "win")
+            format!("win32/en-US/Firefox Setup {release}.exe")
         } else if #[cfg(all(platform_windows, target_arch = "x86_64"))] {
-            concatcp!(BASE_DOWNLOAD_URL, "win64")
+            format!("win64/en-US/Firefox Setup {release}.exe")
         } else if #[cfg(all(platform_windows, target_arch = "aarch64"))] {
-            concatcp!(BASE_DOWNLOAD_URL, "win64-aarch64")
+            format!("win64-aarch64/en-US/Firefox Setup {release}.exe")
         } else if #[cfg(all(platform_linux, target_arch = "x86"))] {
-            concatcp!(BASE_DOWNLOAD_URL, "linux")
+            format!("linux-i686/en-US/firefox-{release}.tar.xz")
         } else if #[cfg(all(platform_linux, target_arch = "x86_64"))] {
-            concatcp!(BASE_DOWNLOAD_URL, "linux64")
+            format!("linux-x86_64/en-US/firefox-{release}.tar.xz")
         } else if #[cfg(all(platform_linux, target_arch = "aarch64"))] {
-            concatcp!(BASE_DOWNLOAD_URL, "linux64-aarch64")
+            format!("linux-aarch64/en-US/firefox-{release}.tar.xz")
         } else if #[cfg(platform_macos)] {
-            concatcp!(BASE_DOWNLOAD_URL, "osx")
+            format!("mac/en-US/Firefox {release}.dmg")
         } else {
+            let _ = release;
             panic!("{}", UNSUPPORTED_PLATFORM_ERROR);
         }
     }
 }
 
+/// Encode the archive path so it can be used in a download URL.
+#[inline]
+fn encode_archive_path(path: &str) -> String {
+    path.split('/').map(|segment| urlencoding::encode(segment)).collect::<Vec<_>>().join("/")
+}
+
+/// Get the latest runtime release available in a release channel.
+///
+/// The release is obtained from Mozilla product details and may
+/// contain a suffix like `esr` that is not part of the installed version.
+#[cfg(not(feature = "immutable-runtime"))]
+pub fn get_latest_release(channel: &str) -> Result<String> {
+    const PRODUCT_DETAILS_URL: &str =
+        "https://product-details.mozilla.org/1.0/firefox_versions.json";
+    const VERSION_ERROR: &str = "Failed to get the latest runtime version";
+
+    let key = get_channel_key(channel)?;
+
+    let client = construct_download_client()?;
+    let response = client
+        .get(PRODUCT_DETAILS_URL)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(network_error)
+        .context(VERSION_ERROR)?;
+
+    let details: serde_json::Value = serde_json::from_reader(response).context(VERSION_ERROR)?;
+    let release = details[key].as_str().context(VERSION_ERROR)?;
+
+    Ok(release.into())
+}
+
+/// Get the published SHA-256 checksum of the runtime archive.
+///
+/// Fails if the checksums file cannot be downloaded or does not
+/// contain the archive, so the archive is never used unverified.
+#[cfg(not(feature = "immutable-runtime"))]
+fn get_archive_checksum(client: &reqwest::blocking::Client, release: &str) -> Result<String> {
+    const CHECKSUM_ERROR: &str = "Failed to get the runtime checksum";
+
+    let checksums = client
+        .get(format!("{}/SHA256SUMS", get_release_url(release)))
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(network_error)
+        .context(CHECKSUM_ERROR)?;
+
+    let path = get_archive_path(release);
+    checksums
+        .lines()
+        .filter_map(|line| line.split_once("  "))
+        .find(|(_, name)| *name == path)
+        .map(|(hash, _)| hash.to_ascii_lowercase())
+        .with_context(|| format!("Runtime {release} is not available for this platform"))
+}
+
+/// Check whether one runtime version is newer than another.
+///
+/// Only numeric components are compared, so pre-release suffixes
+/// like `b5` are treated as an additional component.
+#[must_use = "comparing versions has no side effects"]
+pub fn is_newer_version(version: &str, other: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .split(|char: char| !char.is_ascii_digit())
+            .filter_map(|component| component.parse().ok())
+            .collect()
+    };
+
+    parse(version) > parse(other)
+}
+
 #[non_exhaustive]
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Runtime {
@@ -182,6 +304,52 @@ impl Runtime {
         }
     }
 
+    /// Find an existing Firefox installation on macOS.
+    ///
+    /// Searches the standard `/Applications` directory and Homebrew Cask directories
+    /// for both Apple Silicon and Intel systems. If multiple installations are found,
+    /// the first one in this order is used.
+    ///
+    /// This is only meant for launching web apps when the runtime is not installed.
+    /// The returned runtime must never be installed, uninstalled or patched, as that
+    /// would modify the system installation.
+    #[cfg(platform_macos)]
+    pub fn new_from_system() -> Result<Option<Self>> {
+        let mut directories = vec![PathBuf::from("/Applications")];
+
+        for caskroom in ["/opt/homebrew/Caskroom/firefox", "/usr/local/Caskroom/firefox"] {
+            if let Ok(versions) = read_dir(caskroom) {
+                let mut versions: Vec<PathBuf> =
+                    versions.filter_map(Result::ok).map(|entry| entry.path()).collect();
+                versions.sort();
+                directories.extend(versions.into_iter().rev());
+            }
+        }
+
+        let mut runtimes = vec![];
+        for directory in directories {
+            let runtime = Self::new_in_directory(directory)?;
+            if runtime.version.is_some() {
+                runtimes.push(runtime);
+            }
+        }
+
+        if runtimes.len() > 1 {
+            warn!("Found multiple Firefox installations:");
+            for runtime in &runtimes {
+                warn!("- {}", runtime.executable.display());
+            }
+            warn!("Using the first one, install the runtime to use a dedicated installation");
+        }
+
+        if let Some(runtime) = runtimes.first() {
+            info!("Using the system Firefox installation: {}", runtime.executable.display());
+            warn!("The system installation is not patched, so some features might not work");
+        }
+
+        Ok(runtimes.into_iter().next())
+    }
+
     fn new_in_directory(directory: PathBuf) -> Result<Self> {
         let executable = {
             cfg_if! {
@@ -234,19 +402,42 @@ impl Runtime {
         Ok(Self { version, directory, executable, config })
     }
 
+    /// Download, verify and install a specific runtime release.
+    ///
+    /// The archive is verified against checksums published by Mozilla and
+    /// kept in the cache, so reinstalling the same release does not need to
+    /// download and verify it again.
+    ///
+    /// Each stage and the download percentage are reported to the progress callback.
     #[cfg(not(feature = "immutable-runtime"))]
-    pub fn install(self) -> Result<()> {
+    pub fn install(
+        self,
+        release: &str,
+        cancellation: Option<&Cancellation>,
+        progress: Option<&Progress>,
+    ) -> Result<()> {
+        use std::fs::{create_dir_all, read_to_string, write};
+
+        use anyhow::bail;
+        use fs_extra::dir::get_size;
+
         const TEMP_FILE_ERROR: &str = "Failed to create a temporary file";
         const DOWNLOAD_ERROR: &str = "Failed to download the runtime";
+        const VERIFY_ERROR: &str = "Failed to verify the runtime";
+        const CACHE_ERROR: &str = "Failed to cache the runtime";
         const EXTRACT_ERROR: &str = "Failed to extract the runtime";
         const COPY_ERROR: &str = "Failed to copy the runtime";
         const CLEANUP_ERROR: &str = "Failed to clean up the runtime";
 
+        // Both the archive and the extracted runtime need to fit into the temporary directory
+        const REQUIRED_SPACE: u64 = 1024 * 1024 * 1024;
+
+        let dirs = ProjectDirs::new()?;
+
         #[cfg(platform_linux)]
         {
             use crate::storage::Storage;
 
-            let dirs = ProjectDirs::new()?;
             let mut storage = Storage::load(&dirs)?;
 
             if storage.config.use_linked_runtime {
@@ -266,31 +457,62 @@ impl Runtime {
         warn!("Firefox Privacy Notice: https://www.mozilla.org/privacy/firefox/");
         warn!("Firefox Website: https://www.mozilla.org/firefox/");
 
-        info!("Downloading the runtime archive");
-        let mut archive = NamedTempFile::new().context(TEMP_FILE_ERROR)?;
-        let mut response = reqwest::blocking::get(get_download_url()).context(DOWNLOAD_ERROR)?;
-        (response.copy_to(&mut archive.as_file_mut())).context(DOWNLOAD_ERROR)?;
+        let temp = prepare_temp_dir(REQUIRED_SPACE)?;
+
+        let client = construct_download_client()?;
+        let checksum = get_archive_checksum(&client, release)?;
+
+        // Archive and its verified checksum are cached by the archive name
+        let cache = dirs.userdata.join("cache").join("runtime");
+        let path = get_archive_path(release);
+        let name = path.rsplit('/').next().unwrap_or(&path);
+        let cached = cache.join(name);
+        let cached_checksum = cache.join(format!("{name}.sha256"));
+
+        if cached.exists() && read_to_string(&cached_checksum).is_ok_and(|hash| hash == checksum) {
+            info!("Using the cached runtime archive");
+        } else {
+            create_dir_all(&cache).context(CACHE_ERROR)?;
+
+            // The archive is downloaded next to the cache, so the previous archive
+            // is kept until the new one is verified and can be moved into the cache
+            Progress::stage(progress, "Downloading the runtime archive")?;
+            let parent = cache.parent().unwrap_or(&cache);
+            let mut archive = NamedTempFile::new_in(parent).context(TEMP_FILE_ERROR)?;
+            let url = format!("{}/{}", get_release_url(release), encode_archive_path(&path));
+            download(&client, &url, archive.as_file_mut(), cancellation, progress)
+                .context(DOWNLOAD_ERROR)?;
+
+            Progress::stage(progress, "Verifying the runtime archive")?;
+            let actual = compute_checksum(archive.path()).context(VERIFY_ERROR)?;
+            if actual != checksum {
+                bail!("Runtime archive checksum mismatch: expected {checksum}, got {actual}");
+            }
 
-        // Path to downloaded archive
-        let (_, archive) = archive.keep().context(DOWNLOAD_ERROR)?;
-        let archive = archive.display().to_string();
+            // Only the most recent archive is kept to save space
+            remove_dir_contents(&cache).context(CACHE_ERROR)?;
+            archive.persist(&cached).context(CACHE_ERROR)?;
+            write(&cached_checksum, &checksum).context(CACHE_ERROR)?;
+        }
+
+        // Path to verified archive
+        let archive = cached.display().to_string();
 
         // Path to extracted archive
-        let extracted = TempDir::new().context(TEMP_FILE_ERROR)?;
+        let extracted = TempDir::new_in(&temp).context(TEMP_FILE_ERROR)?;
         let extracted = extracted.path().display().to_string();
 
         // Path to specific directory inside archive and its destination
         let mut source = PathBuf::from(&extracted);
         let destination = self.directory;
 
-        info!("Extracting the runtime archive");
+        Progress::stage(progress, "Extracting the runtime archive")?;
         cfg_if! {
             if #[cfg(platform_windows)] {
-                use anyhow::bail;
                 use crate::components::_7zip::_7Zip;
 
-                let _7zip = _7Zip::new()?;
-                let success = _7zip.run(vec!["x", &archive, &format!("-o{}", &extracted)]).context(EXTRACT_ERROR)?.success();
+                let _7zip = _7Zip::new()?.ensure_minimum()?;
+                let success = _7zip.extract(&archive, &source).context(EXTRACT_ERROR)?.success();
                 if !success { bail!(EXTRACT_ERROR) }
 
                 source.push("core");
@@ -329,11 +551,15 @@ impl Runtime {
         let mut options = CopyOptions::new();
         options.content_only = true;
 
-        info!("Copying the runtime");
+        // The previous runtime is removed first, so only the difference needs to fit
+        let required = get_size(&source).context(COPY_ERROR)?;
+        let existing = get_size(&destination).unwrap_or_default();
+        ensure_free_space(&destination, required.saturating_sub(existing))?;
+
+        Progress::stage(progress, "Copying the runtime")?;
         remove_dir_contents(&destination).context(CLEANUP_ERROR)?;
         copy(&source, &destination, &options).context(COPY_ERROR)?;
 
-        remove_file(archive).context(CLEANUP_ERROR)?;
         remove_dir_all(extracted).context(CLEANUP_ERROR)?;
 
         info!("Runtime installed!");
@@ -1,11 +1,18 @@
 use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
 use std::process::Child;
+use std::time::SystemTime;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow, bail};
 use data_url::DataUrl;
-use log::info;
+use log::{info, warn};
 use reqwest::blocking::Client;
+use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use ulid::Ulid;
 use url::Url;
 pub use web_app_manifest::WebAppManifest as SiteManifest;
@@ -15,12 +22,36 @@ use web_app_manifest::types::{ImagePurpose, ImageSize, Url as ManifestUrl};
 use crate::components::runtime::Runtime;
 use crate::directories::ProjectDirs;
 use crate::storage::Config;
-use crate::utils::sanitize_string;
+use crate::utils::{network_error, sanitize_string};
 
 const DOWNLOAD_ERROR: &str = "Failed to download web app manifest";
 const DATA_URL_ERROR: &str = "Failed to process web app manifest data URL";
 const PARSE_ERROR: &str = "Failed to parse web app manifest";
 const INVALID_URL: &str = "Web app without valid absolute URL is not possible";
+const FALLBACK_ICON_ERROR: &str = "Failed to find a fallback icon";
+
+/// Contains a custom window geometry of the web app.
+///
+/// Sizes and positions are in CSS pixels of the screen. Unset fields use
+/// the default Firefox behavior. If the position is not on any connected
+/// screen, the runtime moves the window to the nearest visible area.
+#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq, Clone)]
+pub struct SiteWindow {
+    #[serde(default)]
+    pub width: Option<u32>,
+
+    #[serde(default)]
+    pub height: Option<u32>,
+
+    #[serde(default)]
+    pub x: Option<i32>,
+
+    #[serde(default)]
+    pub y: Option<i32>,
+
+    #[serde(default)]
+    pub maximized: bool,
+}
 
 /// Contains configuration for the web app.
 ///
@@ -36,6 +67,13 @@ pub struct SiteConfig {
     /// A custom web app name.
     pub name: Option<String>,
 
+    /// A custom web app display name.
+    ///
+    /// Takes precedence over all other names and is never
+    /// changed by manifest updates or web app name changes.
+    #[serde(default)]
+    pub name_override: Option<String>,
+
     /// A custom web app description.
     pub description: Option<String>,
 
@@ -45,6 +83,13 @@ pub struct SiteConfig {
     /// A custom web app icon URL.
     pub icon_url: Option<Url>,
 
+    /// A custom web app icon file.
+    ///
+    /// Takes precedence over the icon URL and all manifest
+    /// icons, so no icons are downloaded when it is set.
+    #[serde(default)]
+    pub icon_override: Option<PathBuf>,
+
     /// Direct URL of the site's main document.
     pub document_url: Url,
 
@@ -91,6 +136,424 @@ pub struct SiteConfig {
     /// Whether the web app should be launched on the browser launch.
     #[serde(default)]
     pub launch_on_browser: bool,
+
+    /// Whether the web app should only have a single window.
+    ///
+    /// When launching a web app that is already running, its
+    /// existing window is focused instead of opening a new one.
+    #[serde(default)]
+    pub single_instance: bool,
+
+    /// Whether the web app is pinned to the taskbar or the Dock.
+    ///
+    /// Used to restore the pin when repairing system integration.
+    #[serde(default)]
+    pub pinned: bool,
+
+    /// A group of the web app.
+    ///
+    /// A free-form name used to organize web apps when listing them.
+    /// Web apps without a group are listed last.
+    #[serde(default)]
+    pub group: Option<String>,
+
+    /// Free-form notes about the web app.
+    ///
+    /// Only managed by the user and never changed by manifest updates.
+    #[serde(default)]
+    pub notes: Option<String>,
+
+    /// When the web app was last launched.
+    #[serde(default)]
+    pub last_launched: Option<SystemTime>,
+
+    /// How many times the web app was launched.
+    #[serde(default)]
+    pub launch_count: u64,
+
+    /// When the web app manifest was last successfully fetched.
+    #[serde(default)]
+    pub last_manifest_fetched: Option<SystemTime>,
+
+    /// When the web app was last installed or updated.
+    ///
+    /// Unlike the manifest fetch time, this is also changed by updates
+    /// that only refresh the system integration or icons.
+    #[serde(default)]
+    pub last_updated: Option<SystemTime>,
+
+    /// A custom web app locale.
+    ///
+    /// Stored as a language tag (such as `en-US`) and used to set the
+    /// preferred languages and the `LANG` variable when launching the
+    /// web app. If not set, the system locale is used.
+    #[serde(default)]
+    pub locale: Option<String>,
+
+    /// A proxy used by the web app.
+    ///
+    /// Written into the profile prefs when launching the web app, so
+    /// it is only isolated when the web app has a dedicated profile.
+    /// Supports `http`, `socks4`, `socks5` and `socks5h` proxies.
+    #[serde(default)]
+    pub proxy: Option<Url>,
+
+    /// A custom window size and position of the web app.
+    ///
+    /// Applied by the runtime when opening a new web app window.
+    #[serde(default)]
+    pub window: SiteWindow,
+
+    /// An AppUserModelID of the web app.
+    ///
+    /// Used on Windows to group web app windows in the taskbar and
+    /// to attribute notifications to the web app. It is derived from
+    /// the web app ID when installing the web app and never changes.
+    #[serde(default)]
+    pub app_user_model_id: Option<String>,
+
+    /// A fallback icon URL found from the site's main document.
+    ///
+    /// Only set when the manifest does not contain any usable icons,
+    /// and re-evaluated every time the manifest is updated.
+    #[serde(default)]
+    pub fallback_icon_url: Option<Url>,
+
+    /// A hash of the currently installed system integration.
+    ///
+    /// Used to skip rewriting the integration when nothing has changed.
+    #[serde(default)]
+    pub integration_hash: Option<String>,
+}
+
+impl SiteConfig {
+    /// Returns names of fields that the user has overridden.
+    ///
+    /// Overridden fields take precedence over the manifest,
+    /// so they are preserved when the manifest is updated.
+    pub fn overridden_fields(&self) -> Vec<&'static str> {
+        [
+            ("name", self.name.is_some()),
+            ("display_name", self.name_override.is_some()),
+            ("description", self.description.is_some()),
+            ("start_url", self.start_url.is_some()),
+            ("icon_url", self.icon_url.is_some()),
+            ("icon_override", self.icon_override.is_some()),
+            ("categories", self.categories.is_some()),
+            ("keywords", self.keywords.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, overridden)| overridden.then_some(field))
+        .collect()
+    }
+
+    /// Discards all overrides, so all fields are taken from the manifest.
+    pub fn reset_overrides(&mut self) {
+        self.name = None;
+        self.name_override = None;
+        self.description = None;
+        self.start_url = None;
+        self.icon_url = None;
+        self.icon_override = None;
+        self.categories = None;
+        self.keywords = None;
+    }
+}
+
+/// A problem with a single field when parsing [`SiteConfig`] from JSON.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SiteConfigParseError {
+    /// A name of the invalid field.
+    pub field: String,
+
+    /// A description of the problem.
+    pub reason: String,
+}
+
+impl fmt::Display for SiteConfigParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid field `{}`: {}", self.field, self.reason)
+    }
+}
+
+impl std::error::Error for SiteConfigParseError {}
+
+/// Parses individual JSON object fields and collects all errors.
+struct FieldParser {
+    object: Map<String, Value>,
+    errors: Vec<SiteConfigParseError>,
+}
+
+impl FieldParser {
+    fn error(&mut self, field: &str, reason: String) {
+        self.errors.push(SiteConfigParseError { field: field.into(), reason });
+    }
+
+    fn required<T: DeserializeOwned>(&mut self, field: &str) -> Option<T> {
+        match self.object.remove(field) {
+            None | Some(Value::Null) => {
+                self.error(field, "field is required".into());
+                None
+            }
+            Some(value) => match serde_json::from_value(value) {
+                Ok(value) => Some(value),
+                Err(error) => {
+                    self.error(field, error.to_string());
+                    None
+                }
+            },
+        }
+    }
+
+    fn optional<T: DeserializeOwned + Default>(&mut self, field: &str) -> T {
+        match self.object.remove(field) {
+            None | Some(Value::Null) => T::default(),
+            Some(value) => match serde_json::from_value(value) {
+                Ok(value) => value,
+                Err(error) => {
+                    self.error(field, error.to_string());
+                    T::default()
+                }
+            },
+        }
+    }
+}
+
+impl TryFrom<Value> for SiteConfig {
+    type Error = Vec<SiteConfigParseError>;
+
+    /// Parses the config from JSON and reports all invalid fields at once.
+    ///
+    /// Missing or `null` optional fields use their default values,
+    /// and unknown fields are ignored, the same as when the config
+    /// is deserialized from the storage.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let Value::Object(object) = value else {
+            let reason = "expected an object".into();
+            return Err(vec![SiteConfigParseError { field: "".into(), reason }]);
+        };
+
+        let mut parser = FieldParser { object, errors: vec![] };
+        let document_url = parser.required("document_url");
+        let manifest_url = parser.required("manifest_url");
+
+        // Wrapped into a closure to emulate currently unstable `try` blocks
+        // Required fields are last, so all optional fields are always checked
+        let config = || -> Option<Self> {
+            Some(Self {
+                name: parser.optional("name"),
+                name_override: parser.optional("name_override"),
+                description: parser.optional("description"),
+                start_url: parser.optional("start_url"),
+                icon_url: parser.optional("icon_url"),
+                icon_override: parser.optional("icon_override"),
+                categories: parser.optional("categories"),
+                keywords: parser.optional("keywords"),
+                enabled_url_handlers: parser.optional("enabled_url_handlers"),
+                enabled_protocol_handlers: parser.optional("enabled_protocol_handlers"),
+                custom_protocol_handlers: parser.optional("custom_protocol_handlers"),
+                launch_on_login: parser.optional("launch_on_login"),
+                launch_on_browser: parser.optional("launch_on_browser"),
+                single_instance: parser.optional("single_instance"),
+                pinned: parser.optional("pinned"),
+                group: parser.optional("group"),
+                notes: parser.optional("notes"),
+                last_launched: parser.optional("last_launched"),
+                launch_count: parser.optional("launch_count"),
+                last_manifest_fetched: parser.optional("last_manifest_fetched"),
+                last_updated: parser.optional("last_updated"),
+                locale: parser.optional("locale"),
+                proxy: parser.optional("proxy"),
+                window: parser.optional("window"),
+                app_user_model_id: parser.optional("app_user_model_id"),
+                fallback_icon_url: parser.optional("fallback_icon_url"),
+                integration_hash: parser.optional("integration_hash"),
+                document_url: document_url?,
+                manifest_url: manifest_url?,
+            })
+        };
+
+        match config() {
+            Some(config) if parser.errors.is_empty() => Ok(config),
+            _ => Err(parser.errors),
+        }
+    }
+}
+
+/// Check whether the URL is a proxy supported by the runtime.
+///
+/// The proxy needs a supported scheme, a host and, for SOCKS proxies,
+/// an explicit port. Credentials and paths are not supported.
+#[must_use = "an invalid proxy must not be stored"]
+pub fn validate_proxy(proxy: &Url) -> Result<()> {
+    if !["http", "socks4", "socks5", "socks5h"].contains(&proxy.scheme()) {
+        bail!("Unsupported proxy scheme: {}, use http, socks4, socks5 or socks5h", proxy.scheme());
+    }
+
+    if proxy.host_str().is_none_or(str::is_empty) {
+        bail!("Proxy URL must contain a host: {proxy}");
+    }
+
+    if proxy.port_or_known_default().is_none() {
+        bail!("Proxy URL must contain a port: {proxy}");
+    }
+
+    if !proxy.username().is_empty() || proxy.password().is_some() {
+        bail!("Proxy credentials are not supported, the runtime will ask for them instead");
+    }
+
+    if !matches!(proxy.path(), "" | "/") || proxy.query().is_some() {
+        bail!("Proxy URL must not contain a path: {proxy}");
+    }
+
+    Ok(())
+}
+
+/// Check whether the string is a valid language tag, such as `en` or `pt-BR`.
+///
+/// Only the basic structure of the tag is validated: a primary language
+/// subtag of 2-3 letters, optionally followed by alphanumeric subtags.
+#[must_use = "an invalid locale must not be stored"]
+pub fn validate_locale(locale: &str) -> Result<()> {
+    let mut subtags = locale.split(['-', '_']);
+    let language = subtags.next().unwrap_or_default();
+
+    let valid = (2..=3).contains(&language.len())
+        && language.bytes().all(|char| char.is_ascii_alphabetic())
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len())
+                && subtag.bytes().all(|char| char.is_ascii_alphanumeric())
+        });
+
+    if !valid {
+        bail!("Invalid locale: {locale}");
+    }
+
+    Ok(())
+}
+
+/// Builds a [`SiteConfig`] with only the desired properties set.
+///
+/// The manifest and document URLs are required and validated when
+/// building the config. All other properties are optional and
+/// default to the values from the web app manifest.
+#[derive(Debug, Default, Clone)]
+#[must_use = "the builder does nothing until `.build()` is called"]
+pub struct SiteConfigBuilder {
+    manifest_url: Option<String>,
+    document_url: Option<String>,
+    name: Option<String>,
+    description: Option<String>,
+    start_url: Option<Url>,
+    icon_url: Option<Url>,
+    categories: Option<Vec<String>>,
+    keywords: Option<Vec<String>>,
+    launch_on_login: bool,
+    launch_on_browser: bool,
+    single_instance: bool,
+}
+
+impl SiteConfigBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn manifest_url(mut self, url: impl Into<String>) -> Self {
+        self.manifest_url = Some(url.into());
+        self
+    }
+
+    pub fn document_url(mut self, url: impl Into<String>) -> Self {
+        self.document_url = Some(url.into());
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<Option<String>>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<Option<String>>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn start_url(mut self, url: impl Into<Option<Url>>) -> Self {
+        self.start_url = url.into();
+        self
+    }
+
+    pub fn icon_url(mut self, url: impl Into<Option<Url>>) -> Self {
+        self.icon_url = url.into();
+        self
+    }
+
+    pub fn categories(mut self, categories: impl Into<Option<Vec<String>>>) -> Self {
+        self.categories = categories.into();
+        self
+    }
+
+    pub fn keywords(mut self, keywords: impl Into<Option<Vec<String>>>) -> Self {
+        self.keywords = keywords.into();
+        self
+    }
+
+    pub fn launch_on_login(mut self, enabled: bool) -> Self {
+        self.launch_on_login = enabled;
+        self
+    }
+
+    pub fn launch_on_browser(mut self, enabled: bool) -> Self {
+        self.launch_on_browser = enabled;
+        self
+    }
+
+    pub fn single_instance(mut self, enabled: bool) -> Self {
+        self.single_instance = enabled;
+        self
+    }
+
+    /// Validates the required URLs and constructs the config.
+    pub fn build(self) -> Result<SiteConfig> {
+        let Some(manifest_url) = self.manifest_url else { bail!("Manifest URL is required") };
+        let Some(document_url) = self.document_url else { bail!("Document URL is required") };
+
+        let manifest_url = Url::parse(&manifest_url).context("Invalid manifest URL")?;
+        let document_url = Url::parse(&document_url).context("Invalid document URL")?;
+
+        Ok(SiteConfig {
+            name: self.name,
+            name_override: None,
+            description: self.description,
+            start_url: self.start_url,
+            icon_url: self.icon_url,
+            icon_override: None,
+            document_url,
+            manifest_url,
+            categories: self.categories,
+            keywords: self.keywords,
+            enabled_url_handlers: vec![],
+            enabled_protocol_handlers: vec![],
+            custom_protocol_handlers: vec![],
+            launch_on_login: self.launch_on_login,
+            launch_on_browser: self.launch_on_browser,
+            single_instance: self.single_instance,
+            pinned: false,
+            group: None,
+            notes: None,
+            last_launched: None,
+            launch_count: 0,
+            last_manifest_fetched: None,
+            last_updated: None,
+            locale: None,
+            proxy: None,
+            window: SiteWindow::default(),
+            app_user_model_id: None,
+            fallback_icon_url: None,
+            integration_hash: None,
+        })
+    }
 }
 
 #[non_exhaustive]
@@ -114,14 +577,28 @@ pub struct Site {
     pub manifest: SiteManifest,
 }
 
+impl fmt::Display for Site {
+    /// Formats the web app as `<name> (id: <ulid>)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (id: {})", self.name(), self.ulid)
+    }
+}
+
 impl Site {
-    fn download(url: &Url, client: &Client) -> Result<String> {
+    fn download(url: &Url, client: &Client, headers: &HeaderMap) -> Result<String> {
+        // If the URL is a file URL (used for locally-hosted web apps), read it directly
+        let json = if url.scheme() == "file" {
+            let path = url.to_file_path().map_err(|_| anyhow!("Invalid file URL: {url}"))?;
+            read_to_string(path)?
+
         // If the URL is not a data URL, just download it using reqwest
-        let json = if url.scheme() != "data" {
+        } else if url.scheme() != "data" {
             client
                 .get(url.to_owned())
                 .header(reqwest::header::REFERER, url.to_string())
-                .send()?
+                .headers(headers.to_owned())
+                .send()
+                .map_err(network_error)?
                 .text()?
 
         // If the URL is a data URL (used for installing non-PWA sites), decode it using data-url
@@ -137,9 +614,104 @@ impl Site {
 
     #[inline]
     pub fn new(profile: Ulid, config: SiteConfig, client: &Client) -> Result<Self> {
+        let json = Self::download_manifest(&config.manifest_url, client)?;
+        let mut site = Self::from_manifest(profile, config, &json, client)?;
+        site.config.last_manifest_fetched = Some(SystemTime::now());
+        Ok(site)
+    }
+
+    /// Create a new web app from an already downloaded manifest.
+    pub fn from_manifest(
+        profile: Ulid,
+        config: SiteConfig,
+        json: &str,
+        client: &Client,
+    ) -> Result<Self> {
+        let mut site = Self::from_parsed_manifest(profile, config, json)?;
+        site.update_fallback_icon(client);
+        Ok(site)
+    }
+
+    /// Create a new web app from an already downloaded manifest, but without any icons.
+    ///
+    /// Unlike [`Site::from_manifest`], the favicon is not looked up, so no network
+    /// requests are made. Icons of the manifest and its shortcuts are removed, so
+    /// system integration uses the generated placeholder icon.
+    pub fn from_manifest_without_icons(
+        profile: Ulid,
+        config: SiteConfig,
+        json: &str,
+    ) -> Result<Self> {
+        let mut site = Self::from_parsed_manifest(profile, config, json)?;
+        site.manifest.icons.clear();
+        site.manifest.shortcuts.iter_mut().for_each(|shortcut| shortcut.icons.clear());
+        site.config.icon_url = None;
+        site.config.fallback_icon_url = None;
+        Ok(site)
+    }
+
+    fn from_parsed_manifest(profile: Ulid, config: SiteConfig, json: &str) -> Result<Self> {
+        let manifest = Self::parse_manifest(&config, json)?;
+
+        let mut site = Self { ulid: Ulid::new(), profile, config, manifest };
+        site.config.app_user_model_id = Some(site.app_user_model_id());
+        Ok(site)
+    }
+
+    #[inline]
+    pub fn update(&mut self, client: &Client) -> Result<()> {
+        self.update_with_headers(client, &HeaderMap::new())
+    }
+
+    /// Update the web app, sending additional headers with the manifest request.
+    ///
+    /// See [`Site::download_manifest_with_headers`] for more details.
+    pub fn update_with_headers(&mut self, client: &Client, headers: &HeaderMap) -> Result<()> {
+        // There is nothing to update if the manifest is a data URL because it is always static
+        if self.config.manifest_url.scheme() == "data" {
+            self.config.last_manifest_fetched = Some(SystemTime::now());
+            return Ok(());
+        }
+
+        let json =
+            Self::download_manifest_with_headers(&self.config.manifest_url, client, headers)?;
+        self.update_from_manifest(&json, client)?;
+        self.config.last_manifest_fetched = Some(SystemTime::now());
+        Ok(())
+    }
+
+    /// Update the web app from an already downloaded manifest.
+    #[must_use = "the web app is not updated if parsing the manifest fails"]
+    pub fn update_from_manifest(&mut self, json: &str, client: &Client) -> Result<()> {
+        self.manifest = Self::parse_manifest(&self.config, json)?;
+        self.update_fallback_icon(client);
+        Ok(())
+    }
+
+    /// Download the raw web app manifest JSON.
+    #[inline]
+    pub fn download_manifest(url: &Url, client: &Client) -> Result<String> {
+        Self::download_manifest_with_headers(url, client, &HeaderMap::new())
+    }
+
+    /// Download the raw web app manifest JSON, sending additional headers with the request.
+    ///
+    /// Headers, such as credentials, are only sent with the manifest request itself.
+    /// They are never sent when downloading icons, which are often hosted on other sites.
+    pub fn download_manifest_with_headers(
+        url: &Url,
+        client: &Client,
+        headers: &HeaderMap,
+    ) -> Result<String> {
         info!("Downloading the web app manifest");
-        let json = Self::download(&config.manifest_url, client).context(DOWNLOAD_ERROR)?;
+        Self::download(url, client, headers).context(DOWNLOAD_ERROR)
+    }
 
+    /// Parse the raw web app manifest JSON and resolve its URLs.
+    ///
+    /// Relative URLs are resolved against the manifest URL of the config,
+    /// or against its document URL if the manifest is a data URL.
+    pub fn parse_manifest(config: &SiteConfig, json: &str) -> Result<SiteManifest> {
         // If the manifest URL is a data URL, replace it with the document URL
         let manifest_url = if config.manifest_url.scheme() != "data" {
             &config.manifest_url
@@ -148,32 +720,152 @@ impl Site {
         };
 
         info!("Parsing the web app manifest");
-        let mut manifest: SiteManifest = serde_json::from_str(&json).context(PARSE_ERROR)?;
+        let mut manifest: SiteManifest = serde_json::from_str(json).context(PARSE_ERROR)?;
         manifest.process(&config.document_url, manifest_url).context(PARSE_ERROR)?;
 
-        Ok(Self { ulid: Ulid::new(), profile, config, manifest })
-    }
+        // Remote manifests must not be able to read local files into generated icons,
+        // so file icons are only allowed in local manifests installed with `--allow-file-url`
+        if manifest_url.scheme() != "file" {
+            let local = |icon: &IconResource| match &icon.src {
+                ManifestUrl::Absolute(url) => url.scheme() == "file",
+                _ => false,
+            };
 
-    #[inline]
-    pub fn update(&mut self, client: &Client) -> Result<()> {
-        // There is nothing to update if the manifest is a data URL because it is always static
-        if self.config.manifest_url.scheme() == "data" {
-            return Ok(());
+            let mut ignored = false;
+            let shortcuts = manifest.shortcuts.iter_mut().map(|shortcut| &mut shortcut.icons);
+            for icons in std::iter::once(&mut manifest.icons).chain(shortcuts) {
+                let count = icons.len();
+                icons.retain(|icon| !local(icon));
+                ignored |= icons.len() != count;
+            }
+
+            if ignored {
+                warn!("Ignoring local icon files in a remote web app manifest");
+            }
         }
 
-        info!("Downloading the web app manifest");
-        let json = Self::download(&self.config.manifest_url, client).context(DOWNLOAD_ERROR)?;
+        Ok(manifest)
+    }
 
-        info!("Parsing the web app manifest");
-        let mut manifest: SiteManifest = serde_json::from_str(&json).context(PARSE_ERROR)?;
-        manifest
-            .process(&self.config.document_url, &self.config.manifest_url)
-            .context(PARSE_ERROR)?;
+    /// Path where the raw manifest of the web app is cached.
+    ///
+    /// Manifests are only cached when requested with `--save-manifest`
+    /// and stored in the `manifests` directory of the web app profile.
+    pub fn manifest_path(&self, dirs: &ProjectDirs) -> PathBuf {
+        dirs.userdata
+            .join("profiles")
+            .join(self.profile.to_string())
+            .join("manifests")
+            .join(format!("{}.json", self.ulid))
+    }
+
+    /// Store the raw manifest into the manifest cache.
+    pub fn save_manifest(&self, dirs: &ProjectDirs, json: &str) -> Result<()> {
+        const SAVE_ERROR: &str = "Failed to save web app manifest";
 
-        self.manifest = manifest;
+        let path = self.manifest_path(dirs);
+        create_dir_all(path.parent().unwrap()).context(SAVE_ERROR)?;
+        write(&path, json).context(SAVE_ERROR)?;
+
+        info!("Web app manifest saved: {}", path.display());
         Ok(())
     }
 
+    /// Load the raw manifest from the manifest cache.
+    pub fn load_manifest(&self, dirs: &ProjectDirs) -> Result<String> {
+        let path = self.manifest_path(dirs);
+
+        if !path.exists() {
+            bail!("Web app manifest is not cached, update it with --save-manifest first");
+        }
+
+        info!("Loading the cached web app manifest");
+        read_to_string(path).context("Failed to load cached web app manifest")
+    }
+
+    /// Find a fallback icon if the manifest does not contain any usable icons.
+    ///
+    /// Usable icons must have the "any" purpose and an absolute URL. If there are
+    /// none, the largest favicon from the site's main document is used instead.
+    fn update_fallback_icon(&mut self, client: &Client) {
+        if self.config.icon_override.is_some() {
+            self.config.fallback_icon_url = None;
+            return;
+        }
+
+        let usable = self.manifest.icons.iter().any(|icon| {
+            icon.purpose.contains(&ImagePurpose::Any)
+                && matches!(icon.src, ManifestUrl::Absolute(_))
+        });
+
+        if usable {
+            self.config.fallback_icon_url = None;
+            return;
+        }
+
+        info!("Searching for a fallback icon");
+        let favicon = Self::find_favicon(&self.config.document_url, client);
+        self.config.fallback_icon_url = match favicon.context(FALLBACK_ICON_ERROR) {
+            Ok(url) => url,
+            Err(error) => {
+                warn!("{error:?}");
+                None
+            }
+        };
+    }
+
+    /// Find the largest favicon of the site's main document.
+    ///
+    /// Looks for icon `<link>` elements in the document and picks the largest one
+    /// based on its `sizes` attribute. If the document does not declare any icons,
+    /// the conventional `/favicon.ico` location is used if it exists.
+    fn find_favicon(document_url: &Url, client: &Client) -> Result<Option<Url>> {
+        if !matches!(document_url.scheme(), "http" | "https") {
+            return Ok(None);
+        }
+
+        let html = client.get(document_url.to_owned()).send()?.error_for_status()?.text()?;
+        let mut largest: Option<(u32, Url)> = None;
+
+        for tag in find_tags(&html, "link") {
+            let rel = find_attribute(tag, "rel").unwrap_or_default().to_ascii_lowercase();
+            if !rel.split_ascii_whitespace().any(|rel| rel == "icon" || rel == "apple-touch-icon") {
+                continue;
+            }
+
+            let Some(href) = find_attribute(tag, "href").filter(|href| !href.is_empty()) else {
+                continue;
+            };
+            let Ok(url) = document_url.join(&href) else { continue };
+            if !matches!(url.scheme(), "http" | "https" | "data") {
+                continue;
+            }
+
+            // Icons with the "any" size are usually scalable, so they are the largest
+            let size = find_attribute(tag, "sizes")
+                .unwrap_or_default()
+                .split_ascii_whitespace()
+                .filter_map(|size| match size.to_ascii_lowercase().as_str() {
+                    "any" => Some(u32::MAX),
+                    size => size.split('x').next()?.parse().ok(),
+                })
+                .max()
+                .unwrap_or(0);
+
+            if largest.as_ref().is_none_or(|(current, _)| size > *current) {
+                largest = Some((size, url));
+            }
+        }
+
+        if let Some((_, url)) = largest {
+            return Ok(Some(url));
+        }
+
+        let url = document_url.join("/favicon.ico")?;
+        let exists = client.head(url.to_owned()).send().is_ok_and(|res| res.status().is_success());
+        Ok(exists.then_some(url))
+    }
+
     #[inline]
     pub fn launch<I: IntoIterator<Item = (String, String)>>(
         &self,
@@ -211,6 +903,13 @@ impl Site {
         if config.runtime_use_portals {
             vars.insert("GTK_USE_PORTAL".into(), "1".into());
         }
+        if let Some(locale) = &self.config.locale {
+            let locale = locale.replace('-', "_");
+            vars.insert("LANG".into(), format!("{locale}.UTF-8"));
+            vars.insert("LANGUAGE".into(), locale);
+        }
+
+        self.apply_proxy_prefs(&profile).context("Failed to apply web app proxy")?;
 
         // Include all user arguments and variables and launch the runtime
         args.extend_from_slice(arguments);
@@ -220,6 +919,83 @@ impl Site {
 }
 
 impl Site {
+    /// Writes proxy prefs of the web app into the profile `user.js` file.
+    ///
+    /// Prefs are written into a separate block that is replaced on every launch.
+    /// When the proxy is removed, the block resets the proxy type once, because
+    /// the runtime keeps the previous values in its own prefs file otherwise.
+    fn apply_proxy_prefs(&self, profile: &Path) -> Result<()> {
+        const BLOCK_START: &str = "// FIREFOXPWA PROXY START";
+        const BLOCK_END: &str = "// FIREFOXPWA PROXY END";
+        const PROXY_RESET: &str = "user_pref(\"network.proxy.type\", 5);";
+
+        let path = profile.join("user.js");
+        let content = if path.exists() { read_to_string(&path)? } else { String::new() };
+
+        // Split the existing content into the proxy block and all other prefs
+        let (rest, previous) = match (content.find(BLOCK_START), content.find(BLOCK_END)) {
+            (Some(start), Some(end)) if start < end => {
+                let end = end + BLOCK_END.len();
+                let rest = format!("{}{}", &content[..start], content[end..].trim_start());
+                (rest, Some(&content[start..end]))
+            }
+            _ => (content.clone(), None),
+        };
+
+        let prefs = match &self.config.proxy {
+            Some(proxy) => {
+                let host = format!("\"{}\"", proxy.host_str().unwrap_or_default());
+                let port = proxy.port_or_known_default().unwrap_or_default().to_string();
+                let none = "\"\"".to_string();
+
+                let mut prefs =
+                    vec![("type", "1".into()), ("share_proxy_settings", "false".into())];
+                match proxy.scheme() {
+                    "http" => prefs.extend([
+                        ("http", host.clone()),
+                        ("http_port", port.clone()),
+                        ("ssl", host),
+                        ("ssl_port", port),
+                        ("socks", none),
+                    ]),
+                    scheme => prefs.extend([
+                        ("socks", host),
+                        ("socks_port", port),
+                        ("socks_version", if scheme == "socks4" { "4" } else { "5" }.into()),
+                        ("socks_remote_dns", (scheme == "socks5h").to_string()),
+                        ("http", none.clone()),
+                        ("ssl", none),
+                    ]),
+                }
+
+                let prefs: Vec<String> = prefs
+                    .into_iter()
+                    .map(|(name, value)| format!("user_pref(\"network.proxy.{name}\", {value});"))
+                    .collect();
+                Some(prefs.join("\n"))
+            }
+            None if previous.is_some_and(|block| !block.contains(PROXY_RESET)) => {
+                Some(PROXY_RESET.into())
+            }
+            None => None,
+        };
+
+        let mut updated = rest;
+        if let Some(prefs) = prefs {
+            if !updated.is_empty() && !updated.ends_with('\n') {
+                updated.push('\n');
+            }
+            updated.push_str(&format!("{BLOCK_START}\n{prefs}\n{BLOCK_END}\n"));
+        }
+
+        if updated != content {
+            create_dir_all(profile)?;
+            write(&path, updated)?;
+        }
+
+        Ok(())
+    }
+
     /// Start URL is used as an info URL on supported systems.
     #[rustfmt::skip]
     pub fn url(&self) -> String {
@@ -246,10 +1022,11 @@ impl Site {
         }
     }
 
-    /// First tries the user-specified name, then tries manifest name
-    /// and then short name. If no name is specified, uses the domain.
+    /// First tries the display name override and the user-specified name, then tries
+    /// manifest name and then short name. If no name is specified, uses the domain.
     pub fn name(&self) -> String {
         [
+            self.config.name_override.as_deref(),
             self.config.name.as_deref(),
             self.manifest.name.as_deref(),
             self.manifest.short_name.as_deref(),
@@ -272,9 +1049,16 @@ impl Site {
             .unwrap_or_else(|| "".into())
     }
 
-    /// First tries the user-specified icon, then tries manifest icons.
+    /// First tries the user-specified icon file and URL, then tries manifest icons.
+    /// If the manifest has no usable icons, uses the fallback icon.
     pub fn icons(&self) -> Vec<IconResource> {
-        match &self.config.icon_url {
+        let icon_override = match &self.config.icon_override {
+            Some(path) => Url::from_file_path(path).ok(),
+            None => None,
+        };
+
+        let icon = icon_override.as_ref().or(self.config.icon_url.as_ref());
+        match icon.or(self.config.fallback_icon_url.as_ref()) {
             Some(icon) => vec![IconResource {
                 src: ManifestUrl::Absolute(icon.clone()),
                 sizes: [ImageSize::default()].iter().cloned().collect(),
@@ -286,6 +1070,17 @@ impl Site {
         }
     }
 
+    /// AppUserModelID is used to identify the web app on Windows.
+    ///
+    /// First tries the stored ID, then derives it from the web app ID.
+    /// Web apps installed before the ID was stored use the same format.
+    pub fn app_user_model_id(&self) -> String {
+        match &self.config.app_user_model_id {
+            Some(id) => id.clone(),
+            None => format!("filips.firefoxpwa.{}", self.ulid),
+        }
+    }
+
     /// Categories can be used for user organization.
     ///
     /// There is no fixed list of categories, but some known categories are converted
@@ -317,4 +1112,83 @@ impl Site {
         .map(|item| sanitize_string(item))
         .collect()
     }
+
+    /// Converts the web app into a stable public JSON representation.
+    ///
+    /// Unlike the storage format, this contains resolved properties
+    /// in camelCase and does not expose any internal fields. It is
+    /// used for the `--json` output of the console program.
+    ///
+    /// Fields: `id`, `profileId`, `name`, `description`, `startUrl`,
+    /// `documentUrl`, `manifestUrl`, `categories`, `keywords`,
+    /// `launchOnLogin`, `launchOnBrowser`, `singleInstance`, `locale`.
+    pub fn to_public_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.ulid,
+            "profileId": self.profile,
+            "name": self.name(),
+            "description": self.description(),
+            "startUrl": self.url(),
+            "documentUrl": self.config.document_url,
+            "manifestUrl": self.config.manifest_url,
+            "categories": self.categories(),
+            "keywords": self.keywords(),
+            "launchOnLogin": self.config.launch_on_login,
+            "launchOnBrowser": self.config.launch_on_browser,
+            "singleInstance": self.config.single_instance,
+            "locale": self.config.locale,
+        })
+    }
+}
+
+/// Find all start tags with the specified name in the HTML document.
+///
+/// This is not a complete HTML parser, but it is good enough
+/// for finding simple elements such as `<link>` in the head.
+fn find_tags<'a>(html: &'a str, name: &str) -> Vec<&'a str> {
+    let lower = html.to_ascii_lowercase();
+    let needle = format!("<{name}");
+    let mut tags = vec![];
+    let mut start = 0;
+
+    while let Some(position) = lower[start..].find(&needle) {
+        let begin = start + position + needle.len();
+        let Some(length) = lower[begin..].find('>') else { break };
+
+        // Make sure the match is not just a prefix of a longer tag name
+        if lower[begin..].starts_with(|char: char| char.is_ascii_whitespace() || char == '/') {
+            tags.push(&html[begin..begin + length]);
+        }
+
+        start = begin + length;
+    }
+
+    tags
+}
+
+/// Find a value of the attribute with the specified name in the HTML tag.
+fn find_attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut start = 0;
+
+    while let Some(position) = lower[start..].find(name) {
+        let index = start + position;
+        start = index + name.len();
+
+        // Attribute name must be a whole word and followed by a value
+        if !lower[..index].ends_with(|char: char| char.is_ascii_whitespace()) {
+            continue;
+        }
+        let Some(rest) = tag[start..].trim_start().strip_prefix('=') else { continue };
+        let rest = rest.trim_start();
+
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next()?,
+            _ => rest.split(|char: char| char.is_ascii_whitespace() || char == '/').next()?,
+        };
+
+        return Some(value.replace("&amp;", "&"));
+    }
+
+    None
 }
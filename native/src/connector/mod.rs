@@ -1,35 +1,304 @@
-use std::fs::OpenOptions;
+use std::cell::{Cell, RefCell};
+#[cfg(not(platform_windows))]
+use std::fs::{Permissions, remove_file, set_permissions};
+use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
-use std::process::exit;
-use std::{env, io};
+#[cfg(not(platform_windows))]
+use std::path::Path;
+use std::{env, fmt, io};
 
 use anyhow::{Context, Result};
 use byteorder::{NativeEndian, ReadBytesExt, WriteBytesExt};
-use log::{error, info};
+use cfg_if::cfg_if;
+use log::{error, info, warn};
 
+pub use crate::connector::pidfile::PidFile;
 use crate::connector::process::Process;
-use crate::connector::request::ConnectorRequest;
-use crate::connector::response::ConnectorResponse;
+pub use crate::connector::request::ConnectorRequest;
+pub use crate::connector::response::ConnectorResponse;
+pub use crate::connector::selftest::self_test;
 use crate::directories::ProjectDirs;
+use crate::storage::StorageError;
 
+mod pidfile;
 mod process;
 mod request;
 mod response;
+mod selftest;
 
-#[derive(Debug, Clone)]
+/// The maximum size of a single message sent to the browser.
+///
+/// This limit is enforced by the browser and cannot be changed.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Maximum time to wait for a complete request on a socket connection.
+///
+/// Connections are served sequentially, so a client that never sends a complete
+/// request would otherwise block all other clients.
+#[cfg(not(platform_windows))]
+const SOCKET_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Get the maximum size of a single message received from the browser in bytes.
+///
+/// Can be overwritten by the `FIREFOXPWA_MAX_MESSAGE_BYTES` variable.
+/// Prevents allocating huge buffers when the message size is corrupted.
+pub fn get_max_message_size() -> usize {
+    const DEFAULT_SIZE: usize = 1024 * 1024;
+
+    std::env::var("FIREFOXPWA_MAX_MESSAGE_BYTES")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(DEFAULT_SIZE)
+}
+
+/// Reads a single message with native messaging framing from the input.
+///
+/// The message is prefixed with its size as a 32-bit integer in native byte order.
+/// The size is checked against the limit before the buffer is allocated, so a
+/// corrupted or malicious size cannot cause a huge allocation.
+pub fn read_message(input: &mut impl Read) -> Result<serde_json::Value, ConnectionError> {
+    let size = input.read_u32::<NativeEndian>()? as usize;
+    if size == 0 {
+        let message = "Received an empty message".into();
+        return Err(ConnectionError::Protocol { message });
+    }
+
+    // Check the size before allocating the buffer for the message
+    let limit = get_max_message_size();
+    if size > limit {
+        let message = format!("Message of {size} bytes exceeds the limit of {limit} bytes");
+        return Err(ConnectionError::Protocol { message });
+    }
+
+    let mut buffer = vec![0u8; size];
+    input.read_exact(&mut buffer)?;
+    Ok(serde_json::from_slice(&buffer)?)
+}
+
+/// An error that caused the connection to fail.
+#[derive(Debug)]
+pub enum ConnectionError {
+    /// Reading from or writing to the browser failed.
+    Io(io::Error),
+
+    /// A message could not be serialized or deserialized.
+    Json(serde_json::Error),
+
+    /// The storage could not be loaded or saved while processing the request.
+    Storage(StorageError),
+
+    /// A message violated the native messaging protocol.
+    Protocol { message: String },
+
+    /// The browser did not send or receive a message in time.
+    Timeout,
+}
+
+impl fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "Failed to communicate with the browser"),
+            Self::Json(_) => write!(f, "Failed to serialize or deserialize message"),
+            Self::Storage(_) => write!(f, "Failed to access storage"),
+            Self::Protocol { message } => write!(f, "Protocol error: {message}"),
+            Self::Timeout => write!(f, "Connection timed out"),
+        }
+    }
+}
+
+impl std::error::Error for ConnectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Json(error) => Some(error),
+            Self::Storage(error) => Some(error),
+            Self::Protocol { .. } | Self::Timeout => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConnectionError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => Self::Timeout,
+            _ => Self::Io(error),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ConnectionError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+impl From<StorageError> for ConnectionError {
+    fn from(error: StorageError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+/// Duplicates the stdout handle.
+///
+/// Stdout is redirected or discarded while processing the request, so all messages
+/// are written to a duplicated handle that still points to the original stdout.
+fn duplicate_stdout() -> io::Result<File> {
+    cfg_if! {
+        if #[cfg(platform_windows)] {
+            use std::os::windows::io::AsHandle;
+            Ok(File::from(io::stdout().as_handle().try_clone_to_owned()?))
+        } else {
+            use std::os::fd::AsFd;
+            Ok(File::from(io::stdout().as_fd().try_clone_to_owned()?))
+        }
+    }
+}
+
+/// A reader that transparently retries reads interrupted by a signal.
+///
+/// On POSIX systems, reading from stdin may fail with [`io::ErrorKind::Interrupted`]
+/// if a signal arrives in the middle of the read. This is not a real error, so the
+/// read is retried instead of failing the whole connection.
+struct RetryInterrupted<R>(R);
+
+impl<R: Read> Read for RetryInterrupted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.0.read(buf) {
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A native messaging connection with the browser extension.
+///
+/// Each connection receives exactly one request and sends exactly one final response.
+/// If the request message contains `"progress": true` next to the `cmd` and `params`
+/// fields, long-running operations may also send any number of interim
+/// [`ConnectorResponse::Progress`] messages before the final response.
+///
+/// Requests that start cancellable operations may also contain an `"operation"`
+/// field with an ID that can be later passed to [`request::CancelOperation`].
 pub struct Connection<'a> {
     dirs: &'a ProjectDirs,
     debugmode: bool,
+    output: RefCell<Box<dyn Write + 'a>>,
+    progress: Cell<bool>,
+    operation: RefCell<Option<String>>,
+}
+
+impl fmt::Debug for Connection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connection")
+            .field("dirs", &self.dirs)
+            .field("debugmode", &self.debugmode)
+            .field("progress", &self.progress)
+            .field("operation", &self.operation)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<'a> Connection<'a> {
-    pub fn start(dirs: &'a ProjectDirs, debugmode: bool) -> Result<()> {
-        let connection = Self { dirs, debugmode };
+    fn new(dirs: &'a ProjectDirs, debugmode: bool, output: impl Write + 'a) -> Self {
+        Self {
+            dirs,
+            debugmode,
+            output: RefCell::new(Box::new(output)),
+            progress: Cell::new(false),
+            operation: RefCell::new(None),
+        }
+    }
+
+    /// Receives the request from stdin, processes it and sends the response to stdout.
+    ///
+    /// Errors of the request itself are sent back to the browser as
+    /// [`ConnectorResponse::Error`] and also returned as the response, so the
+    /// caller can exit with a failure code after cleaning up. Storage errors
+    /// are returned as errors.
+    pub fn start(
+        dirs: &'a ProjectDirs,
+        debugmode: bool,
+    ) -> Result<ConnectorResponse, ConnectionError> {
+        let output = duplicate_stdout()?;
         info!("Connection established: {:?}", env::args().collect::<Vec<String>>());
 
+        let input = RetryInterrupted(io::stdin().lock());
+        Self::serve(dirs, debugmode, input, output)
+    }
+
+    /// Listens for connections on a Unix domain socket.
+    ///
+    /// Connections are accepted sequentially, and each of them receives exactly one
+    /// request and sends exactly one final response, using the same framing as native
+    /// messaging. Errors of a single connection are logged and do not stop listening.
+    #[cfg(not(platform_windows))]
+    pub fn listen(
+        dirs: &'a ProjectDirs,
+        debugmode: bool,
+        path: &Path,
+    ) -> Result<(), ConnectionError> {
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::UnixListener;
+
+        // Remove the socket left over from the previous run
+        if path.exists() {
+            remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        set_permissions(path, Permissions::from_mode(0o600))?;
+        info!("Listening on {}", path.display());
+
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(error) => {
+                    warn!("Failed to accept connection: {error}");
+                    continue;
+                }
+            };
+
+            if let Err(error) = stream.set_read_timeout(Some(SOCKET_READ_TIMEOUT)) {
+                warn!("Failed to set connection timeout: {error}");
+                continue;
+            }
+
+            let output = stream.try_clone()?;
+            match Self::serve(dirs, debugmode, RetryInterrupted(&stream), output) {
+                Ok(_) => info!("Connection closed"),
+                Err(error @ ConnectionError::Timeout) => warn!("{error}"),
+                Err(error) => error!("{:?}", anyhow::Error::new(error)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Receives the request from the input, processes it and sends the response to the output.
+    ///
+    /// Both the request and the response use native messaging framing. Errors of the
+    /// request itself are sent as [`ConnectorResponse::Error`] and also returned as
+    /// the response, except for storage errors, which are returned as errors. If the
+    /// request is not received in time, [`ConnectionError::Timeout`] is returned.
+    pub fn serve(
+        dirs: &'a ProjectDirs,
+        debugmode: bool,
+        mut input: impl Read,
+        output: impl Write + 'a,
+    ) -> Result<ConnectorResponse, ConnectionError> {
+        let connection = Self::new(dirs, debugmode, output);
+
+        // The client that did not send the request in time will not read the response either
+        let request = match connection.receive(&mut input) {
+            Err(ConnectionError::Timeout) => return Err(ConnectionError::Timeout),
+            request => request,
+        };
+
         // Wrapped into a closure to emulate currently unstable `try` blocks
         let handle = || -> Result<ConnectorResponse> {
-            let request = connection.receive().context("Failed to receive request")?;
+            let request = request.context("Failed to receive request")?;
             info!("Received a request: {request:?}");
 
             let response = connection.process(&request).context("Failed to process request")?;
@@ -43,8 +312,9 @@ impl<'a> Connection<'a> {
             Ok(response) => {
                 // Everything seems to be fine
                 // Just send the response back
-                connection.send(&response).context("Failed to send response")?;
+                connection.send(&response)?;
                 info!("Sent a response");
+                Ok(response)
             }
             Err(error) => {
                 // There was some error while processing the request
@@ -60,36 +330,105 @@ impl<'a> Connection<'a> {
                     .join(": ");
 
                 let response = ConnectorResponse::Error(cause);
-                connection.send(&response).context("Failed to send response")?;
+                connection.send(&response)?;
                 info!("Sent a response");
-                exit(1);
+
+                // Storage errors are returned, so they can be handled by the caller
+                match error.downcast::<StorageError>() {
+                    Ok(error) => Err(error.into()),
+                    Err(_) => Ok(response),
+                }
             }
         }
-        Ok(())
     }
 
-    fn receive(&self) -> Result<ConnectorRequest> {
-        let size = io::stdin().read_u32::<NativeEndian>().context("Failed to read message size")?;
-        let mut buffer = vec![0u8; size as usize];
+    /// Processes a single already deserialized request message and returns the response.
+    ///
+    /// Unlike [`Connection::serve`], this does not use native messaging framing, does not
+    /// redirect stdout and stderr, and discards all progress messages. This makes it
+    /// possible to exercise request handling without the browser.
+    pub fn handle_message(
+        dirs: &'a ProjectDirs,
+        message: serde_json::Value,
+    ) -> Result<ConnectorResponse> {
+        let connection = Self::new(dirs, false, io::sink());
+        let request = connection.parse(message).context("Failed to parse request")?;
+        request.process(&connection)
+    }
+
+    fn receive(&self, input: &mut impl Read) -> Result<ConnectorRequest, ConnectionError> {
+        self.parse(read_message(input)?)
+    }
+
+    fn parse(&self, mut message: serde_json::Value) -> Result<ConnectorRequest, ConnectionError> {
+        // Progress reporting is opt-in, as the extension needs to support multiple messages
+        if let Some(progress) = message.as_object_mut().and_then(|object| object.remove("progress"))
+        {
+            self.progress.set(progress.as_bool().unwrap_or(false));
+        }
+
+        // Operation ID is optional and only used by cancellable operations
+        if let Some(operation) =
+            message.as_object_mut().and_then(|object| object.remove("operation"))
+        {
+            *self.operation.borrow_mut() = operation.as_str().map(String::from);
+        }
+
+        Ok(serde_json::from_value(message)?)
+    }
+
+    fn send(&self, response: &ConnectorResponse) -> Result<(), ConnectionError> {
+        let serialized = serde_json::to_vec(&response)?;
+        if serialized.len() > MAX_MESSAGE_SIZE {
+            let message = format!("Message of {} bytes exceeds the size limit", serialized.len());
+            return Err(ConnectionError::Protocol { message });
+        }
+
+        let mut handle = self.output.borrow_mut();
+        handle.write_u32::<NativeEndian>(serialized.len() as u32)?;
+        handle.write_all(&serialized)?;
+        handle.flush()?;
 
-        io::stdin().read_exact(&mut buffer).context("Failed to read message")?;
-        serde_json::from_slice(&buffer).context("Failed to deserialize message")
+        Ok(())
     }
 
-    fn send(&self, response: &ConnectorResponse) -> Result<()> {
-        let serialized = serde_json::to_vec(&response).context("Failed to serialize message")?;
+    /// Reports progress of a long-running operation.
+    ///
+    /// Does nothing unless progress reporting was enabled by the request.
+    ///
+    /// # Parameters
+    ///
+    /// - `operation`: A name of the operation, usually the request command.
+    /// - `percent`: A completion percentage, if it can be determined.
+    /// - `message`: A human-readable description of the current step.
+    ///
+    pub(crate) fn progress(
+        &self,
+        operation: &str,
+        percent: Option<u8>,
+        message: &str,
+    ) -> Result<()> {
+        if !self.progress.get() {
+            return Ok(());
+        }
+
+        let response = ConnectorResponse::Progress {
+            operation: operation.into(),
+            percent,
+            message: message.into(),
+        };
 
-        let stdout = io::stdout();
-        let mut handle = stdout.lock();
-        handle
-            .write_u32::<NativeEndian>(serialized.len() as u32)
-            .context("Failed to write message size")?;
-        handle.write_all(&serialized).context("Failed to write message")?;
-        handle.flush().context("Failed to flush stdout")?;
+        self.send(&response).context("Failed to send progress")?;
+        info!("Sent a progress message: {message}");
 
         Ok(())
     }
 
+    /// Returns the ID of the current operation, if it was specified by the request.
+    pub(crate) fn operation(&self) -> Option<String> {
+        self.operation.borrow().clone()
+    }
+
     fn process(&self, request: &ConnectorRequest) -> Result<ConnectorResponse> {
         // If not in debug mode, discard both stdout and stderr
         // If in debug mode, redirect them to the log files
@@ -0,0 +1,77 @@
+use std::fs::{read_to_string, remove_file, write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use cfg_if::cfg_if;
+use log::warn;
+
+/// A file that contains the PID of the running connector process.
+///
+/// Used by process supervisors to detect and restart the connector.
+/// The file is created when the connector starts and removed when
+/// it is dropped, which happens when the connector shuts down cleanly.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Writes the PID of the current process to the file.
+    ///
+    /// If the file already exists and contains a PID of a running process,
+    /// a warning is logged and the file is overwritten anyway.
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(pid) = read_to_string(path).ok().and_then(|pid| pid.trim().parse().ok())
+            && is_running(pid)
+        {
+            warn!("PID file {} belongs to running process {pid}", path.display());
+        }
+
+        write(path, format!("{}\n", std::process::id())).context("Failed to write PID file")?;
+        Ok(Self { path: path.into() })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(error) = remove_file(&self.path).context("Failed to remove PID file") {
+            warn!("{error:?}");
+        }
+    }
+}
+
+/// Checks whether a process with the specified PID is running.
+fn is_running(pid: u32) -> bool {
+    cfg_if! {
+        if #[cfg(platform_windows)] {
+            use windows::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+            use windows::Win32::System::Threading::{
+                GetExitCodeProcess,
+                OpenProcess,
+                PROCESS_QUERY_LIMITED_INFORMATION,
+            };
+
+            unsafe {
+                let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+                    return false;
+                };
+
+                let mut code = 0;
+                let result = GetExitCodeProcess(handle, &mut code);
+                let _ = CloseHandle(handle);
+
+                result.is_ok() && code == STILL_ACTIVE.0 as u32
+            }
+        } else {
+            use std::process::{Command, Stdio};
+
+            // Signal 0 only checks whether the process exists
+            Command::new("kill")
+                .arg("-0")
+                .arg(pid.to_string())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        }
+    }
+}
@@ -5,6 +5,7 @@ use log::{info, warn};
 use crate::components::runtime::Runtime;
 use crate::connector::Connection;
 use crate::connector::request::{
+    CancelOperation,
     CreateProfile,
     GetConfig,
     GetProfileList,
@@ -27,6 +28,7 @@ use crate::connector::request::{
 use crate::connector::response::ConnectorResponse;
 use crate::console::Run;
 use crate::console::app::{
+    HTTPCredentialsConfig,
     ProfileCreateCommand,
     ProfileRemoveCommand,
     ProfileUpdateCommand,
@@ -37,10 +39,11 @@ use crate::console::app::{
     SiteUninstallCommand,
     SiteUpdateCommand,
 };
+use crate::error::CommandError;
 use crate::integrations;
 use crate::integrations::IntegrationInstallArgs;
 use crate::storage::Storage;
-use crate::utils::construct_certificates_and_client;
+use crate::utils::{Cancellation, ClientArgs, Progress, construct_certificates_and_client};
 
 pub trait Process {
     fn process(&self, connection: &Connection) -> Result<ConnectorResponse>;
@@ -82,16 +85,32 @@ impl Process for SetConfig {
 }
 
 impl Process for InstallRuntime {
-    fn process(&self, _connection: &Connection) -> Result<ConnectorResponse> {
+    fn process(&self, connection: &Connection) -> Result<ConnectorResponse> {
+        connection.progress("InstallRuntime", None, "Installing the runtime")?;
+        let progress = Progress::new(|percent, message| {
+            connection.progress("InstallRuntime", percent, message)
+        });
+
         #[cfg(platform_linux)]
         let options = self.clone().unwrap_or_default();
 
         let command = RuntimeInstallCommand {
             #[cfg(platform_linux)]
             link: options.link,
+            runtime_version: None,
+            channel: None,
+            notify: false,
+            operation: connection.operation(),
         };
-        command.run()?;
+        let result = command._run(Some(&progress));
+
+        // The operation has finished, so a later cancellation must not affect a new one
+        if let Some(id) = connection.operation() {
+            Cancellation::new(connection.dirs, &id)?.clear();
+        }
 
+        result?;
+        connection.progress("InstallRuntime", Some(100), "Runtime installed")?;
         Ok(ConnectorResponse::RuntimeInstalled)
     }
 }
@@ -115,10 +134,12 @@ impl Process for GetSiteList {
 impl Process for LaunchSite {
     fn process(&self, _connection: &Connection) -> Result<ConnectorResponse> {
         let command = SiteLaunchCommand {
-            id: self.id,
+            id: Some(self.id),
+            document_url: None,
             url: self.url.to_owned(),
             protocol: None,
             arguments: vec![],
+            runtime_arg: vec![],
             #[cfg(platform_macos)]
             direct_launch: false,
         };
@@ -129,24 +150,38 @@ impl Process for LaunchSite {
 }
 
 impl Process for InstallSite {
-    fn process(&self, _connection: &Connection) -> Result<ConnectorResponse> {
+    fn process(&self, connection: &Connection) -> Result<ConnectorResponse> {
+        connection.progress("InstallSite", None, "Installing the web app")?;
+        let progress =
+            Progress::new(|percent, message| connection.progress("InstallSite", percent, message));
+
         let command = SiteInstallCommand {
-            manifest_url: self.manifest_url.to_owned(),
+            manifest_url: Some(self.manifest_url.to_owned()),
             document_url: self.document_url.to_owned(),
             start_url: self.start_url.to_owned(),
             icon_url: self.icon_url.to_owned(),
+            no_icon: false,
+            save_manifest: false,
+            allow_file_url: false,
             profile: self.profile.to_owned(),
+            profile_name: None,
+            profile_exact: false,
             name: self.name.to_owned(),
             description: self.description.to_owned(),
             categories: self.categories.to_owned(),
             keywords: self.keywords.to_owned(),
             launch_on_login: Some(self.launch_on_login),
             launch_on_browser: Some(self.launch_on_browser),
+            single_instance: Some(self.single_instance),
             launch_now: self.launch_now,
+            runtime_arg: vec![],
+            force_duplicate: true,
             system_integration: true,
             client: self.client.to_owned().into(),
+            credentials: HTTPCredentialsConfig::default(),
         };
-        let ulid = command._run()?;
+        let ulid = command._run_with_progress(Some(&progress))?;
+        connection.progress("InstallSite", Some(100), "Web app installed")?;
 
         Ok(ConnectorResponse::SiteInstalled(ulid))
     }
@@ -154,7 +189,12 @@ impl Process for InstallSite {
 
 impl Process for UninstallSite {
     fn process(&self, _connection: &Connection) -> Result<ConnectorResponse> {
-        let command = SiteUninstallCommand { id: self.id, quiet: true, system_integration: true };
+        let command = SiteUninstallCommand {
+            id: Some(self.id),
+            document_url: None,
+            quiet: true,
+            system_integration: true,
+        };
         command.run()?;
 
         Ok(ConnectorResponse::SiteUninstalled)
@@ -166,21 +206,48 @@ impl Process for UpdateSite {
         // `categories` and `keywords` need some weird hack to be compatible with Clap
         // See [`crate::console::store_value_vec`] for more details
         let command = SiteUpdateCommand {
-            id: self.id,
+            id: Some(self.id),
+            document_url: None,
+            all: false,
+            jobs: 1,
+            pattern: None,
+            profile: None,
+            stale: None,
+            notify: false,
+            quiet: true,
+            reset: false,
+            dry_run: false,
+            icon_only: false,
+            save_manifest: false,
+            from_cache: false,
             start_url: self.start_url.to_owned(),
             icon_url: self.icon_url.to_owned(),
+            icon: None,
             name: self.name.to_owned(),
+            display_name: None,
+            clear_display_name: false,
             description: self.description.to_owned(),
             categories: self.categories.clone().map(|x| x.unwrap_or_else(|| vec!["".into()])),
+            add_category: vec![],
+            remove_category: vec![],
             keywords: self.keywords.clone().map(|x| x.unwrap_or_else(|| vec!["".into()])),
             enabled_url_handlers: self.enabled_url_handlers.to_owned(),
             enabled_protocol_handlers: self.enabled_protocol_handlers.to_owned(),
             launch_on_login: self.launch_on_login,
             launch_on_browser: self.launch_on_browser,
+            single_instance: self.single_instance,
+            locale: None,
+            proxy: None,
+            size: None,
+            position: None,
+            maximized: None,
+            notes: None,
+            notes_file: None,
             update_manifest: self.update_manifest,
             update_icons: self.update_icons,
             system_integration: true,
             client: self.client.to_owned().into(),
+            credentials: HTTPCredentialsConfig::default(),
         };
         command.run()?;
 
@@ -191,24 +258,23 @@ impl Process for UpdateSite {
 impl Process for UpdateAllSites {
     fn process(&self, connection: &Connection) -> Result<ConnectorResponse> {
         let mut storage = Storage::load(connection.dirs)?;
+        let total = storage.sites.len();
 
-        for site in storage.sites.values_mut() {
-            info!("Updating web app {}", site.ulid);
+        for (i, site) in storage.sites.values_mut().enumerate() {
+            info!("Updating web app {site}");
             let old_name = site.name();
 
-            let client = construct_certificates_and_client(
-                self.client.user_agent.as_deref(),
-                &self.client.tls_root_certificates_der,
-                &self.client.tls_root_certificates_pem,
-                self.client.tls_danger_accept_invalid_certs,
-                self.client.tls_danger_accept_invalid_hostnames,
-            )?;
+            let percent = (i * 100 / total) as u8;
+            let message = format!("Updating web app {site}");
+            connection.progress("UpdateAllSites", Some(percent), &message)?;
+
+            let client = construct_certificates_and_client(&ClientArgs::from(&self.client))?;
 
             if self.update_manifest {
                 site.update(&client).context("Failed to update web app manifest")?;
             }
 
-            integrations::install(&IntegrationInstallArgs {
+            let hash = integrations::update(&IntegrationInstallArgs {
                 site,
                 dirs: connection.dirs,
                 client: Some(&client),
@@ -217,6 +283,10 @@ impl Process for UpdateAllSites {
                 old_name: Some(&old_name),
             })
             .context("Failed to update system integration")?;
+
+            if hash.is_some() {
+                site.config.integration_hash = hash;
+            }
         }
 
         storage.write(connection.dirs)?;
@@ -237,6 +307,8 @@ impl Process for CreateProfile {
             name: self.name.to_owned(),
             description: self.description.to_owned(),
             template: self.template.to_owned(),
+            template_exclude: vec![],
+            from: None,
         };
         let ulid = command._run()?;
 
@@ -246,7 +318,8 @@ impl Process for CreateProfile {
 
 impl Process for RemoveProfile {
     fn process(&self, _connection: &Connection) -> Result<ConnectorResponse> {
-        let command = ProfileRemoveCommand { id: self.id, quiet: true };
+        let command =
+            ProfileRemoveCommand { id: Some(self.id), name: None, exact: false, quiet: true };
         command.run()?;
 
         Ok(ConnectorResponse::ProfileRemoved)
@@ -256,10 +329,16 @@ impl Process for RemoveProfile {
 impl Process for UpdateProfile {
     fn process(&self, _connection: &Connection) -> Result<ConnectorResponse> {
         let command = ProfileUpdateCommand {
-            id: self.id,
+            id: Some(self.id),
+            profile_name: None,
+            exact: false,
             name: self.name.to_owned(),
             description: self.description.to_owned(),
             template: self.template.to_owned(),
+            template_exclude: vec![],
+            add_tag: vec![],
+            remove_tag: vec![],
+            template_strategy: "overwrite".into(),
         };
         command.run()?;
 
@@ -282,7 +361,7 @@ impl Process for PatchAllProfiles {
 
         if self.patch_profiles {
             for profile in storage.profiles.values() {
-                info!("Patching profile {}", profile.ulid);
+                info!("Patching profile {profile}");
                 profile.patch(connection.dirs)?;
             }
         }
@@ -294,7 +373,7 @@ impl Process for PatchAllProfiles {
 impl Process for RegisterProtocolHandler {
     fn process(&self, connection: &Connection) -> Result<ConnectorResponse> {
         let mut storage = Storage::load(connection.dirs)?;
-        let site = storage.sites.get_mut(&self.site).context("Web app does not exist")?;
+        let site = storage.sites.get_mut(&self.site).context(CommandError::SiteNotFound)?;
 
         // Check if this protocol scheme is already used in custom or manifest handlers
         #[rustfmt::skip]
@@ -330,7 +409,7 @@ impl Process for RegisterProtocolHandler {
 impl Process for UnregisterProtocolHandler {
     fn process(&self, connection: &Connection) -> Result<ConnectorResponse> {
         let mut storage = Storage::load(connection.dirs)?;
-        let site = storage.sites.get_mut(&self.site).context("Web app does not exist")?;
+        let site = storage.sites.get_mut(&self.site).context(CommandError::SiteNotFound)?;
 
         // Remove handler from both lists
         site.config.enabled_protocol_handlers.retain(|it| it != &self.handler.protocol);
@@ -351,3 +430,12 @@ impl Process for UnregisterProtocolHandler {
         Ok(ConnectorResponse::ProtocolHandlerUnregistered)
     }
 }
+
+impl Process for CancelOperation {
+    fn process(&self, connection: &Connection) -> Result<ConnectorResponse> {
+        info!("Cancelling operation {}", self.id);
+        Cancellation::new(connection.dirs, &self.id)?.cancel()?;
+
+        Ok(ConnectorResponse::OperationCancelled)
+    }
+}
@@ -283,6 +283,10 @@ pub struct InstallSite {
     #[serde(default)]
     pub launch_on_browser: bool,
 
+    /// Whether the web app should only have a single window.
+    #[serde(default)]
+    pub single_instance: bool,
+
     /// Whether the web app should be launch when the installation finishes.
     #[serde(default)]
     pub launch_now: bool,
@@ -384,6 +388,10 @@ pub struct UpdateSite {
     #[serde(default)]
     pub launch_on_browser: Option<bool>,
 
+    /// Whether the web app should only have a single window (default: `false`).
+    #[serde(default)]
+    pub single_instance: Option<bool>,
+
     /// Whether the manifest should be updated (default: `true`).
     #[serde(default = "default_as_true")]
     pub update_manifest: bool,
@@ -579,6 +587,27 @@ pub struct UnregisterProtocolHandler {
     pub handler: ProtocolHandlerResource,
 }
 
+/// Cancels an operation that is currently in progress.
+///
+/// Currently, only downloads performed while installing the runtime (and 7-Zip)
+/// can be cancelled. The operation must have been started by a request with
+/// the same ID in its `operation` field. Cancelling an operation that has not
+/// started yet is not an error, and will cancel it as soon as it starts.
+///
+/// # Parameters
+///
+/// See [fields](#fields).
+///
+/// # Returns
+///
+/// [`ConnectorResponse::OperationCancelled`] - No data.
+///
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+pub struct CancelOperation {
+    /// An operation ID, consisting of only alphanumeric characters and dashes.
+    pub id: String,
+}
+
 /// Contains a HTTP client configuration.
 #[derive(Deserialize, Debug, Eq, PartialEq, Clone, Default)]
 pub struct HTTPClientConfig {
@@ -613,6 +642,18 @@ impl Into<crate::console::app::HTTPClientConfig> for HTTPClientConfig {
     }
 }
 
+impl<'a> From<&'a HTTPClientConfig> for crate::utils::ClientArgs<'a> {
+    fn from(config: &'a HTTPClientConfig) -> Self {
+        Self {
+            user_agent: config.user_agent.as_deref(),
+            certificates_der: &config.tls_root_certificates_der,
+            certificates_pem: &config.tls_root_certificates_pem,
+            danger_accept_invalid_certs: config.tls_danger_accept_invalid_certs,
+            danger_accept_invalid_hostnames: config.tls_danger_accept_invalid_hostnames,
+        }
+    }
+}
+
 deserialize_unit_struct!(GetSystemVersions);
 deserialize_unit_struct!(GetConfig);
 deserialize_unit_struct!(GetSiteList);
@@ -637,4 +678,5 @@ build_request_enum!(
     PatchAllProfiles,
     RegisterProtocolHandler,
     UnregisterProtocolHandler,
+    CancelOperation,
 );
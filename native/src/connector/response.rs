@@ -83,6 +83,24 @@ pub enum ConnectorResponse {
     /// Protocol handler has been unregistered.
     ProtocolHandlerUnregistered,
 
+    /// Operation has been signalled to stop.
+    OperationCancelled,
+
+    /// Progress of a long-running operation.
+    ///
+    /// Only sent when the request enables progress reporting. Any number of
+    /// progress messages may be sent before exactly one final response.
+    Progress {
+        /// Name of the operation, usually the request command.
+        operation: String,
+
+        /// Completion percentage, if it can be determined.
+        percent: Option<u8>,
+
+        /// Human-readable description of the current step.
+        message: String,
+    },
+
     /// Something went wrong...
     Error(String),
 }
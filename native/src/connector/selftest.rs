@@ -0,0 +1,86 @@
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+
+use crate::connector::{Connection, ConnectorResponse};
+use crate::directories::ProjectDirs;
+
+/// A minimal SVG icon, so the system integration does not need the network.
+const ICON: &str = concat!(
+    r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 1 1">"#,
+    r#"<rect width="1" height="1"/></svg>"#,
+);
+
+/// Exercises the full request and response cycle with synthetic messages.
+///
+/// Creates a profile and a web app, checks that both are listed, and then removes
+/// them again. The web app manifest and icon are passed as data URLs, so the network
+/// is not needed. Both the provided directories and the ones that commands obtain
+/// themselves should point to a temporary location, and system integration should
+/// be disabled with the `FIREFOXPWA_NO_INTEGRATIONS` variable.
+///
+/// Returns an error with the name of the failing step.
+pub fn self_test(dirs: &ProjectDirs) -> Result<()> {
+    let request = |step: &str, message: Value| -> Result<ConnectorResponse> {
+        Connection::handle_message(dirs, message).with_context(|| format!("Step failed: {step}"))
+    };
+
+    let step = "Create a profile";
+    let message = json!({ "cmd": "CreateProfile", "params": { "name": "Self-test" } });
+    let ConnectorResponse::ProfileCreated(profile) = request(step, message)? else {
+        bail!("Step failed: {step}: Unexpected response");
+    };
+
+    let step = "List profiles";
+    let message = json!({ "cmd": "GetProfileList", "params": null });
+    match request(step, message)? {
+        ConnectorResponse::ProfileList(profiles) if profiles.contains_key(&profile) => {}
+        _ => bail!("Step failed: {step}: Profile {profile} is not listed"),
+    }
+
+    let step = "Create a web app";
+    let message = json!({ "cmd": "InstallSite", "params": {
+        "manifest_url": manifest_url(),
+        "document_url": "https://example.com/",
+        "profile": profile,
+    }});
+    let ConnectorResponse::SiteInstalled(site) = request(step, message)? else {
+        bail!("Step failed: {step}: Unexpected response");
+    };
+
+    let step = "List web apps";
+    let message = json!({ "cmd": "GetSiteList", "params": null });
+    match request(step, message)? {
+        ConnectorResponse::SiteList(sites) if sites.contains_key(&site) => {}
+        _ => bail!("Step failed: {step}: Web app {site} is not listed"),
+    }
+
+    let step = "Delete the web app";
+    let message = json!({ "cmd": "UninstallSite", "params": { "id": site } });
+    let ConnectorResponse::SiteUninstalled = request(step, message)? else {
+        bail!("Step failed: {step}: Unexpected response");
+    };
+
+    let step = "Delete the profile";
+    let message = json!({ "cmd": "RemoveProfile", "params": { "id": profile } });
+    let ConnectorResponse::ProfileRemoved = request(step, message)? else {
+        bail!("Step failed: {step}: Unexpected response");
+    };
+
+    Ok(())
+}
+
+/// Builds a data URL with a static web app manifest.
+fn manifest_url() -> String {
+    let manifest = json!({
+        "name": "Self-test",
+        "start_url": "https://example.com/",
+        "scope": "https://example.com/",
+        "icons": [{
+            "src": format!("data:image/svg+xml,{}", urlencoding::encode(ICON)),
+            "sizes": "any",
+            "type": "image/svg+xml",
+        }],
+    });
+
+    format!("data:application/manifest+json,{}", urlencoding::encode(&manifest.to_string()))
+}
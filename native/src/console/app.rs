@@ -9,7 +9,24 @@ use url::Url;
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 #[clap(propagate_version = true)]
 #[clap(version)]
-pub enum App {
+pub struct App {
+    /// Load default options from a TOML config file
+    /// {n}Defaults to `firefoxpwa/config.toml` in the user config directory
+    #[clap(long, global = true, value_hint = clap::ValueHint::FilePath)]
+    pub config: Option<PathBuf>,
+
+    /// Import additional root certificates from a PEM file for all requests
+    /// {n}Also applies to runtime and 7-Zip downloads, and overrides the
+    /// `FIREFOXPWA_CAFILE` variable and the `cafile` config option
+    #[clap(long, global = true, value_hint = clap::ValueHint::FilePath)]
+    pub cafile: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    pub command: AppCommand,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub enum AppCommand {
     /// Manage web apps
     #[clap(subcommand)]
     Site(SiteCommand),
@@ -21,6 +38,22 @@ pub enum App {
     /// Manage the runtime
     #[clap(subcommand)]
     Runtime(RuntimeCommand),
+
+    /// Manage the storage
+    #[clap(subcommand)]
+    Storage(StorageCommand),
+
+    /// Manage the global configuration
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+
+    /// Manage the whole installation
+    #[clap(name = "self", subcommand)]
+    Installation(InstallationCommand),
+
+    /// Internal: Debug system integration
+    #[clap(subcommand, hide = true)]
+    Integrations(IntegrationsCommand),
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
@@ -36,16 +69,52 @@ pub enum SiteCommand {
 
     /// Update a web app
     Update(SiteUpdateCommand),
+
+    /// Verify system integration of web apps
+    Verify(SiteVerifyCommand),
+
+    /// Repair system integration of web apps
+    Repair(SiteRepairCommand),
+
+    /// Pin a web app to the taskbar or the Dock
+    Pin(SitePinCommand),
+
+    /// Unpin a web app from the taskbar or the Dock
+    Unpin(SiteUnpinCommand),
+
+    /// List web apps organized by their groups
+    List(SiteListCommand),
+
+    /// Show details of a web app
+    Show(SiteShowCommand),
+
+    /// Add a web app to a group
+    Group(SiteGroupCommand),
+
+    /// Remove a web app from its group
+    Ungroup(SiteUngroupCommand),
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 pub struct SiteLaunchCommand {
     /// Web app ID
-    pub id: Ulid,
+    #[clap(required_unless_present = "document_url")]
+    pub id: Option<Ulid>,
+
+    /// Document URL of the web app, as an alternative to the web app ID
+    /// {n}If multiple web apps match the URL, the web app ID is required
+    #[clap(long, conflicts_with = "id", value_hint = clap::ValueHint::Url)]
+    pub document_url: Option<Url>,
 
     /// Arguments passed to the runtime
     pub arguments: Vec<String>,
 
+    /// Append a raw argument to the runtime for this launch only, such as `--safe-mode`
+    /// {n}Unlike other arguments, it does not replace the default runtime arguments.
+    /// It can be repeated and is passed without any shell interpretation
+    #[clap(long = "runtime-arg", allow_hyphen_values = true)]
+    pub runtime_arg: Vec<String>,
+
     /// Launch web app on a custom start URL
     #[clap(long, conflicts_with = "protocol", value_hint = clap::ValueHint::Url)]
     pub url: Vec<Url>,
@@ -63,27 +132,52 @@ pub struct SiteLaunchCommand {
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 pub struct SiteInstallCommand {
     /// Direct URL of the site's web app manifest
+    /// {n}When omitted in an interactive terminal, the installation wizard is started
     #[clap(value_hint = clap::ValueHint::Url)]
-    pub manifest_url: Url,
+    pub manifest_url: Option<Url>,
 
     /// Direct URL of the site's main document
     /// {n}Defaults to the result of parsing a manifest URL with `.`
     #[clap(long, value_hint = clap::ValueHint::Url)]
     pub document_url: Option<Url>,
 
+    /// Allow local `file://` manifest and document URLs
+    /// {n}Local web apps can read other local files, so they are not allowed by default
+    #[clap(long)]
+    pub allow_file_url: bool,
+
     /// Profile where this web app will be installed
     /// {n}Defaults to the shared profile
     #[clap(long)]
     pub profile: Option<Ulid>,
 
+    /// Name of the profile where this web app will be installed, as an alternative to its ID
+    /// {n}Names are compared case-insensitively unless --profile-exact is used
+    #[clap(long, conflicts_with = "profile")]
+    pub profile_name: Option<String>,
+
+    /// Match the profile name exactly, including its case
+    #[clap(long, requires = "profile_name")]
+    pub profile_exact: bool,
+
     /// Set a custom web app start URL
     #[clap(long, value_hint = clap::ValueHint::Url)]
     pub start_url: Option<Url>,
 
     /// Set a custom web app icon URL
-    #[clap(long, value_hint = clap::ValueHint::Url)]
+    #[clap(long, conflicts_with = "no_icon", value_hint = clap::ValueHint::Url)]
     pub icon_url: Option<Url>,
 
+    /// Skip downloading and converting icons
+    /// {n}System integration will use a generated placeholder icon
+    #[clap(long)]
+    pub no_icon: bool,
+
+    /// Keep the downloaded manifest JSON alongside the web app
+    /// {n}It can be used later to update the web app with --from-cache
+    #[clap(long)]
+    pub save_manifest: bool,
+
     /// Set a custom web app name
     #[clap(long)]
     pub name: Option<String>,
@@ -108,10 +202,25 @@ pub struct SiteInstallCommand {
     #[clap(long)]
     pub launch_on_browser: Option<bool>,
 
+    /// Set the web app to only have a single window
+    /// {n}Launching it again will focus the existing window
+    #[clap(long)]
+    pub single_instance: Option<bool>,
+
     /// Launch this web app when the installation finishes
     #[clap(long)]
     pub launch_now: bool,
 
+    /// Append a raw argument to the runtime when launching the web app
+    /// {n}It can be repeated and is passed without any shell interpretation
+    #[clap(long = "runtime-arg", requires = "launch_now", allow_hyphen_values = true)]
+    pub runtime_arg: Vec<String>,
+
+    /// Allow installing a web app whose document URL is already used by another web app
+    /// {n}Useful for installing the same web app into multiple profiles with different settings
+    #[clap(long)]
+    pub force_duplicate: bool,
+
     /// Disable system integration
     #[clap(long = "no-system-integration", action = ArgAction::SetFalse)]
     pub system_integration: bool,
@@ -119,15 +228,25 @@ pub struct SiteInstallCommand {
     /// Configuration of the HTTP client
     #[clap(flatten)]
     pub client: HTTPClientConfig,
+
+    /// Credentials for the manifest request
+    #[clap(flatten)]
+    pub credentials: HTTPCredentialsConfig,
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 pub struct SiteUninstallCommand {
     /// Web app ID
-    pub id: Ulid,
+    #[clap(required_unless_present = "document_url")]
+    pub id: Option<Ulid>,
+
+    /// Document URL of the web app, as an alternative to the web app ID
+    /// {n}If multiple web apps match the URL, the web app ID is required
+    #[clap(long, conflicts_with = "id", value_hint = clap::ValueHint::Url)]
+    pub document_url: Option<Url>,
 
     /// Disable any interactive prompts
-    #[clap(short, long)]
+    #[clap(short, long, visible_alias = "yes")]
     pub quiet: bool,
 
     /// Disable system integration
@@ -138,7 +257,154 @@ pub struct SiteUninstallCommand {
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 pub struct SiteUpdateCommand {
     /// Web app ID
-    pub id: Ulid,
+    #[clap(required_unless_present_any = ["all", "document_url"])]
+    pub id: Option<Ulid>,
+
+    /// Document URL of the web app, as an alternative to the web app ID
+    /// {n}If multiple web apps match the URL, the web app ID is required
+    #[clap(long, conflicts_with = "id", value_hint = clap::ValueHint::Url)]
+    pub document_url: Option<Url>,
+
+    /// Update manifests and system integration of all web apps
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "id",
+            "document_url",
+            "start_url",
+            "icon_url",
+            "icon",
+            "name",
+            "display_name",
+            "clear_display_name",
+            "description",
+            "categories",
+            "add_category",
+            "remove_category",
+            "keywords",
+            "enabled_url_handlers",
+            "enabled_protocol_handlers",
+            "launch_on_login",
+            "launch_on_browser",
+            "single_instance",
+            "locale",
+            "proxy",
+            "size",
+            "position",
+            "maximized",
+            "notes",
+            "notes_file",
+            "auth",
+            "headers",
+        ]
+    )]
+    pub all: bool,
+
+    /// Number of web apps updated concurrently
+    #[clap(
+        long,
+        requires = "all",
+        default_value_t = 4,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub jobs: u32,
+
+    /// Only include web apps whose name or document URL contains this text
+    /// {n}Text is matched case-insensitively
+    #[clap(long = "match", requires = "all")]
+    pub pattern: Option<String>,
+
+    /// Only include web apps installed in this profile
+    #[clap(long, requires = "all")]
+    pub profile: Option<Ulid>,
+
+    /// Only include web apps that were not updated within this duration
+    /// {n}Durations are numbers with a unit, such as `7d`, `12h` or `30m`
+    #[clap(long, requires = "all")]
+    pub stale: Option<String>,
+
+    /// Show a desktop notification with the result when finished
+    #[clap(long, requires = "all")]
+    pub notify: bool,
+
+    /// Disable any interactive prompts
+    #[clap(short, long, visible_alias = "yes", requires = "all")]
+    pub quiet: bool,
+
+    /// Discard all custom properties and take them from the manifest
+    /// {n}By default, custom name, description, start URL, icon,
+    /// categories and keywords are preserved when updating
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "start_url",
+            "icon_url",
+            "icon",
+            "name",
+            "display_name",
+            "clear_display_name",
+            "description",
+            "categories",
+            "add_category",
+            "remove_category",
+            "keywords",
+        ]
+    )]
+    pub reset: bool,
+
+    /// Only show what would change without applying anything
+    /// {n}Neither the storage nor the system integration is modified
+    #[clap(long, conflicts_with = "notify")]
+    pub dry_run: bool,
+
+    /// Only refresh web app icons and keep all other properties unchanged
+    /// {n}Icons are taken from the current manifest, and the system integration is updated
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "all",
+            "reset",
+            "save_manifest",
+            "from_cache",
+            "start_url",
+            "icon_url",
+            "icon",
+            "name",
+            "display_name",
+            "clear_display_name",
+            "description",
+            "categories",
+            "add_category",
+            "remove_category",
+            "keywords",
+            "enabled_url_handlers",
+            "enabled_protocol_handlers",
+            "launch_on_login",
+            "launch_on_browser",
+            "single_instance",
+            "locale",
+            "proxy",
+            "size",
+            "position",
+            "maximized",
+            "notes",
+            "notes_file",
+            "update_manifest",
+            "update_icons",
+            "system_integration",
+        ]
+    )]
+    pub icon_only: bool,
+
+    /// Keep the downloaded manifest JSON alongside the web app
+    /// {n}It can be used later to update the web app with --from-cache
+    #[clap(long, conflicts_with_all = ["dry_run", "update_manifest"])]
+    pub save_manifest: bool,
+
+    /// Reprocess the previously saved manifest instead of downloading it
+    /// {n}The manifest needs to be saved first with --save-manifest
+    #[clap(long, conflicts_with_all = ["save_manifest", "update_manifest"])]
+    pub from_cache: bool,
 
     /// Set a custom web app start URL
     #[clap(long, value_hint = clap::ValueHint::Url)]
@@ -148,10 +414,24 @@ pub struct SiteUpdateCommand {
     #[clap(long, value_hint = clap::ValueHint::Url)]
     pub icon_url: Option<Option<Url>>,
 
+    /// Set a custom web app icon from a local image file
+    /// {n}Takes precedence over the icon URL and manifest icons
+    #[clap(long, value_hint = clap::ValueHint::FilePath)]
+    pub icon: Option<Option<PathBuf>>,
+
     /// Set a custom web app name
     #[clap(long)]
     pub name: Option<Option<String>>,
 
+    /// Set a display name that is preferred over all other names
+    /// {n}It is never changed by manifest updates
+    #[clap(long, conflicts_with = "clear_display_name")]
+    pub display_name: Option<String>,
+
+    /// Clear the display name and use the regular name again
+    #[clap(long)]
+    pub clear_display_name: bool,
+
     /// Set a custom web app description
     #[clap(long)]
     pub description: Option<Option<String>>,
@@ -160,6 +440,16 @@ pub struct SiteUpdateCommand {
     #[clap(long)]
     pub categories: Option<Vec<String>>,
 
+    /// Add a custom web app category
+    /// {n}Starts from the current categories and can be repeated
+    #[clap(long = "add-category", conflicts_with = "categories")]
+    pub add_category: Vec<String>,
+
+    /// Remove a custom web app category
+    /// {n}Starts from the current categories and can be repeated
+    #[clap(long = "remove-category", conflicts_with = "categories")]
+    pub remove_category: Vec<String>,
+
     /// Set custom web app keywords
     #[clap(long)]
     pub keywords: Option<Vec<String>>,
@@ -180,6 +470,45 @@ pub struct SiteUpdateCommand {
     #[clap(long)]
     pub launch_on_browser: Option<bool>,
 
+    /// Set the web app to only have a single window.
+    /// {n}Launching it again will focus the existing window
+    #[clap(long)]
+    pub single_instance: Option<bool>,
+
+    /// Set a custom web app locale, such as `en-US`
+    /// {n}Defaults to the system locale
+    #[clap(long)]
+    pub locale: Option<Option<String>>,
+
+    /// Set a proxy for the web app, such as `socks5h://localhost:9050`
+    /// {n}Supported schemes are `http`, `socks4`, `socks5` and `socks5h`.
+    /// The web app needs its own profile, because proxy applies to the whole profile
+    #[clap(long, value_hint = clap::ValueHint::Url)]
+    pub proxy: Option<Option<Url>>,
+
+    /// Set a custom window size, such as `1200x800`
+    /// {n}Defaults to the size chosen by the runtime
+    #[clap(long)]
+    pub size: Option<Option<String>>,
+
+    /// Set a custom window position, such as `100,100`
+    /// {n}Positions that are off-screen are moved to the nearest visible area
+    #[clap(long, allow_hyphen_values = true)]
+    pub position: Option<Option<String>>,
+
+    /// Set the web app window to open maximized
+    #[clap(long)]
+    pub maximized: Option<bool>,
+
+    /// Set free-form notes about the web app
+    /// {n}Notes are only shown by `site show` and never changed by manifest updates
+    #[clap(long, conflicts_with = "notes_file")]
+    pub notes: Option<Option<String>>,
+
+    /// Set notes about the web app from the contents of a file
+    #[clap(long, value_hint = clap::ValueHint::FilePath)]
+    pub notes_file: Option<PathBuf>,
+
     /// Disable manifest updates
     #[clap(long = "no-manifest-updates", action = ArgAction::SetFalse)]
     pub update_manifest: bool,
@@ -195,6 +524,140 @@ pub struct SiteUpdateCommand {
     /// Configuration of the HTTP client
     #[clap(flatten)]
     pub client: HTTPClientConfig,
+
+    /// Credentials for the manifest request
+    #[clap(flatten)]
+    pub credentials: HTTPCredentialsConfig,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct SiteVerifyCommand {
+    /// Reinstall missing and remove stale system integration
+    #[clap(long)]
+    pub fix: bool,
+
+    /// Configuration of the HTTP client
+    #[clap(flatten)]
+    pub client: HTTPClientConfig,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct SiteRepairCommand {
+    /// Web app ID
+    #[clap(required_unless_present = "all")]
+    pub id: Option<Ulid>,
+
+    /// Repair all web apps
+    #[clap(long, conflicts_with = "id")]
+    pub all: bool,
+
+    /// Only include web apps whose name or document URL contains this text
+    /// {n}Text is matched case-insensitively
+    #[clap(long = "match", requires = "all")]
+    pub pattern: Option<String>,
+
+    /// Only include web apps installed in this profile
+    #[clap(long, requires = "all")]
+    pub profile: Option<Ulid>,
+
+    /// Configuration of the HTTP client
+    #[clap(flatten)]
+    pub client: HTTPClientConfig,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct SitePinCommand {
+    /// Web app ID
+    #[clap(required_unless_present = "document_url")]
+    pub id: Option<Ulid>,
+
+    /// Document URL of the web app, as an alternative to the web app ID
+    /// {n}If multiple web apps match the URL, the web app ID is required
+    #[clap(long, conflicts_with = "id", value_hint = clap::ValueHint::Url)]
+    pub document_url: Option<Url>,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct SiteUnpinCommand {
+    /// Web app ID
+    #[clap(required_unless_present = "document_url")]
+    pub id: Option<Ulid>,
+
+    /// Document URL of the web app, as an alternative to the web app ID
+    /// {n}If multiple web apps match the URL, the web app ID is required
+    #[clap(long, conflicts_with = "id", value_hint = clap::ValueHint::Url)]
+    pub document_url: Option<Url>,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct SiteListCommand {
+    /// Only show web apps in this group
+    #[clap(long)]
+    pub group: Option<String>,
+
+    /// Sort web apps within groups by their ID (creation time), name or launch statistics
+    /// {n}Statistics are sorted in descending order, with web apps that were never launched last
+    #[clap(
+        long,
+        default_value = "ulid",
+        value_parser = ["ulid", "name", "last-launched", "launch-count"]
+    )]
+    pub sort: String,
+
+    /// Reverse the sort order within groups
+    #[clap(long)]
+    pub reverse: bool,
+
+    /// Only show web apps installed since this date
+    /// {n}Accepts RFC 3339 timestamps or dates in the `YYYY-MM-DD` format
+    #[clap(long)]
+    pub since: Option<String>,
+
+    /// Only print the number of web apps
+    #[clap(long, conflicts_with = "verbose")]
+    pub count: bool,
+
+    /// Show launch and manifest update statistics of web apps
+    #[clap(short, long)]
+    pub verbose: bool,
+
+    /// Only show web apps whose manifest was not fetched recently
+    /// {n}Their names or icons might be out of date
+    #[clap(long)]
+    pub outdated: bool,
+
+    /// Number of days after which the manifest is not recent anymore
+    #[clap(long, requires = "outdated", default_value_t = 7)]
+    pub stale_days: u64,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct SiteShowCommand {
+    /// Web app ID
+    #[clap(long)]
+    pub id: Ulid,
+
+    /// Print all stored fields of the web app as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct SiteGroupCommand {
+    /// Web app ID
+    #[clap(long)]
+    pub id: Ulid,
+
+    /// Name of the group
+    #[clap(long)]
+    pub group: String,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct SiteUngroupCommand {
+    /// Web app ID
+    #[clap(long)]
+    pub id: Ulid,
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
@@ -210,10 +673,69 @@ pub enum ProfileCommand {
 
     /// Update an existing profile
     Update(ProfileUpdateCommand),
+
+    /// Rename an existing profile
+    Rename(ProfileRenameCommand),
+
+    /// Print the path to the profile directory
+    Path(ProfilePathCommand),
+
+    /// Show details of a profile and its web apps
+    Show(ProfileShowCommand),
+
+    /// Move all web apps from one profile into another profile
+    Merge(ProfileMergeCommand),
+
+    /// Manage profile extensions
+    #[clap(subcommand)]
+    Extensions(ProfileExtensionsCommand),
+
+    /// Manage profile templates
+    #[clap(subcommand)]
+    Template(ProfileTemplateCommand),
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
-pub struct ProfileListCommand {}
+pub struct ProfileListCommand {
+    /// Profile ID
+    /// {n}If specified, only this profile and its web apps are shown
+    pub id: Option<Ulid>,
+
+    /// Profile name, as an alternative to the profile ID
+    /// {n}Names are compared case-insensitively unless --exact is used
+    #[clap(long, conflicts_with = "id")]
+    pub name: Option<String>,
+
+    /// Match the profile name exactly, including its case
+    #[clap(long, requires = "name")]
+    pub exact: bool,
+
+    /// Print profiles and web apps as JSON
+    #[clap(long)]
+    pub json: bool,
+
+    /// Sort profiles by their ID (creation time), name or statistics
+    /// {n}Statistics are sorted in descending order, and disk usage may be slow to calculate
+    #[clap(
+        long,
+        default_value = "ulid",
+        value_parser = ["ulid", "name", "last-launched", "launch-count", "disk-usage"]
+    )]
+    pub sort: String,
+
+    /// Reverse the sort order
+    #[clap(long)]
+    pub reverse: bool,
+
+    /// Only show profiles created since this date
+    /// {n}Accepts RFC 3339 timestamps or dates in the `YYYY-MM-DD` format
+    #[clap(long, conflicts_with_all = ["id", "name"])]
+    pub since: Option<String>,
+
+    /// Only print the number of profiles
+    #[clap(long, conflicts_with = "json")]
+    pub count: bool,
+}
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 pub struct ProfileCreateCommand {
@@ -230,22 +752,54 @@ pub struct ProfileCreateCommand {
     /// will be copied to a newly-created profile
     #[clap(long, value_hint = clap::ValueHint::DirPath)]
     pub template: Option<PathBuf>,
+
+    /// Exclude template files matching this glob pattern
+    /// {n}Patterns are matched against paths relative to the template,
+    /// such as `cookies.sqlite` or `cache2/**`, and can be repeated
+    #[clap(long = "template-exclude", requires = "template")]
+    pub template_exclude: Vec<String>,
+
+    /// Copy the configuration of an existing profile
+    /// {n}Only prefs from `user.js`, extensions and the `chrome` directory
+    /// are copied, while cookies, history and other data are not
+    #[clap(long, conflicts_with = "template")]
+    pub from: Option<Ulid>,
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 pub struct ProfileRemoveCommand {
     /// Profile ID
-    pub id: Ulid,
+    #[clap(required_unless_present = "name")]
+    pub id: Option<Ulid>,
+
+    /// Profile name, as an alternative to the profile ID
+    /// {n}Names are compared case-insensitively unless --exact is used
+    #[clap(long, conflicts_with = "id")]
+    pub name: Option<String>,
+
+    /// Match the profile name exactly, including its case
+    #[clap(long, requires = "name")]
+    pub exact: bool,
 
     /// Disable any interactive prompts
-    #[clap(short, long)]
+    #[clap(short, long, visible_alias = "yes")]
     pub quiet: bool,
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 pub struct ProfileUpdateCommand {
     /// Profile ID
-    pub id: Ulid,
+    #[clap(required_unless_present = "profile_name")]
+    pub id: Option<Ulid>,
+
+    /// Current profile name, as an alternative to the profile ID
+    /// {n}Names are compared case-insensitively unless --exact is used
+    #[clap(long, conflicts_with = "id")]
+    pub profile_name: Option<String>,
+
+    /// Match the current profile name exactly, including its case
+    #[clap(long, requires = "profile_name")]
+    pub exact: bool,
 
     /// Set a profile name
     #[clap(long)]
@@ -260,6 +814,210 @@ pub struct ProfileUpdateCommand {
     /// will be copied to the currently-updated profile
     #[clap(long, value_hint = clap::ValueHint::DirPath)]
     pub template: Option<PathBuf>,
+
+    /// Exclude template files matching this glob pattern
+    /// {n}Patterns are matched against paths relative to the template,
+    /// such as `cookies.sqlite` or `cache2/**`, and can be repeated
+    #[clap(long = "template-exclude", requires = "template")]
+    pub template_exclude: Vec<String>,
+
+    /// Add a profile tag
+    /// {n}Tags that are already present are ignored, and it can be repeated
+    #[clap(long = "add-tag")]
+    pub add_tag: Vec<String>,
+
+    /// Remove a profile tag
+    /// {n}It can be repeated
+    #[clap(long = "remove-tag")]
+    pub remove_tag: Vec<String>,
+
+    /// How to handle files that already exist in the profile
+    /// {n}`overwrite` replaces them with template files, `merge` keeps them,
+    /// and `skip` does not apply the template if any of its files already exist
+    #[clap(
+        long,
+        requires = "template",
+        default_value = "overwrite",
+        value_parser = ["overwrite", "merge", "skip"]
+    )]
+    pub template_strategy: String,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+#[clap(allow_missing_positional = true)]
+pub struct ProfileRenameCommand {
+    /// Profile ID
+    #[clap(required_unless_present = "profile_name")]
+    pub id: Option<Ulid>,
+
+    /// New profile name
+    pub name: String,
+
+    /// Current profile name, as an alternative to the profile ID
+    /// {n}Names are compared case-insensitively unless --exact is used
+    #[clap(long = "name", conflicts_with = "id")]
+    pub profile_name: Option<String>,
+
+    /// Match the current profile name exactly, including its case
+    #[clap(long, requires = "profile_name")]
+    pub exact: bool,
+
+    /// Allow using a name that is already used by another profile
+    /// {n}Names are compared case-insensitively
+    #[clap(long)]
+    pub allow_duplicate: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct ProfilePathCommand {
+    /// Profile ID
+    #[clap(long, required_unless_present = "name")]
+    pub id: Option<Ulid>,
+
+    /// Profile name, as an alternative to the profile ID
+    /// {n}Names are compared case-insensitively unless --exact is used
+    #[clap(long, conflicts_with = "id")]
+    pub name: Option<String>,
+
+    /// Match the profile name exactly, including its case
+    #[clap(long, requires = "name")]
+    pub exact: bool,
+
+    /// Print the path as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct ProfileShowCommand {
+    /// Profile ID
+    #[clap(long, required_unless_present = "name")]
+    pub id: Option<Ulid>,
+
+    /// Profile name, as an alternative to the profile ID
+    /// {n}Names are compared case-insensitively unless --exact is used
+    #[clap(long, conflicts_with = "id")]
+    pub name: Option<String>,
+
+    /// Match the profile name exactly, including its case
+    #[clap(long, requires = "name")]
+    pub exact: bool,
+
+    /// Print the profile details as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct ProfileMergeCommand {
+    /// ID of the profile that will be merged and removed
+    #[clap(long, required_unless_present = "from_name")]
+    pub from: Option<Ulid>,
+
+    /// Name of the profile that will be merged and removed, as an alternative to its ID
+    #[clap(long, conflicts_with = "from")]
+    pub from_name: Option<String>,
+
+    /// ID of the profile that will receive the web apps
+    #[clap(long, required_unless_present = "into_name")]
+    pub into: Option<Ulid>,
+
+    /// Name of the profile that will receive the web apps, as an alternative to its ID
+    #[clap(long, conflicts_with = "into")]
+    pub into_name: Option<String>,
+
+    /// Match the profile names exactly, including their case
+    /// {n}Names are compared case-insensitively by default
+    #[clap(long)]
+    pub exact: bool,
+
+    /// Also merge the profile data directories
+    /// {n}Files in the source profile overwrite files in the target profile,
+    /// and without this flag, data of the source profile is removed
+    #[clap(long)]
+    pub merge_data: bool,
+
+    /// Disable any interactive prompts
+    #[clap(short, long, visible_alias = "yes")]
+    pub quiet: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub enum ProfileExtensionsCommand {
+    /// List extensions installed in a profile
+    List(ProfileExtensionsListCommand),
+
+    /// Add an extension to a profile
+    Add(ProfileExtensionsAddCommand),
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct ProfileExtensionsListCommand {
+    /// Profile ID
+    #[clap(required_unless_present = "name")]
+    pub id: Option<Ulid>,
+
+    /// Profile name, as an alternative to the profile ID
+    /// {n}Names are compared case-insensitively unless --exact is used
+    #[clap(long, conflicts_with = "id")]
+    pub name: Option<String>,
+
+    /// Match the profile name exactly, including its case
+    #[clap(long, requires = "name")]
+    pub exact: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+#[clap(allow_missing_positional = true)]
+pub struct ProfileExtensionsAddCommand {
+    /// Profile ID
+    #[clap(required_unless_present = "name")]
+    pub id: Option<Ulid>,
+
+    /// Path to the extension XPI file
+    #[clap(value_hint = clap::ValueHint::FilePath)]
+    pub xpi: PathBuf,
+
+    /// Profile name, as an alternative to the profile ID
+    /// {n}Names are compared case-insensitively unless --exact is used
+    #[clap(long, conflicts_with = "id")]
+    pub name: Option<String>,
+
+    /// Match the profile name exactly, including its case
+    #[clap(long, requires = "name")]
+    pub exact: bool,
+
+    /// Set the extension ID
+    /// {n}Defaults to the XPI filename if it is a valid extension ID
+    #[clap(long)]
+    pub extension_id: Option<String>,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub enum ProfileTemplateCommand {
+    /// Save the current state of a profile as a template
+    Save(ProfileTemplateSaveCommand),
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct ProfileTemplateSaveCommand {
+    /// Profile ID
+    #[clap(long, required_unless_present = "name")]
+    pub id: Option<Ulid>,
+
+    /// Profile name, as an alternative to the profile ID
+    /// {n}Names are compared case-insensitively unless --exact is used
+    #[clap(long, conflicts_with = "id")]
+    pub name: Option<String>,
+
+    /// Match the profile name exactly, including its case
+    #[clap(long, requires = "name")]
+    pub exact: bool,
+
+    /// Directory where the template will be created
+    /// {n}It must not exist yet or must be empty
+    #[clap(long, value_hint = clap::ValueHint::DirPath)]
+    pub output: PathBuf,
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
@@ -267,11 +1025,17 @@ pub enum RuntimeCommand {
     /// Install the runtime
     Install(RuntimeInstallCommand),
 
+    /// Update the runtime if a newer version is available
+    Update(RuntimeUpdateCommand),
+
     /// Uninstall the runtime
     Uninstall(RuntimeUninstallCommand),
 
     /// Patch the runtime
     Patch(RuntimePatchCommand),
+
+    /// Print the path to the runtime executable
+    Path(RuntimePathCommand),
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
@@ -280,6 +1044,37 @@ pub struct RuntimeInstallCommand {
     #[cfg(target_os = "linux")]
     #[clap(long)]
     pub link: bool,
+
+    /// Install a specific runtime version instead of the latest release
+    /// {n}Versions are named as on the Mozilla archive, for example `140.0.4` or `140.3.0esr`
+    #[cfg_attr(target_os = "linux", clap(long, conflicts_with = "link"))]
+    #[cfg_attr(not(target_os = "linux"), clap(long))]
+    pub runtime_version: Option<String>,
+
+    /// Release channel to install from
+    /// {n}Defaults to the `channel` config option or the release channel
+    #[clap(long, value_parser = ["release", "beta", "esr"], conflicts_with = "runtime_version")]
+    pub channel: Option<String>,
+
+    /// Show a desktop notification with the result when finished
+    #[clap(long)]
+    pub notify: bool,
+
+    /// Internal: ID of the connector operation that can be cancelled
+    #[clap(skip)]
+    pub operation: Option<String>,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct RuntimeUpdateCommand {
+    /// Release channel to update from
+    /// {n}Defaults to the `channel` config option or the release channel
+    #[clap(long, value_parser = ["release", "beta", "esr"])]
+    pub channel: Option<String>,
+
+    /// Reinstall the runtime even if it is already up to date
+    #[clap(long)]
+    pub force: bool,
 }
 
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
@@ -288,9 +1083,93 @@ pub struct RuntimeUninstallCommand {}
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 pub struct RuntimePatchCommand {}
 
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct RuntimePathCommand {
+    /// Print the path as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub enum StorageCommand {
+    /// Verify references between profiles and web apps
+    Verify(StorageVerifyCommand),
+
+    /// Export the whole installation into a single bundle
+    Export(StorageExportCommand),
+
+    /// Import the whole installation from a bundle
+    Import(StorageImportCommand),
+
+    /// Pretty-print the raw storage file
+    Show(StorageShowCommand),
+
+    /// Print the path to the storage file
+    Path(StoragePathCommand),
+
+    /// Print the path to the config file with program settings
+    ConfigPath(StorageConfigPathCommand),
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct StorageVerifyCommand {
+    /// Remove dangling references and restore missing ones
+    #[clap(long)]
+    pub fix: bool,
+
+    /// Also remove web apps whose profile does not exist
+    #[clap(long, requires = "fix")]
+    pub remove_orphaned_sites: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct StorageExportCommand {
+    /// Path where the bundle will be created
+    #[clap(value_hint = clap::ValueHint::FilePath)]
+    pub path: PathBuf,
+
+    /// Only include web apps whose name or document URL contains this text
+    /// {n}Text is matched case-insensitively
+    #[clap(long = "match")]
+    pub pattern: Option<String>,
+
+    /// Only include web apps installed in this profile
+    #[clap(long)]
+    pub profile: Option<Ulid>,
+
+    /// Disable any interactive prompts
+    #[clap(short, long, visible_alias = "yes")]
+    pub quiet: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct StorageImportCommand {
+    /// Path to the bundle
+    #[clap(value_hint = clap::ValueHint::FilePath)]
+    pub path: PathBuf,
+
+    /// Overwrite an existing installation with web apps
+    #[clap(long)]
+    pub force: bool,
+
+    /// Configuration of the HTTP client
+    #[clap(flatten)]
+    pub client: HTTPClientConfig,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct StorageShowCommand {}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct StoragePathCommand {}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct StorageConfigPathCommand {}
+
 #[derive(Parser, Debug, Eq, PartialEq, Clone)]
 pub struct HTTPClientConfig {
     /// Use a custom user-agent header
+    /// {n}Defaults to the `FIREFOXPWA_USER_AGENT` variable or a Firefox-like user-agent
     #[clap(long)]
     pub user_agent: Option<String>,
 
@@ -299,6 +1178,7 @@ pub struct HTTPClientConfig {
     pub tls_root_certificates_der: Option<Vec<PathBuf>>,
 
     /// Import additional root certificates from a PEM file
+    /// {n}Certificates from the `FIREFOXPWA_CAFILE` variable are always imported
     #[clap(long, value_hint = clap::ValueHint::FilePath)]
     pub tls_root_certificates_pem: Option<Vec<PathBuf>>,
 
@@ -310,3 +1190,82 @@ pub struct HTTPClientConfig {
     #[clap(long)]
     pub tls_danger_accept_invalid_hostnames: bool,
 }
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone, Default)]
+pub struct HTTPCredentialsConfig {
+    /// Authenticate the manifest request with HTTP Basic credentials in the `USER:PASSWORD` format
+    /// {n}Credentials are only sent to the manifest URL and are never stored
+    #[clap(long)]
+    pub auth: Option<String>,
+
+    /// Send a custom header in the `NAME: VALUE` format with the manifest request
+    /// {n}Can be specified multiple times, headers are only sent to the manifest URL
+    /// and are never stored
+    #[clap(long = "header")]
+    pub headers: Vec<String>,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub enum InstallationCommand {
+    /// Remove all web apps, profiles, the runtime and other data
+    Uninstall(InstallationUninstallCommand),
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct InstallationUninstallCommand {
+    /// Disable any interactive prompts
+    #[clap(short, long, visible_alias = "yes")]
+    pub quiet: bool,
+
+    /// Remove all data even if system integration of some web apps could not be removed
+    #[clap(long)]
+    pub force: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub enum ConfigCommand {
+    /// Set prefs that are applied to all newly-created profiles
+    SetDefaultPrefs(ConfigSetDefaultPrefsCommand),
+
+    /// Print the effective configuration and the source of each value
+    Show(ConfigShowCommand),
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct ConfigSetDefaultPrefsCommand {
+    /// Path to the `user.js` file with default prefs
+    /// {n}Profile templates are applied on top of the default prefs
+    #[clap(required_unless_present = "clear", value_hint = clap::ValueHint::FilePath)]
+    pub file: Option<PathBuf>,
+
+    /// Remove the default prefs
+    #[clap(long, conflicts_with = "file")]
+    pub clear: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct ConfigShowCommand {
+    /// Print the configuration as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub enum IntegrationsCommand {
+    /// Generate web app icons into a directory without installing them
+    DumpIcons(IntegrationsDumpIconsCommand),
+}
+
+#[derive(Parser, Debug, Eq, PartialEq, Clone)]
+pub struct IntegrationsDumpIconsCommand {
+    /// Web app ID
+    pub id: Ulid,
+
+    /// Directory where the generated icons will be stored
+    #[clap(value_hint = clap::ValueHint::DirPath)]
+    pub directory: PathBuf,
+
+    /// Configuration of the HTTP client
+    #[clap(flatten)]
+    pub client: HTTPClientConfig,
+}
@@ -0,0 +1,171 @@
+use std::fs::{copy, create_dir_all, remove_file};
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use cfg_if::cfg_if;
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::console::Run;
+use crate::console::app::{ConfigSetDefaultPrefsCommand, ConfigShowCommand};
+use crate::console::defaults::{Applied, Defaults};
+use crate::directories::ProjectDirs;
+use crate::utils::temp_dir;
+
+/// Where the effective value of a config option comes from.
+#[derive(Serialize, Debug, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Source {
+    Default,
+    Env,
+    File,
+    Flag,
+}
+
+impl Source {
+    /// Determines the source of an option that is read from an environment variable.
+    fn of(variable: &str, applied: &Applied) -> Self {
+        if applied.flags.iter().any(|applied| *applied == variable) {
+            Self::Flag
+        } else if applied.variables.iter().any(|applied| *applied == variable) {
+            Self::File
+        } else if std::env::var_os(variable).is_some_and(|value| !value.is_empty()) {
+            Self::Env
+        } else {
+            Self::Default
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Env => "env",
+            Self::File => "file",
+            Self::Flag => "flag",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Eq, PartialEq, Clone)]
+struct Entry {
+    value: Option<String>,
+    source: Source,
+}
+
+impl Entry {
+    fn new(value: Option<String>, source: Source) -> Self {
+        Self { value, source }
+    }
+
+    fn path(value: Option<&Path>, source: Source) -> Self {
+        Self::new(value.map(|path| path.display().to_string()), source)
+    }
+
+    fn variable(variable: &str, applied: &Applied) -> Self {
+        let value = std::env::var(variable).ok().filter(|value| !value.is_empty());
+        Self::new(value, Source::of(variable, applied))
+    }
+}
+
+impl Run for ConfigSetDefaultPrefsCommand {
+    fn run(&self) -> Result<()> {
+        let Some(target) = Defaults::prefs_path() else {
+            bail!("Failed to determine the config directory");
+        };
+
+        let Some(file) = &self.file else {
+            if target.exists() {
+                remove_file(&target).context("Failed to remove default prefs")?;
+                info!("Default prefs removed!");
+            } else {
+                warn!("Default prefs are not set");
+            }
+
+            return Ok(());
+        };
+
+        if !file.is_file() {
+            bail!("Prefs file does not exist: {}", file.display());
+        }
+
+        if let Some(parent) = target.parent() {
+            create_dir_all(parent).context("Failed to create the config directory")?;
+        }
+        copy(file, &target).context("Failed to copy default prefs")?;
+
+        info!("Default prefs set: {}", target.display());
+        Ok(())
+    }
+}
+
+impl Run for ConfigShowCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let applied = Defaults::applied();
+
+        // Directories can only be changed at run-time if they are not static
+        let directory = |path: &Path, variable: &str| {
+            let source = match ProjectDirs::static_only() {
+                true => Source::Default,
+                false => Source::of(variable, &applied),
+            };
+            Entry::path(Some(path), source)
+        };
+
+        let config_file = match applied.explicit {
+            true => Source::Flag,
+            false => Source::Default,
+        };
+
+        let prefs = Defaults::prefs_path().filter(|path| path.exists());
+        let prefs_source = match prefs {
+            Some(_) => Source::File,
+            None => Source::Default,
+        };
+
+        let channel = std::env::var("FIREFOXPWA_RUNTIME_CHANNEL")
+            .ok()
+            .filter(|channel| !channel.is_empty())
+            .unwrap_or_else(|| "release".into());
+        let channel = Entry::new(Some(channel), Source::of("FIREFOXPWA_RUNTIME_CHANNEL", &applied));
+
+        #[allow(unused_mut)]
+        let mut entries = vec![
+            ("config-file", Entry::path(applied.file.as_deref(), config_file)),
+            ("executables", directory(&dirs.executables, "FFPWA_EXECUTABLES")),
+            ("sysdata", directory(&dirs.sysdata, "FFPWA_SYSDATA")),
+            ("data-dir", directory(&dirs.userdata, "FFPWA_USERDATA")),
+            ("proxy", Entry::variable("FIREFOXPWA_PROXY", &applied)),
+            ("cafile", Entry::variable("FIREFOXPWA_CAFILE", &applied)),
+            ("channel", channel),
+            ("temp-dir", Entry::path(Some(&temp_dir()), Source::of("FIREFOXPWA_TMPDIR", &applied))),
+            ("default-prefs", Entry::path(prefs.as_deref(), prefs_source)),
+        ];
+
+        cfg_if! {
+            if #[cfg(platform_windows)] {
+                use crate::components::_7zip::_7Zip;
+
+                let _7zip = _7Zip::new()?;
+                entries.push(("7zip", Entry::path(_7zip.executable.as_deref(), Source::Default)));
+            }
+        }
+
+        if self.json {
+            let output: serde_json::Map<_, _> = entries
+                .into_iter()
+                .map(|(name, entry)| Ok((name.to_owned(), serde_json::to_value(entry)?)))
+                .collect::<Result<_>>()?;
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        let width = entries.iter().map(|(name, _)| name.len()).max().unwrap_or_default();
+        for (name, entry) in entries {
+            let value = entry.value.as_deref().unwrap_or("-");
+            println!("{name:width$}  {value} ({})", entry.source.as_str());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use directories::BaseDirs;
+use serde::Deserialize;
+
+const CONFIG_READ_ERROR: &str = "Failed to read config file";
+const CONFIG_PARSE_ERROR: &str = "Failed to parse config file";
+
+static APPLIED: OnceLock<Applied> = OnceLock::new();
+
+/// Record of the config file that was applied to the current process.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct Applied {
+    /// Path to the loaded config file, if any.
+    pub file: Option<PathBuf>,
+
+    /// Whether the config file was specified with the `--config` argument.
+    pub explicit: bool,
+
+    /// Environment variables that were set from the config file.
+    pub variables: Vec<&'static str>,
+
+    /// Environment variables that were set from command-line arguments.
+    pub flags: Vec<&'static str>,
+}
+
+/// Default options loaded from the TOML config file.
+///
+/// Options are applied as environment variables that the program already
+/// understands, so variables that are explicitly set in the environment
+/// and arguments passed on the command line always take precedence.
+#[derive(Deserialize, Debug, Default, Eq, PartialEq, Clone)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Defaults {
+    /// User data directory, same as the `FFPWA_USERDATA` variable.
+    pub data_dir: Option<PathBuf>,
+
+    /// Proxy for all HTTP requests, same as the `FIREFOXPWA_PROXY` variable.
+    pub proxy: Option<String>,
+
+    /// Additional root certificates, same as the `FIREFOXPWA_CAFILE` variable.
+    pub cafile: Option<PathBuf>,
+
+    /// Runtime release channel, same as the `FIREFOXPWA_RUNTIME_CHANNEL` variable.
+    pub channel: Option<String>,
+
+    /// Temporary directory, same as the `FIREFOXPWA_TMPDIR` variable.
+    pub temp_dir: Option<PathBuf>,
+
+    #[serde(skip)]
+    file: Option<PathBuf>,
+
+    #[serde(skip)]
+    explicit: bool,
+
+    #[serde(skip)]
+    overrides: Vec<&'static str>,
+}
+
+impl Defaults {
+    /// Returns the default path to the config file.
+    pub fn path() -> Option<PathBuf> {
+        Some(Self::directory()?.join("config.toml"))
+    }
+
+    /// Returns the path to the default prefs applied to new profiles.
+    pub fn prefs_path() -> Option<PathBuf> {
+        Some(Self::directory()?.join("user.js"))
+    }
+
+    fn directory() -> Option<PathBuf> {
+        let base = BaseDirs::new()?;
+        Some(base.config_dir().join("firefoxpwa"))
+    }
+
+    /// Loads the config file from the path or its default location.
+    ///
+    /// A missing file is only an error if its path was explicitly specified.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let explicit = path.is_some();
+        let path = match path {
+            Some(path) => path.to_owned(),
+            None => match Self::path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+
+        let data = read_to_string(&path).context(CONFIG_READ_ERROR)?;
+        let defaults: Self = toml::from_str(&data).context(CONFIG_PARSE_ERROR)?;
+        Ok(Self { file: Some(path), explicit, ..defaults })
+    }
+
+    /// Overrides root certificates with the `--cafile` argument.
+    ///
+    /// Unlike options from the config file, the argument also
+    /// takes precedence over the environment variable.
+    pub fn override_cafile(&mut self, cafile: Option<&Path>) {
+        if let Some(cafile) = cafile {
+            self.cafile = Some(cafile.to_owned());
+            self.overrides.push("FIREFOXPWA_CAFILE");
+        }
+    }
+
+    /// Returns the record of the config file applied to the current process.
+    ///
+    /// Used to report the source of each option, so it is empty if
+    /// [`Defaults::apply`] has not been called.
+    pub fn applied() -> Applied {
+        APPLIED.get().cloned().unwrap_or_default()
+    }
+
+    /// Applies the options as environment variables, unless they are already set.
+    ///
+    /// # Safety
+    ///
+    /// Must be called before any other threads are started,
+    /// because it modifies the process environment.
+    pub unsafe fn apply(&self) {
+        let options = [
+            ("FFPWA_USERDATA", self.data_dir.as_ref().map(|path| path.as_os_str())),
+            ("FIREFOXPWA_PROXY", self.proxy.as_ref().map(|proxy| proxy.as_ref())),
+            ("FIREFOXPWA_CAFILE", self.cafile.as_ref().map(|path| path.as_os_str())),
+            ("FIREFOXPWA_RUNTIME_CHANNEL", self.channel.as_ref().map(|channel| channel.as_ref())),
+            ("FIREFOXPWA_TMPDIR", self.temp_dir.as_ref().map(|path| path.as_os_str())),
+        ];
+
+        let mut variables = vec![];
+        let mut flags = vec![];
+        for (variable, value) in options {
+            let overridden = self.overrides.contains(&variable);
+            if let Some(value) = value
+                && (overridden || std::env::var_os(variable).is_none())
+            {
+                // SAFETY: The caller guarantees no other threads are running
+                unsafe { std::env::set_var(variable, value) };
+                if overridden { flags.push(variable) } else { variables.push(variable) }
+            }
+        }
+
+        let file = self.file.clone();
+        let _ = APPLIED.set(Applied { file, explicit: self.explicit, variables, flags });
+    }
+}
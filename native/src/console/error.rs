@@ -0,0 +1,46 @@
+use crate::error::CommandError;
+
+/// Returns the exit code of the console program for the error.
+///
+/// The codes are part of the public interface and must not change:
+///
+/// - `1` - Any other error.
+/// - `2` - Invalid command-line arguments (returned by the argument parser).
+/// - `3` - Profile does not exist.
+/// - `4` - Web app does not exist.
+/// - `5` - Runtime is not installed.
+/// - `6` - 7-Zip is not installed.
+/// - `7` - Network error.
+/// - `8` - Operation has been cancelled.
+///
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    for cause in error.chain() {
+        if let Some(error) = cause.downcast_ref::<CommandError>() {
+            return match error {
+                CommandError::ProfileNotFound => 3,
+                CommandError::SiteNotFound => 4,
+                CommandError::RuntimeNotInstalled => 5,
+                CommandError::SevenZipNotInstalled => 6,
+                CommandError::Cancelled => 8,
+            };
+        }
+
+        #[cfg(platform_windows)]
+        {
+            use crate::components::_7zip::AdminError;
+
+            // Declining the administrator permission request cancels the installation
+            if let Some(AdminError::AdminPermissionDenied | AdminError::Timeout) =
+                cause.downcast_ref::<AdminError>()
+            {
+                return 8;
+            }
+        }
+
+        if cause.is::<reqwest::Error>() {
+            return 7;
+        }
+    }
+
+    1
+}
@@ -0,0 +1,103 @@
+use std::fs::{remove_dir, remove_dir_all, remove_file};
+
+use anyhow::{Context, Result, bail};
+use log::{info, warn};
+
+#[cfg(not(feature = "immutable-runtime"))]
+use crate::components::runtime::Runtime;
+use crate::console::app::InstallationUninstallCommand;
+use crate::console::defaults::Defaults;
+use crate::console::{Run, confirm};
+use crate::directories::ProjectDirs;
+use crate::error::CommandError;
+use crate::integrations;
+use crate::integrations::IntegrationUninstallArgs;
+use crate::storage::Storage;
+
+impl Run for InstallationUninstallCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        if !self.quiet {
+            warn!("This will remove all web apps, profiles and the runtime, including all data");
+            warn!("The native program itself and the browser extension will not be removed");
+            warn!("You will not be able to recover this action");
+
+            if !confirm()? {
+                bail!(CommandError::Cancelled);
+            }
+        }
+
+        let mut failed = 0;
+
+        for site in storage.sites.values() {
+            info!("Uninstalling system integration for web app {site}");
+            if let Err(error) =
+                integrations::uninstall(&IntegrationUninstallArgs { site, dirs: &dirs })
+            {
+                warn!("Failed to uninstall system integration: {error:?}");
+                failed += 1;
+            }
+        }
+
+        if failed > 0 && !self.force {
+            // Storage is needed to retry the uninstallation, so nothing is removed yet
+            warn!("Fix the errors and try again, or use --force to remove all data anyway");
+            bail!("System integration of {failed} web apps could not be removed");
+        }
+
+        let profiles = dirs.userdata.join("profiles");
+        if profiles.exists() {
+            info!("Removing profiles");
+            remove_dir_all(profiles).context("Failed to remove profiles")?;
+        }
+
+        #[cfg(not(feature = "immutable-runtime"))]
+        {
+            let runtime = Runtime::new(&dirs)?;
+            if runtime.version.is_some() {
+                info!("Removing the runtime");
+                runtime.uninstall().context("Failed to uninstall runtime")?;
+            }
+        }
+
+        #[cfg(feature = "immutable-runtime")]
+        info!("Runtime is managed by the system and will not be removed");
+
+        let cache = dirs.userdata.join("cache");
+        if cache.exists() {
+            info!("Removing the cache");
+            remove_dir_all(cache).context("Failed to remove the cache")?;
+        }
+
+        let storage = Storage::path(&dirs);
+        if storage.exists() {
+            info!("Removing the storage");
+            remove_file(storage).context("Failed to remove the storage")?;
+        }
+
+        for name in ["firefoxpwa.log", "firefoxpwa-stdout.log", "firefoxpwa-stderr.log"] {
+            let _ = remove_file(dirs.userdata.join(name));
+        }
+
+        let config = [Defaults::path(), Defaults::prefs_path()];
+        for path in config.into_iter().flatten().filter(|path| path.exists()) {
+            info!("Removing {}", path.display());
+            remove_file(&path).context("Failed to remove the config")?;
+
+            // Only removed once empty, so unrelated files are kept
+            if let Some(parent) = path.parent() {
+                let _ = remove_dir(parent);
+            }
+        }
+
+        if failed > 0 {
+            warn!("System integration of {failed} web apps could not be removed");
+            warn!("Remove their remaining shortcuts manually");
+        }
+
+        info!("All data removed!");
+        Ok(())
+    }
+}
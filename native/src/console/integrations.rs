@@ -0,0 +1,31 @@
+use std::fs::create_dir_all;
+
+use anyhow::{Context, Result};
+use log::info;
+
+use crate::console::Run;
+use crate::console::app::IntegrationsDumpIconsCommand;
+use crate::directories::ProjectDirs;
+use crate::error::CommandError;
+use crate::integrations;
+use crate::storage::Storage;
+use crate::utils::{ClientArgs, construct_certificates_and_client};
+
+impl Run for IntegrationsDumpIconsCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let site = storage.sites.get(&self.id).context(CommandError::SiteNotFound)?;
+
+        let client = construct_certificates_and_client(&ClientArgs::from(&self.client))?;
+
+        info!("Generating web app icons");
+        create_dir_all(&self.directory).context("Failed to create target directory")?;
+        integrations::dump_icons(site, &self.directory, &client)
+            .context("Failed to generate web app icons")?;
+
+        info!("Icons stored to {}", self.directory.display());
+        Ok(())
+    }
+}
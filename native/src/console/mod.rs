@@ -1,12 +1,37 @@
-use anyhow::Result;
+use std::io;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
 
 pub use crate::console::app::App;
-use crate::console::app::{ProfileCommand, RuntimeCommand, SiteCommand};
+use crate::console::app::{
+    AppCommand,
+    ConfigCommand,
+    HTTPClientConfig,
+    InstallationCommand,
+    IntegrationsCommand,
+    ProfileCommand,
+    ProfileExtensionsCommand,
+    ProfileTemplateCommand,
+    RuntimeCommand,
+    SiteCommand,
+    StorageCommand,
+};
+use crate::error::CommandError;
+use crate::utils::ClientArgs;
 
 pub mod app;
+pub mod config;
+pub mod defaults;
+pub mod error;
+pub mod installation;
+pub mod integrations;
 pub mod profile;
 pub mod runtime;
 pub mod site;
+pub mod storage;
 
 /// Parses and stores `Option<Option<X>>` parameters.
 ///
@@ -24,6 +49,20 @@ macro_rules! store_value {
     };
 }
 
+/// Validates and stores `Option<Option<X>>` parameters.
+///
+/// Works the same as [`store_value`], but a new value is first passed
+/// to the validator as `&X`, and its error is returned before the value
+/// is stored. Clearing the value with `Some(None)` is never validated.
+macro_rules! store_validated_value {
+    ($target:expr, $source:expr, $validator:expr) => {
+        if let Some(Some(value)) = &$source {
+            ($validator)(value)?;
+        }
+        $crate::console::store_value!($target, $source);
+    };
+}
+
 /// Parses and stores `Option<Vec<X>>` parameters.
 ///
 /// This needs some weird parsing hacks to be compatible both with the
@@ -47,9 +86,165 @@ macro_rules! store_value_vec {
     };
 }
 
+/// Appends a value to a `Vec<X>` field.
+///
+/// The value is only appended if the field does not contain it yet.
+macro_rules! append_value {
+    ($target:expr, $value:expr) => {
+        let value = $value;
+        if !$target.contains(&value) {
+            $target.push(value);
+        }
+    };
+}
+
+/// Removes a value from a `Vec<X>` field.
+///
+/// All occurrences of the value are removed.
+macro_rules! remove_value {
+    ($target:expr, $value:expr) => {
+        let value = $value;
+        $target.retain(|item| *item != value);
+    };
+}
+
+pub(in crate::console) use append_value;
+pub(in crate::console) use remove_value;
+pub(in crate::console) use store_validated_value;
 pub(in crate::console) use store_value;
 pub(in crate::console) use store_value_vec;
 
+/// Asks the user to confirm a destructive action.
+///
+/// When stdin is not interactive (for example, when piped or running from
+/// a scheduled task), the prompt cannot be answered, so an error is returned
+/// instead of silently aborting or waiting for input.
+pub(in crate::console) fn confirm() -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        bail!("Confirmation is required, but stdin is not interactive, use --yes to skip it");
+    }
+
+    print!("Do you want to continue (y/n)? ");
+    io::stdout().flush()?;
+
+    let mut confirm = String::new();
+    if io::stdin().read_line(&mut confirm)? == 0 {
+        // Stdin was closed before anything was entered
+        return Err(CommandError::Cancelled.into());
+    }
+    confirm = confirm.trim().into();
+
+    Ok(confirm == "Y" || confirm == "y")
+}
+
+/// Asks the user to enter a value and returns the trimmed answer.
+///
+/// Like [`confirm`], this returns an error when stdin is not interactive.
+pub(in crate::console) fn prompt(question: &str) -> Result<String> {
+    if !io::stdin().is_terminal() {
+        bail!("Input is required, but stdin is not interactive");
+    }
+
+    print!("{question}: ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        // Stdin was closed before anything was entered
+        return Err(CommandError::Cancelled.into());
+    }
+    Ok(answer.trim().into())
+}
+
+/// Formats the time elapsed since the given time, such as `3 days ago`.
+pub(in crate::console) fn format_elapsed(time: SystemTime) -> String {
+    let seconds = time.elapsed().unwrap_or_default().as_secs();
+
+    let (value, unit) = match seconds {
+        0..60 => return "just now".into(),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+
+    if value == 1 { format!("1 {unit} ago") } else { format!("{value} {unit}s ago") }
+}
+
+/// Formats the number of bytes as a human-readable size, such as `1.5 MiB`.
+pub(in crate::console) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+
+    format!("{size:.1} {unit}")
+}
+
+/// Parses a date used to filter profiles and web apps by their creation time.
+///
+/// Accepts RFC 3339 timestamps, such as `2024-05-01T12:00:00+02:00`,
+/// and plain dates, such as `2024-05-01`, which start at local midnight.
+pub(in crate::console) fn parse_date(date: &str) -> Result<SystemTime> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Ok(date.into());
+    }
+
+    let Ok(day) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+        bail!("Invalid date: {date}, expected an RFC 3339 timestamp or YYYY-MM-DD");
+    };
+
+    match day.and_time(NaiveTime::MIN).and_local_timezone(Local).earliest() {
+        Some(date) => Ok(date.into()),
+        None => bail!("Invalid local date: {date}"),
+    }
+}
+
+/// Parses a duration such as `7d`, `12h`, `30m` or `45s`.
+///
+/// Supported units are weeks (`w`), days (`d`), hours (`h`), minutes (`m`) and seconds (`s`).
+pub(in crate::console) fn parse_duration(duration: &str) -> Result<Duration> {
+    let split = duration.find(|char: char| !char.is_ascii_digit()).unwrap_or(duration.len());
+    let (value, unit) = duration.split_at(split);
+
+    let Ok(value) = value.parse::<u64>() else {
+        bail!("Invalid duration: {duration}, expected a number with a unit, such as 7d");
+    };
+
+    let multiplier = match unit {
+        "w" => 7 * 24 * 60 * 60,
+        "d" => 24 * 60 * 60,
+        "h" => 60 * 60,
+        "m" => 60,
+        "s" => 1,
+        _ => bail!("Invalid duration unit: {unit}, expected one of w, d, h, m or s"),
+    };
+
+    Ok(Duration::from_secs(value.saturating_mul(multiplier)))
+}
+
+impl<'a> From<&'a HTTPClientConfig> for ClientArgs<'a> {
+    fn from(config: &'a HTTPClientConfig) -> Self {
+        Self {
+            user_agent: config.user_agent.as_deref(),
+            certificates_der: &config.tls_root_certificates_der,
+            certificates_pem: &config.tls_root_certificates_pem,
+            danger_accept_invalid_certs: config.tls_danger_accept_invalid_certs,
+            danger_accept_invalid_hostnames: config.tls_danger_accept_invalid_hostnames,
+        }
+    }
+}
+
 pub trait Run {
     fn run(&self) -> Result<()>;
 }
@@ -57,10 +252,14 @@ pub trait Run {
 impl Run for App {
     #[inline]
     fn run(&self) -> Result<()> {
-        match self {
-            App::Site(cmd) => cmd.run(),
-            App::Profile(cmd) => cmd.run(),
-            App::Runtime(cmd) => cmd.run(),
+        match &self.command {
+            AppCommand::Site(cmd) => cmd.run(),
+            AppCommand::Profile(cmd) => cmd.run(),
+            AppCommand::Runtime(cmd) => cmd.run(),
+            AppCommand::Storage(cmd) => cmd.run(),
+            AppCommand::Config(cmd) => cmd.run(),
+            AppCommand::Installation(cmd) => cmd.run(),
+            AppCommand::Integrations(cmd) => cmd.run(),
         }
     }
 }
@@ -73,6 +272,14 @@ impl Run for SiteCommand {
             SiteCommand::Install(cmd) => cmd.run(),
             SiteCommand::Uninstall(cmd) => cmd.run(),
             SiteCommand::Update(cmd) => cmd.run(),
+            SiteCommand::Verify(cmd) => cmd.run(),
+            SiteCommand::Repair(cmd) => cmd.run(),
+            SiteCommand::Pin(cmd) => cmd.run(),
+            SiteCommand::Unpin(cmd) => cmd.run(),
+            SiteCommand::List(cmd) => cmd.run(),
+            SiteCommand::Show(cmd) => cmd.run(),
+            SiteCommand::Group(cmd) => cmd.run(),
+            SiteCommand::Ungroup(cmd) => cmd.run(),
         }
     }
 }
@@ -85,6 +292,31 @@ impl Run for ProfileCommand {
             ProfileCommand::Create(cmd) => cmd.run(),
             ProfileCommand::Remove(cmd) => cmd.run(),
             ProfileCommand::Update(cmd) => cmd.run(),
+            ProfileCommand::Rename(cmd) => cmd.run(),
+            ProfileCommand::Path(cmd) => cmd.run(),
+            ProfileCommand::Show(cmd) => cmd.run(),
+            ProfileCommand::Merge(cmd) => cmd.run(),
+            ProfileCommand::Extensions(cmd) => cmd.run(),
+            ProfileCommand::Template(cmd) => cmd.run(),
+        }
+    }
+}
+
+impl Run for ProfileExtensionsCommand {
+    #[inline]
+    fn run(&self) -> Result<()> {
+        match self {
+            ProfileExtensionsCommand::List(cmd) => cmd.run(),
+            ProfileExtensionsCommand::Add(cmd) => cmd.run(),
+        }
+    }
+}
+
+impl Run for ProfileTemplateCommand {
+    #[inline]
+    fn run(&self) -> Result<()> {
+        match self {
+            ProfileTemplateCommand::Save(cmd) => cmd.run(),
         }
     }
 }
@@ -94,8 +326,52 @@ impl Run for RuntimeCommand {
     fn run(&self) -> Result<()> {
         match self {
             RuntimeCommand::Install(cmd) => cmd.run(),
+            RuntimeCommand::Update(cmd) => cmd.run(),
             RuntimeCommand::Uninstall(cmd) => cmd.run(),
             RuntimeCommand::Patch(cmd) => cmd.run(),
+            RuntimeCommand::Path(cmd) => cmd.run(),
+        }
+    }
+}
+
+impl Run for StorageCommand {
+    #[inline]
+    fn run(&self) -> Result<()> {
+        match self {
+            StorageCommand::Verify(cmd) => cmd.run(),
+            StorageCommand::Export(cmd) => cmd.run(),
+            StorageCommand::Import(cmd) => cmd.run(),
+            StorageCommand::Show(cmd) => cmd.run(),
+            StorageCommand::Path(cmd) => cmd.run(),
+            StorageCommand::ConfigPath(cmd) => cmd.run(),
+        }
+    }
+}
+
+impl Run for ConfigCommand {
+    #[inline]
+    fn run(&self) -> Result<()> {
+        match self {
+            ConfigCommand::SetDefaultPrefs(cmd) => cmd.run(),
+            ConfigCommand::Show(cmd) => cmd.run(),
+        }
+    }
+}
+
+impl Run for InstallationCommand {
+    #[inline]
+    fn run(&self) -> Result<()> {
+        match self {
+            InstallationCommand::Uninstall(cmd) => cmd.run(),
+        }
+    }
+}
+
+impl Run for IntegrationsCommand {
+    #[inline]
+    fn run(&self) -> Result<()> {
+        match self {
+            IntegrationsCommand::DumpIcons(cmd) => cmd.run(),
         }
     }
 }
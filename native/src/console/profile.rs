@@ -1,85 +1,404 @@
-use std::fs::{create_dir_all, remove_dir_all};
-use std::io;
-use std::io::Write;
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::fs::{
+    copy as copy_file,
+    create_dir_all,
+    read_dir,
+    read_to_string,
+    remove_dir,
+    remove_dir_all,
+    remove_file,
+    write,
+};
+use std::io::Result as IoResult;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
-use fs_extra::dir::{CopyOptions, copy};
+use anyhow::{Context, Result, bail};
+use fs_extra::dir::{CopyOptions, copy, get_size};
+use glob::Pattern;
 use log::{info, warn};
 use ulid::Ulid;
 
-use crate::components::profile::Profile;
+use crate::components::profile::{Profile, ProfileBuilder, validate_extension_id};
+use crate::components::site::Site;
 use crate::console::app::{
     ProfileCreateCommand,
+    ProfileExtensionsAddCommand,
+    ProfileExtensionsListCommand,
     ProfileListCommand,
+    ProfileMergeCommand,
+    ProfilePathCommand,
     ProfileRemoveCommand,
+    ProfileRenameCommand,
+    ProfileShowCommand,
+    ProfileTemplateSaveCommand,
     ProfileUpdateCommand,
 };
-use crate::console::{Run, store_value};
+use crate::console::defaults::Defaults;
+use crate::console::{
+    Run,
+    append_value,
+    confirm,
+    format_elapsed,
+    format_size,
+    parse_date,
+    remove_value,
+    store_value,
+};
 use crate::directories::ProjectDirs;
+use crate::error::CommandError;
 use crate::integrations;
 use crate::integrations::IntegrationUninstallArgs;
 use crate::storage::Storage;
 use crate::utils::sanitize_string;
 
+/// Copies configuration files from one profile to another.
+///
+/// Only prefs, extensions and the `chrome` directory are copied, so data
+/// like cookies, history and caches of the source profile stay private.
+fn copy_profile_config(source: &Ulid, target: &Ulid, dirs: &ProjectDirs) -> Result<()> {
+    const COPY_ERROR: &str = "Failed to copy the profile configuration";
+
+    let source = dirs.userdata.join("profiles").join(source.to_string());
+    let target = dirs.userdata.join("profiles").join(target.to_string());
+
+    if !source.is_dir() {
+        warn!("Source profile does not have a directory yet, nothing to copy");
+        return Ok(());
+    }
+
+    info!("Copying the profile configuration");
+    create_dir_all(&target).context(COPY_ERROR)?;
+
+    let prefs = source.join("user.js");
+    if prefs.is_file() {
+        copy_file(prefs, target.join("user.js")).context(COPY_ERROR)?;
+    }
+
+    let mut options = CopyOptions::new();
+    options.overwrite = true;
+
+    for directory in ["extensions", "chrome"] {
+        let directory = source.join(directory);
+        if directory.is_dir() {
+            copy(directory, &target, &options).context(COPY_ERROR)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Merges default prefs into the profile `user.js` file.
+///
+/// Default prefs are written into a separate block at the start of the file, which
+/// is replaced when they are applied again. All other prefs are kept after the block,
+/// so they override the default prefs.
+fn apply_default_prefs(prefs: &Path, profile: &Path) -> Result<()> {
+    const BLOCK_START: &str = "// FIREFOXPWA DEFAULT PREFS START";
+    const BLOCK_END: &str = "// FIREFOXPWA DEFAULT PREFS END";
+
+    let path = profile.join("user.js");
+    let content = if path.exists() { read_to_string(&path)? } else { String::new() };
+
+    // Remove the previous default prefs block and keep all other prefs
+    let rest = match (content.find(BLOCK_START), content.find(BLOCK_END)) {
+        (Some(start), Some(end)) if start < end => {
+            let end = end + BLOCK_END.len();
+            format!("{}{}", &content[..start], content[end..].trim_start())
+        }
+        _ => content,
+    };
+
+    let prefs = read_to_string(prefs)?;
+    let prefs = prefs.trim_end();
+
+    create_dir_all(profile)?;
+    write(&path, format!("{BLOCK_START}\n{prefs}\n{BLOCK_END}\n{rest}"))?;
+    Ok(())
+}
+
+/// Copies the template directory, skipping entries that match any exclude pattern.
+///
+/// Patterns are matched against `/`-separated paths relative to the template root.
+/// When a directory matches, its whole contents are skipped.
+fn copy_template(
+    source: &Path,
+    target: &Path,
+    relative: &Path,
+    exclude: &[Pattern],
+    overwrite: bool,
+) -> Result<()> {
+    for entry in read_dir(source.join(relative))? {
+        let entry = entry?;
+        let relative = relative.join(entry.file_name());
+
+        if is_excluded(&relative, exclude) {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            create_dir_all(target.join(&relative))?;
+            copy_template(source, target, &relative, exclude, overwrite)?;
+        } else if overwrite || !target.join(&relative).exists() {
+            copy_file(entry.path(), target.join(&relative))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks whether any non-excluded template file already exists in the target directory.
+fn template_conflicts(
+    source: &Path,
+    target: &Path,
+    relative: &Path,
+    exclude: &[Pattern],
+) -> Result<bool> {
+    for entry in read_dir(source.join(relative))? {
+        let entry = entry?;
+        let relative = relative.join(entry.file_name());
+
+        if is_excluded(&relative, exclude) {
+            continue;
+        }
+
+        let conflicts = if entry.file_type()?.is_dir() {
+            template_conflicts(source, target, &relative, exclude)?
+        } else {
+            target.join(&relative).exists()
+        };
+
+        if conflicts {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn is_excluded(relative: &Path, exclude: &[Pattern]) -> bool {
+    let name: Vec<_> = relative.iter().map(|part| part.to_string_lossy()).collect();
+    exclude.iter().any(|pattern| pattern.matches(&name.join("/")))
+}
+
+/// Recursively collects paths of all files and directories in the directory.
+fn list_entries(directory: &Path, entries: &mut BTreeSet<PathBuf>) -> IoResult<()> {
+    for entry in read_dir(directory)? {
+        let entry = entry?;
+        entries.insert(entry.path());
+
+        if entry.file_type()?.is_dir() {
+            list_entries(&entry.path(), entries)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies the profile template to the profile directory and merges default prefs.
+///
+/// The strategy is one of `overwrite`, `merge` or `skip`,
+/// and controls how files that already exist in the profile are handled.
 fn apply_profile_template(
     template: &Option<PathBuf>,
+    exclude: &[String],
+    strategy: &str,
     profile: &Ulid,
     dirs: &ProjectDirs,
 ) -> Result<()> {
-    if let Some(template) = template {
-        let mut options = CopyOptions::new();
-        options.content_only = true;
-        options.overwrite = true;
+    let target = dirs.userdata.join("profiles").join(profile.to_string());
+    let overwrite = strategy == "overwrite";
+
+    let exclude = exclude
+        .iter()
+        .map(|pattern| Pattern::new(pattern))
+        .collect::<Result<Vec<_>, _>>()
+        .context("Invalid template exclude pattern")?;
+
+    let conflicts = match template {
+        Some(template) if strategy == "skip" && target.exists() => {
+            template_conflicts(template, &target, Path::new(""), &exclude)
+                .context("Failed to compare a profile template")?
+        }
+        _ => false,
+    };
 
+    if conflicts {
+        warn!("Skipping a profile template because some of its files already exist");
+        return Ok(());
+    }
+
+    if let Some(template) = template {
         info!("Copying a profile template");
-        let target = dirs.userdata.join("profiles").join(profile.to_string());
         create_dir_all(&target).context("Failed to create a profile directory")?;
-        copy(template, target, &options).context("Failed to copy a profile template")?;
+
+        // Remember existing entries, so a partially copied template can be removed
+        let mut existing = BTreeSet::new();
+        list_entries(&target, &mut existing).context("Failed to read a profile directory")?;
+
+        if let Err(error) = copy_template(template, &target, Path::new(""), &exclude, overwrite) {
+            let mut copied = BTreeSet::new();
+            let _ = list_entries(&target, &mut copied);
+
+            // Entries are removed in reverse order, so files are removed before their directories
+            warn!("Removing a partially copied profile template");
+            for path in copied.difference(&existing).rev() {
+                let result = if path.is_dir() { remove_dir(path) } else { remove_file(path) };
+                if let Err(error) = result {
+                    warn!("Failed to remove {}: {error}", path.display());
+                }
+            }
+
+            return Err(error).context("Failed to copy a profile template");
+        }
+    }
+
+    // Default prefs are merged after the template, so prefs from its `user.js` override them
+    if let Some(prefs) = Defaults::prefs_path().filter(|path| path.exists()) {
+        info!("Applying default prefs");
+        apply_default_prefs(&prefs, &target).context("Failed to apply default prefs")?;
     }
 
     Ok(())
 }
 
+/// Resolves the profile ID from either the ID or the name argument.
+///
+/// If multiple profiles share the name, they are listed and the user
+/// is asked to select the profile by its ID instead.
+pub(in crate::console) fn resolve_profile(
+    storage: &Storage,
+    id: Option<Ulid>,
+    name: Option<&str>,
+    exact: bool,
+) -> Result<Ulid> {
+    let Some(name) = name else {
+        return id.context("Profile ID or name is required");
+    };
+
+    match storage.find_profiles_by_name(name, exact).as_slice() {
+        [] => bail!(CommandError::ProfileNotFound),
+        [(ulid, _)] => Ok(*ulid),
+        profiles => {
+            println!("Multiple profiles are named {}:", sanitize_string(name));
+            for (ulid, profile) in profiles {
+                let name = profile.name.as_deref().unwrap_or_default();
+                println!("- {}: {}", sanitize_string(name), ulid);
+            }
+            println!();
+
+            bail!("Profile name is ambiguous, use the profile ID instead")
+        }
+    }
+}
+
 impl Run for ProfileListCommand {
     fn run(&self) -> Result<()> {
         let dirs = ProjectDirs::new()?;
         let storage = Storage::load(&dirs)?;
 
-        for (_, profile) in storage.profiles {
-            println!(
-                "{:=^60}\nDescription: {}\nID: {}",
-                format!(
-                    " {} ",
-                    sanitize_string(&profile.name.unwrap_or_else(|| "* Unnamed *".into()))
-                ),
-                sanitize_string(&profile.description.unwrap_or_else(|| "* Nothing *".into())),
-                profile.ulid
-            );
+        let id = match &self.name {
+            Some(name) => Some(resolve_profile(&storage, None, Some(name), self.exact)?),
+            None => self.id,
+        };
 
-            if !profile.sites.is_empty() {
-                println!("\nApps:");
-            }
+        let mut profiles: Vec<&Profile> = match id {
+            Some(id) => vec![storage.profiles.get(&id).context(CommandError::ProfileNotFound)?],
+            None => storage.profiles.values().collect(),
+        };
 
-            for site in profile.sites {
-                let site = storage.sites.get(&site).context("Profile with invalid web app")?;
+        if let Some(since) = &self.since {
+            let since = parse_date(since)?;
+            profiles.retain(|profile| !profile.ulid.is_nil() && profile.ulid.datetime() >= since);
+        }
 
-                let url = if site.config.manifest_url.scheme() != "data" {
-                    &site.config.manifest_url
-                } else {
-                    &site.config.document_url
-                };
+        if self.count {
+            println!("{}", profiles.len());
+            return Ok(());
+        }
 
-                println!("- {}: {} ({})", site.name(), url, site.ulid);
-            }
+        // Profiles are already in ULID order, and statistics are sorted in descending order
+        let sites = |profile: &Profile| {
+            let sites = profile.sites.iter().filter_map(|id| storage.sites.get(id));
+            sites.map(|site| &site.config).collect::<Vec<_>>()
+        };
+        match self.sort.as_str() {
+            "name" => profiles.sort_by_cached_key(|profile| {
+                profile.name.as_deref().unwrap_or_default().to_lowercase()
+            }),
+            "last-launched" => profiles.sort_by_cached_key(|profile| {
+                Reverse(sites(profile).iter().filter_map(|config| config.last_launched).max())
+            }),
+            "launch-count" => profiles.sort_by_cached_key(|profile| {
+                Reverse(sites(profile).iter().map(|config| config.launch_count).sum::<u64>())
+            }),
+            "disk-usage" => profiles.sort_by_cached_key(|profile| {
+                let directory = dirs.userdata.join("profiles").join(profile.ulid.to_string());
+                Reverse(get_size(directory).unwrap_or_default())
+            }),
+            _ => {}
+        }
 
-            println!();
+        if self.reverse {
+            profiles.reverse();
+        }
+
+        if self.json {
+            let listed: Vec<Ulid> = profiles.iter().map(|profile| profile.ulid).collect();
+            let sites: Vec<_> =
+                storage.sites.values().filter(|site| listed.contains(&site.profile)).collect();
+
+            let profiles: Vec<_> = profiles.into_iter().map(Profile::to_public_json).collect();
+            let sites: Vec<_> = sites.into_iter().map(Site::to_public_json).collect();
+            let output = serde_json::json!({ "profiles": profiles, "sites": sites });
+
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        for profile in profiles {
+            print_profile(profile, &storage)?;
         }
 
         Ok(())
     }
 }
 
+/// Prints the profile and all web apps installed in it.
+fn print_profile(profile: &Profile, storage: &Storage) -> Result<()> {
+    println!(
+        "{:=^60}\nDescription: {}\nID: {}",
+        format!(" {} ", sanitize_string(profile.name.as_deref().unwrap_or("* Unnamed *"))),
+        sanitize_string(profile.description.as_deref().unwrap_or("* Nothing *")),
+        profile.ulid
+    );
+
+    if !profile.sites.is_empty() {
+        println!("\nApps:");
+    }
+
+    for site in &profile.sites {
+        let site = storage.sites.get(site).context("Profile with invalid web app")?;
+
+        let url = if site.config.manifest_url.scheme() != "data" {
+            &site.config.manifest_url
+        } else {
+            &site.config.document_url
+        };
+
+        // Local web apps are shown with their file path, which is easier to read
+        let url = match url.to_file_path() {
+            Ok(path) if url.scheme() == "file" => path.display().to_string(),
+            _ => url.to_string(),
+        };
+
+        println!("- {}: {} ({})", site.name(), url, site.ulid);
+    }
+
+    println!();
+    Ok(())
+}
+
 impl Run for ProfileCreateCommand {
     fn run(&self) -> Result<()> {
         self._run()?;
@@ -89,18 +408,36 @@ impl Run for ProfileCreateCommand {
 
 impl ProfileCreateCommand {
     pub fn _run(&self) -> Result<Ulid> {
-        let dirs = ProjectDirs::new()?;
-        let mut storage = Storage::load(&dirs)?;
+        self._run_with(&ProjectDirs::new()?)
+    }
+
+    /// Creates the profile in the given project directories and returns its ID.
+    pub fn _run_with(&self, dirs: &ProjectDirs) -> Result<Ulid> {
+        let mut storage = Storage::load(dirs)?;
+
+        if let Some(from) = &self.from
+            && !storage.profiles.contains_key(from)
+        {
+            bail!(CommandError::ProfileNotFound);
+        }
 
         info!("Creating the profile");
 
-        let profile = Profile::new(self.name.clone(), self.description.clone());
+        let profile = ProfileBuilder::new()
+            .name(self.name.clone())
+            .description(self.description.clone())
+            .template(self.template.clone())
+            .build();
         let ulid = profile.ulid;
 
         storage.profiles.insert(ulid, profile);
-        storage.write(&dirs)?;
+        storage.write(dirs)?;
 
-        apply_profile_template(&self.template, &ulid, &dirs)?;
+        apply_profile_template(&self.template, &self.template_exclude, "overwrite", &ulid, dirs)?;
+
+        if let Some(from) = &self.from {
+            copy_profile_config(from, &ulid, dirs)?;
+        }
 
         info!("Profile created: {ulid}");
         Ok(ulid)
@@ -109,10 +446,17 @@ impl ProfileCreateCommand {
 
 impl Run for ProfileRemoveCommand {
     fn run(&self) -> Result<()> {
-        let dirs = ProjectDirs::new()?;
-        let mut storage = Storage::load(&dirs)?;
+        self._run_with(&ProjectDirs::new()?)
+    }
+}
 
-        let profile = storage.profiles.get_mut(&self.id).context("Profile does not exist")?;
+impl ProfileRemoveCommand {
+    /// Removes the profile from the given project directories.
+    pub fn _run_with(&self, dirs: &ProjectDirs) -> Result<()> {
+        let mut storage = Storage::load(dirs)?;
+
+        let id = resolve_profile(&storage, self.id, self.name.as_deref(), self.exact)?;
+        let profile = storage.profiles.get_mut(&id).context(CommandError::ProfileNotFound)?;
 
         if !self.quiet {
             warn!(
@@ -120,16 +464,8 @@ impl Run for ProfileRemoveCommand {
             );
             warn!("You might not be able to fully recover this action");
 
-            print!("Do you want to continue (y/n)? ");
-            io::stdout().flush()?;
-
-            let mut confirm = String::new();
-            io::stdin().read_line(&mut confirm)?;
-            confirm = confirm.trim().into();
-
-            if confirm != "Y" && confirm != "y" {
-                info!("Aborting!");
-                return Ok(());
+            if !confirm()? {
+                bail!(CommandError::Cancelled);
             }
         }
 
@@ -139,24 +475,24 @@ impl Run for ProfileRemoveCommand {
         }
 
         info!("Removing directories");
-        let _ = remove_dir_all(dirs.userdata.join("profiles").join(self.id.to_string()));
+        let _ = remove_dir_all(dirs.userdata.join("profiles").join(id.to_string()));
 
         info!("Removing web apps");
         for site in &profile.sites {
             if let Some(site) = storage.sites.remove(site) {
-                integrations::uninstall(&IntegrationUninstallArgs { site: &site, dirs: &dirs })
+                integrations::uninstall(&IntegrationUninstallArgs { site: &site, dirs })
                     .context("Failed to uninstall system integration")?;
             }
         }
 
         if profile.ulid != Ulid::nil() {
             info!("Removing the profile");
-            storage.profiles.remove(&self.id);
+            storage.profiles.remove(&id);
         } else {
             profile.sites.clear();
         }
 
-        storage.write(&dirs)?;
+        storage.write(dirs)?;
 
         info!("Profile removed!");
         Ok(())
@@ -165,19 +501,357 @@ impl Run for ProfileRemoveCommand {
 
 impl Run for ProfileUpdateCommand {
     fn run(&self) -> Result<()> {
-        let dirs = ProjectDirs::new()?;
-        let mut storage = Storage::load(&dirs)?;
+        self._run_with(&ProjectDirs::new()?)
+    }
+}
 
-        let profile = storage.profiles.get_mut(&self.id).context("Profile does not exist")?;
+impl ProfileUpdateCommand {
+    /// Updates the profile in the given project directories.
+    pub fn _run_with(&self, dirs: &ProjectDirs) -> Result<()> {
+        let mut storage = Storage::load(dirs)?;
+
+        let id = resolve_profile(&storage, self.id, self.profile_name.as_deref(), self.exact)?;
+        let profile = storage.profiles.get_mut(&id).context(CommandError::ProfileNotFound)?;
 
         info!("Updating the profile");
         store_value!(profile.name, self.name);
         store_value!(profile.description, self.description);
+        for tag in &self.add_tag {
+            append_value!(profile.tags, tag.to_owned());
+        }
+        for tag in &self.remove_tag {
+            remove_value!(profile.tags, tag.to_owned());
+        }
+        if self.template.is_some() {
+            profile.template.clone_from(&self.template);
+        }
+        storage.write(dirs)?;
+
+        if self.template.is_some() {
+            apply_profile_template(
+                &self.template,
+                &self.template_exclude,
+                &self.template_strategy,
+                &id,
+                dirs,
+            )?;
+        }
+
+        info!("Profile updated!");
+        Ok(())
+    }
+}
+
+impl Run for ProfileRenameCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let id = resolve_profile(&storage, self.id, self.profile_name.as_deref(), self.exact)?;
+        if !storage.profiles.contains_key(&id) {
+            bail!(CommandError::ProfileNotFound);
+        }
+
+        let name = self.name.to_lowercase();
+        let duplicate = storage.profiles.values().find(|profile| {
+            profile.ulid != id
+                && profile.name.as_ref().is_some_and(|other| other.to_lowercase() == name)
+        });
+
+        if let Some(duplicate) = duplicate {
+            if !self.allow_duplicate {
+                bail!("Profile {duplicate} already uses this name");
+            }
+            warn!("Profile {duplicate} already uses this name");
+        }
+
+        let command = ProfileUpdateCommand {
+            id: Some(id),
+            profile_name: None,
+            exact: false,
+            name: Some(Some(self.name.clone())),
+            description: None,
+            template: None,
+            template_exclude: vec![],
+            add_tag: vec![],
+            remove_tag: vec![],
+            template_strategy: "overwrite".into(),
+        };
+        command.run()
+    }
+}
+
+impl Run for ProfileExtensionsListCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let id = resolve_profile(&storage, self.id, self.name.as_deref(), self.exact)?;
+        let profile = storage.profiles.get(&id).context(CommandError::ProfileNotFound)?;
+        let extensions = profile.extensions(&dirs)?;
+
+        if extensions.is_empty() {
+            info!("No extensions installed in this profile");
+            return Ok(());
+        }
+
+        for extension in extensions {
+            let name = sanitize_string(extension.name.as_deref().unwrap_or(&extension.id));
+            let status = match (extension.version, extension.active) {
+                (Some(version), Some(false)) => format!("{version}, disabled"),
+                (Some(version), _) => version,
+                (None, _) => "pending installation".into(),
+            };
+
+            println!("- {}: {} ({})", name, sanitize_string(&extension.id), status);
+        }
+
+        Ok(())
+    }
+}
+
+impl Run for ProfileExtensionsAddCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let profile = resolve_profile(&storage, self.id, self.name.as_deref(), self.exact)?;
+        let profile = storage.profiles.get(&profile).context(CommandError::ProfileNotFound)?;
+
+        let id = match &self.extension_id {
+            Some(id) => id.to_owned(),
+            None => self
+                .xpi
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .filter(|stem| validate_extension_id(stem).is_ok())
+                .context("Failed to determine extension ID from the filename, use --extension-id")?
+                .to_owned(),
+        };
+
+        profile.add_extension(&dirs, &self.xpi, &id)?;
+
+        warn!("The extension will be installed the next time the profile is launched");
+        warn!("You might need to enable it in the add-ons manager");
+        Ok(())
+    }
+}
+
+impl Run for ProfileTemplateSaveCommand {
+    fn run(&self) -> Result<()> {
+        const SAVE_ERROR: &str = "Failed to save the profile template";
+
+        // Lock files of a running profile would prevent profiles created from the template to start
+        const LOCK_FILES: [&str; 3] = ["parent.lock", "lock", ".parentlock"];
+
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let id = resolve_profile(&storage, self.id, self.name.as_deref(), self.exact)?;
+        if !storage.profiles.contains_key(&id) {
+            bail!(CommandError::ProfileNotFound);
+        }
+
+        let source = dirs.userdata.join("profiles").join(id.to_string());
+        if !source.is_dir() {
+            bail!("Profile does not have a directory yet, launch its web app first");
+        }
+
+        if self.output.exists() && read_dir(&self.output).context(SAVE_ERROR)?.next().is_some() {
+            bail!("Template directory {} is not empty", self.output.display());
+        }
+
+        info!("Saving the profile template");
+        create_dir_all(&self.output).context(SAVE_ERROR)?;
+
+        let mut options = CopyOptions::new();
+        options.content_only = true;
+        copy(&source, &self.output, &options).context(SAVE_ERROR)?;
+
+        for lock in LOCK_FILES {
+            let path = self.output.join(lock);
+            if path.symlink_metadata().is_ok() {
+                remove_file(path).context(SAVE_ERROR)?;
+            }
+        }
+
+        info!("Profile template saved: {}", self.output.display());
+        Ok(())
+    }
+}
+
+impl Run for ProfilePathCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let id = resolve_profile(&storage, self.id, self.name.as_deref(), self.exact)?;
+        if !storage.profiles.contains_key(&id) {
+            bail!(CommandError::ProfileNotFound);
+        }
+
+        // Profile directories are only created when a web app is launched for the first time
+        let path = dirs.userdata.join("profiles").join(id.to_string());
+
+        if self.json {
+            let output = serde_json::json!({
+                "id": id,
+                "path": path,
+                "exists": path.is_dir(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            println!("{}", path.display());
+        }
+
+        Ok(())
+    }
+}
+
+impl Run for ProfileShowCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let id = resolve_profile(&storage, self.id, self.name.as_deref(), self.exact)?;
+        let profile = storage.profiles.get(&id).context(CommandError::ProfileNotFound)?;
+
+        // The default profile is not created by the user, so it does not have a creation time
+        let created = (!id.is_nil()).then(|| id.datetime());
+
+        // Profile directories are only created when a web app is launched for the first time
+        let directory = dirs.userdata.join("profiles").join(id.to_string());
+        let size = if directory.is_dir() {
+            Some(get_size(&directory).context("Failed to calculate profile size")?)
+        } else {
+            None
+        };
+
+        let sites: Vec<&Site> =
+            profile.sites.iter().filter_map(|site| storage.sites.get(site)).collect();
+
+        if self.json {
+            let sites: Vec<_> = sites
+                .iter()
+                .map(|site| {
+                    serde_json::json!({
+                        "id": site.ulid,
+                        "name": site.name(),
+                        "manifestUrl": site.config.manifest_url,
+                        "lastLaunched": site.config.last_launched,
+                    })
+                })
+                .collect();
+
+            let output = serde_json::json!({
+                "id": profile.ulid,
+                "name": profile.name,
+                "description": profile.description,
+                "created": created,
+                "tags": profile.tags,
+                "directory": directory,
+                "size": size,
+                "sites": sites,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        let optional = |value: &Option<String>| match value.as_deref() {
+            Some(value) if !value.is_empty() => sanitize_string(value),
+            _ => "none".into(),
+        };
+
+        println!("Name: {}", optional(&profile.name));
+        println!("Description: {}", optional(&profile.description));
+        println!("ID: {}", profile.ulid);
+        println!("Created: {}", created.map_or("unknown".into(), format_elapsed));
+        println!("Tags: {}", optional(&Some(profile.tags.join(", "))));
+        println!("Directory: {}", directory.display());
+        println!("Disk usage: {}", size.map_or("not created yet".into(), format_size));
+        println!();
+
+        println!("Web apps: {}", sites.len());
+        for site in sites {
+            let last = site.config.last_launched.map_or("never".into(), format_elapsed);
+            println!("- {}: {} ({})", site.name(), site.config.manifest_url, site.ulid);
+            println!("  Last launched: {last}");
+        }
+
+        Ok(())
+    }
+}
+
+impl Run for ProfileMergeCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let mut storage = Storage::load(&dirs)?;
+
+        let from = resolve_profile(&storage, self.from, self.from_name.as_deref(), self.exact)?;
+        let into = resolve_profile(&storage, self.into, self.into_name.as_deref(), self.exact)?;
+
+        if from == Ulid::nil() {
+            bail!("Default profile cannot be merged into another profile");
+        }
+
+        if from == into {
+            bail!("Profile cannot be merged into itself");
+        }
+
+        if !storage.profiles.contains_key(&into) {
+            bail!(CommandError::ProfileNotFound);
+        }
+
+        let source = storage.profiles.remove(&from).context(CommandError::ProfileNotFound)?;
+
+        if !self.quiet {
+            warn!(
+                "This will move all web apps into the target profile and remove the source profile"
+            );
+            if !self.merge_data {
+                warn!("Data of the source profile will be removed, use --merge-data to keep it");
+            }
+
+            if !confirm()? {
+                bail!(CommandError::Cancelled);
+            }
+        }
+
+        let source_dir = dirs.userdata.join("profiles").join(from.to_string());
+        let target_dir = dirs.userdata.join("profiles").join(into.to_string());
+
+        // Data is merged first, so nothing is changed if copying fails
+        if self.merge_data && source_dir.is_dir() {
+            let mut options = CopyOptions::new();
+            options.content_only = true;
+            options.overwrite = true;
+
+            info!("Merging profile data");
+            create_dir_all(&target_dir).context("Failed to create a profile directory")?;
+            copy(&source_dir, &target_dir, &options).context("Failed to merge profile data")?;
+        }
+
+        info!("Moving web apps");
+        for ulid in &source.sites {
+            if let Some(site) = storage.sites.get_mut(ulid) {
+                site.profile = into;
+            }
+        }
+
+        let target = storage.profiles.get_mut(&into).unwrap();
+        for ulid in source.sites {
+            if !target.sites.contains(&ulid) {
+                target.sites.push(ulid);
+            }
+        }
+
+        // All changes are written at once, so the storage is never left half-merged
         storage.write(&dirs)?;
 
-        apply_profile_template(&self.template, &self.id, &dirs)?;
+        info!("Removing the source profile");
+        let _ = remove_dir_all(source_dir);
 
-        info!("Profile updated!");
+        info!("Profile merged!");
         Ok(())
     }
 }
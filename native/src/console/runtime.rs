@@ -1,40 +1,82 @@
 use anyhow::{Context, Result};
 use cfg_if::cfg_if;
+#[cfg(not(feature = "immutable-runtime"))]
+use log::info;
 
 use crate::components::runtime::Runtime;
+#[cfg(not(feature = "immutable-runtime"))]
+use crate::components::runtime::{get_latest_release, is_newer_version};
 use crate::console::Run;
-use crate::console::app::{RuntimeInstallCommand, RuntimePatchCommand, RuntimeUninstallCommand};
+use crate::console::app::{
+    RuntimeInstallCommand,
+    RuntimePatchCommand,
+    RuntimePathCommand,
+    RuntimeUninstallCommand,
+    RuntimeUpdateCommand,
+};
 use crate::directories::ProjectDirs;
+use crate::error::CommandError;
+use crate::utils::Progress;
+#[cfg(not(feature = "immutable-runtime"))]
+use crate::utils::Cancellation;
 
 impl Run for RuntimeInstallCommand {
     #[cfg(not(feature = "immutable-runtime"))]
     fn run(&self) -> Result<()> {
-        cfg_if! {
-            if #[cfg(platform_windows)] {
-                use log::warn;
-                use crate::components::_7zip::_7Zip;
-
-                let _7zip = _7Zip::new()?;
-                if _7zip.version.is_none() {
-                    warn!("7-Zip is currently not installed and will be installed automatically");
-                    warn!("You can remove it manually after the runtime is installed");
-                    _7zip.install().context("Failed to install 7-Zip")?;
-                }
+        let result = self._run(None);
+
+        if self.notify {
+            let body = match &result {
+                Ok(_) => "Runtime installed successfully".to_string(),
+                Err(error) => format!("Failed to install runtime: {error}"),
+            };
+
+            if let Err(error) = crate::utils::send_notification("Runtime install finished", &body) {
+                log::warn!("{error:?}");
             }
         }
 
+        result
+    }
+
+    #[cfg(feature = "immutable-runtime")]
+    fn run(&self) -> Result<()> {
+        self._run(None)
+    }
+}
+
+impl RuntimeInstallCommand {
+    /// Installs the runtime and reports each stage to the progress callback.
+    #[cfg(not(feature = "immutable-runtime"))]
+    pub fn _run(&self, progress: Option<&Progress>) -> Result<()> {
         let dirs = ProjectDirs::new()?;
+
+        let cancellation = match &self.operation {
+            Some(id) => Some(Cancellation::new(&dirs, id)?),
+            None => None,
+        };
+
+        ensure_7zip(cancellation.as_ref(), progress)?;
+
         let runtime = Runtime::new(&dirs)?;
 
         #[cfg(platform_linux)]
         if self.link {
             runtime.link().context("Failed to link runtime")?
         } else {
-            runtime.install().context("Failed to install runtime")?;
+            runtime
+                .install(&self.release()?, cancellation.as_ref(), progress)
+                .context("Failed to install runtime")?;
         }
 
         #[cfg(not(platform_linux))]
-        runtime.install().context("Failed to install runtime")?;
+        runtime
+            .install(&self.release()?, cancellation.as_ref(), progress)
+            .context("Failed to install runtime")?;
+
+        if let Some(progress) = progress {
+            progress.report(None, "Patching the runtime")?;
+        }
 
         let runtime = Runtime::new(&dirs)?;
         runtime.patch(&dirs, None)?;
@@ -43,9 +85,60 @@ impl Run for RuntimeInstallCommand {
     }
 
     #[cfg(feature = "immutable-runtime")]
-    fn run(&self) -> Result<()> {
+    pub fn _run(&self, _progress: Option<&Progress>) -> Result<()> {
         anyhow::bail!("Cannot install runtime when the immutable runtime feature is enabled")
     }
+
+    /// Returns the pinned runtime version or the latest release.
+    #[cfg(not(feature = "immutable-runtime"))]
+    fn release(&self) -> Result<String> {
+        match &self.runtime_version {
+            Some(version) => Ok(version.to_owned()),
+            None => get_latest_release(&release_channel(self.channel.as_ref())),
+        }
+    }
+}
+
+impl Run for RuntimeUpdateCommand {
+    #[cfg(not(feature = "immutable-runtime"))]
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let runtime = Runtime::new(&dirs)?;
+
+        let Some(current) = runtime.version.clone() else {
+            anyhow::bail!(CommandError::RuntimeNotInstalled);
+        };
+
+        #[cfg(platform_linux)]
+        if crate::storage::Storage::load(&dirs)?.config.use_linked_runtime {
+            anyhow::bail!("Linked runtime must be updated with the system package manager");
+        }
+
+        let channel = release_channel(self.channel.as_ref());
+
+        info!("Checking for runtime updates");
+        let latest = get_latest_release(&channel)?;
+
+        if !self.force && !is_newer_version(&latest, &current) {
+            info!("Runtime is already up to date: {current}");
+            return Ok(());
+        }
+
+        info!("Updating runtime: {current} -> {latest}");
+        ensure_7zip(None, None)?;
+        runtime.install(&latest, None, None).context("Failed to update runtime")?;
+
+        let runtime = Runtime::new(&dirs)?;
+        runtime.patch(&dirs, None)?;
+
+        info!("Runtime updated!");
+        Ok(())
+    }
+
+    #[cfg(feature = "immutable-runtime")]
+    fn run(&self) -> Result<()> {
+        anyhow::bail!("Cannot update runtime when the immutable runtime feature is enabled")
+    }
 }
 
 impl Run for RuntimeUninstallCommand {
@@ -70,3 +163,83 @@ impl Run for RuntimePatchCommand {
         runtime.patch(&dirs, None)
     }
 }
+
+impl Run for RuntimePathCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let runtime = Runtime::new(&dirs)?;
+
+        if runtime.version.is_none() {
+            anyhow::bail!(CommandError::RuntimeNotInstalled);
+        }
+
+        if self.json {
+            let output = serde_json::json!({
+                "version": runtime.version,
+                "directory": runtime.directory,
+                "executable": runtime.executable,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            println!("{}", runtime.executable.display());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the channel argument, the `channel` config option or the release channel.
+#[cfg(not(feature = "immutable-runtime"))]
+fn release_channel(channel: Option<&String>) -> String {
+    match channel {
+        Some(channel) => channel.to_owned(),
+        None => std::env::var("FIREFOXPWA_RUNTIME_CHANNEL").unwrap_or_else(|_| "release".into()),
+    }
+}
+
+/// Make sure 7-Zip is installed before extracting the runtime on Windows.
+#[cfg(not(feature = "immutable-runtime"))]
+fn ensure_7zip(cancellation: Option<&Cancellation>, progress: Option<&Progress>) -> Result<()> {
+    cfg_if! {
+        if #[cfg(platform_windows)] {
+            use std::io::{self, IsTerminal};
+
+            use anyhow::bail;
+            use log::warn;
+
+            use crate::components::_7zip::_7Zip;
+            use crate::console::confirm;
+
+            // Installers of custom versions run as administrator, so the user needs to check them
+            let accept_checksum = |version: &str, _: &str| {
+                if !io::stdin().is_terminal() {
+                    bail!("Set FIREFOXPWA_7ZIP_SHA256 to the 7-Zip {version} installer checksum");
+                }
+                confirm()
+            };
+
+            let _7zip = _7Zip::new()?;
+            if _7zip.version.is_none() {
+                warn!("7-Zip is currently not installed and will be installed automatically");
+                warn!("You can remove it manually after the runtime is installed");
+                _7zip
+                    .install(cancellation, progress, accept_checksum)
+                    .context("Failed to install 7-Zip")?;
+            } else if _7zip.is_outdated() && io::stdin().is_terminal() {
+                warn!("7-Zip is too old to extract the runtime and needs to be upgraded");
+                if !confirm()? {
+                    bail!(CommandError::Cancelled);
+                }
+                _7zip
+                    .install(cancellation, progress, accept_checksum)
+                    .context("Failed to upgrade 7-Zip")?;
+            } else {
+                _7zip.ensure_minimum()?;
+            }
+        } else {
+            let _ = (cancellation, progress);
+        }
+    }
+
+    Ok(())
+}
@@ -1,35 +1,139 @@
-use std::fs::metadata;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{metadata, read_to_string, remove_file};
 use std::io;
-use std::io::Write;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result, bail};
 use cfg_if::cfg_if;
-use log::{info, warn};
+use log::{error, info, warn};
+use reqwest::blocking::Client;
+use reqwest::header::HeaderMap;
 use ulid::Ulid;
 use url::Url;
 
 use crate::components::runtime::Runtime;
-use crate::components::site::{Site, SiteConfig};
+use crate::components::site::{Site, SiteConfigBuilder, validate_locale, validate_proxy};
 use crate::console::app::{
+    ProfileCreateCommand,
+    ProfileRemoveCommand,
+    SiteGroupCommand,
     SiteInstallCommand,
     SiteLaunchCommand,
+    SiteListCommand,
+    SitePinCommand,
+    SiteRepairCommand,
+    SiteShowCommand,
+    SiteUngroupCommand,
     SiteUninstallCommand,
+    SiteUnpinCommand,
     SiteUpdateCommand,
+    SiteVerifyCommand,
+};
+use crate::console::profile::resolve_profile;
+use crate::console::{
+    Run,
+    append_value,
+    confirm,
+    format_elapsed,
+    parse_date,
+    parse_duration,
+    prompt,
+    remove_value,
+    store_validated_value,
+    store_value,
+    store_value_vec,
 };
-use crate::console::{Run, store_value, store_value_vec};
 use crate::directories::ProjectDirs;
+use crate::error::CommandError;
 use crate::integrations;
 use crate::integrations::{IntegrationInstallArgs, IntegrationUninstallArgs};
 use crate::storage::Storage;
-use crate::utils::construct_certificates_and_client;
+use crate::utils::{
+    ClientArgs,
+    Progress,
+    construct_certificates_and_client,
+    normalize_url,
+    parse_headers,
+    run_parallel,
+    sanitize_string,
+    send_notification,
+};
+
+/// Parses a window size in the `WIDTHxHEIGHT` format, such as `1200x800`.
+fn parse_size(size: &str) -> Result<(u32, u32)> {
+    let parsed = size.split_once('x').and_then(|(width, height)| {
+        Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+    });
+
+    match parsed {
+        Some((width @ 1.., height @ 1..)) => Ok((width, height)),
+        _ => bail!("Invalid window size: {size}, expected a size like 1200x800"),
+    }
+}
+
+/// Parses a window position in the `X,Y` format, such as `100,100`.
+///
+/// Coordinates can be negative, because screens can be placed left of or above the primary one.
+fn parse_position(position: &str) -> Result<(i32, i32)> {
+    let parsed = position
+        .split_once(',')
+        .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)));
+
+    parsed.with_context(|| format!("Invalid window position: {position}, expected X,Y"))
+}
+
+/// Resolves the web app ID from either the ID or the document URL argument.
+///
+/// If multiple web apps match the URL, they are listed and the user
+/// is asked to select the web app by its ID instead.
+fn resolve_site(storage: &Storage, id: Option<Ulid>, url: Option<&Url>) -> Result<Ulid> {
+    let Some(url) = url else {
+        return id.context("Web app ID or document URL is required");
+    };
+
+    match storage.find_sites_by_url(url).as_slice() {
+        [] => bail!(CommandError::SiteNotFound),
+        [(ulid, _)] => Ok(*ulid),
+        sites => {
+            println!("Multiple web apps match {url}:");
+            for (ulid, site) in sites {
+                println!("- {}: {} ({})", site.name(), site.config.document_url, ulid);
+            }
+            println!();
+
+            bail!("Document URL is ambiguous, use the web app ID instead")
+        }
+    }
+}
+
+/// Updates the launch statistics of the web app.
+///
+/// The storage is loaded again, so the launch itself does not need to hold
+/// a mutable reference to it while the runtime and profile are prepared.
+fn record_launch(dirs: &ProjectDirs, id: &Ulid) -> Result<()> {
+    let mut storage = Storage::load(dirs)?;
+
+    if let Some(site) = storage.sites.get_mut(id) {
+        site.config.last_launched = Some(SystemTime::now());
+        site.config.launch_count += 1;
+        storage.write(dirs)?;
+    }
+
+    Ok(())
+}
 
 impl Run for SiteLaunchCommand {
     fn run(&self) -> Result<()> {
         let dirs = ProjectDirs::new()?;
         let storage = Storage::load(&dirs)?;
 
-        let site = storage.sites.get(&self.id).context("Web app does not exist")?;
+        let id = resolve_site(&storage, self.id, self.document_url.as_ref())?;
+        let site = storage.sites.get(&id).context(CommandError::SiteNotFound)?;
         let args = if !&self.arguments.is_empty() { &self.arguments } else { &storage.arguments };
+        let args = &[args.as_slice(), &self.runtime_arg].concat();
 
         #[cfg(platform_macos)]
         {
@@ -42,8 +146,32 @@ impl Run for SiteLaunchCommand {
         let runtime = Runtime::new(&dirs)?;
         let profile = storage.profiles.get(&site.profile).context("Web app without a profile")?;
 
+        // Fall back to an existing Firefox installation if the runtime is not installed
+        #[cfg(platform_macos)]
+        let (runtime, system) = match runtime.version {
+            Some(_) => (runtime, false),
+            None => match Runtime::new_from_system()? {
+                Some(system) => (system, true),
+                None => (runtime, false),
+            },
+        };
+        #[cfg(not(platform_macos))]
+        let system = false;
+
         if runtime.version.is_none() {
-            bail!("Runtime not installed");
+            warn!("Install the runtime using `firefoxpwa runtime install`");
+
+            #[cfg(platform_linux)]
+            if let Some(command) = crate::components::runtime::get_package_manager_command() {
+                warn!("Alternatively, install Firefox with `{command}`");
+                warn!("Then link it using `firefoxpwa runtime install --link`");
+            }
+
+            bail!(CommandError::RuntimeNotInstalled);
+        }
+
+        if let Err(error) = record_launch(&dirs, &id) {
+            warn!("Failed to record launch statistics: {error:?}");
         }
 
         #[cfg(all(platform_linux, not(feature = "immutable-runtime")))]
@@ -96,9 +224,13 @@ impl Run for SiteLaunchCommand {
             }
         };
 
+        // The system installation is never patched, as that would modify the user's own
+        // Firefox and break its code signature, so only the profile is patched for it
         if should_patch {
-            #[cfg(not(feature = "immutable-runtime"))]
-            runtime.patch(&dirs, Some(site))?;
+            if !system {
+                #[cfg(not(feature = "immutable-runtime"))]
+                runtime.patch(&dirs, Some(site))?;
+            }
             profile.patch(&dirs)?;
         }
 
@@ -155,59 +287,127 @@ impl Run for SiteLaunchCommand {
 
 impl Run for SiteInstallCommand {
     fn run(&self) -> Result<()> {
-        self._run()?;
+        if self.manifest_url.is_some() {
+            self._run()?;
+            return Ok(());
+        }
+
+        if !io::stdin().is_terminal() {
+            bail!("The manifest URL is required when stdin is not interactive");
+        }
+
+        self.wizard()?;
         Ok(())
     }
 }
 
 impl SiteInstallCommand {
     pub fn _run(&self) -> Result<Ulid> {
-        if self.manifest_url.scheme() == "data" && self.document_url.is_none() {
+        self._run_with_progress(None)
+    }
+
+    /// Installs the web app and reports each stage to the progress callback.
+    pub fn _run_with_progress(&self, progress: Option<&Progress>) -> Result<Ulid> {
+        let manifest_url = self.manifest_url.as_ref().context("The manifest URL is required")?;
+        let manifest_url = &normalize_url(manifest_url);
+
+        if manifest_url.scheme() == "data" && self.document_url.is_none() {
             bail!("The document URL is required when the manifest URL is a data URL");
         }
 
+        let local = [Some(manifest_url), self.document_url.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|url| url.scheme() == "file");
+        if local && !self.allow_file_url {
+            bail!("Local file URLs are only allowed with --allow-file-url");
+        }
+
         let dirs = ProjectDirs::new()?;
         let mut storage = Storage::load(&dirs)?;
 
-        let profile = storage
-            .profiles
-            .get_mut(&self.profile.unwrap_or_else(Ulid::nil))
-            .context("Profile does not exist")?;
+        let profile = match &self.profile_name {
+            Some(name) => resolve_profile(&storage, None, Some(name), self.profile_exact)?,
+            None => self.profile.unwrap_or_else(Ulid::nil),
+        };
+        let profile = storage.profiles.get_mut(&profile).context(CommandError::ProfileNotFound)?;
+
+        let proxied =
+            |id: &Ulid| storage.sites.get(id).is_some_and(|site| site.config.proxy.is_some());
+        if profile.sites.iter().any(proxied) {
+            warn!("Profile contains a web app with a proxy, which will also apply to this web app");
+        }
+
+        let localized =
+            |id: &Ulid| storage.sites.get(id).is_some_and(|site| site.config.locale.is_some());
+        if profile.sites.iter().any(localized) {
+            warn!("Profile contains a web app with a locale, which will also apply to this one");
+        }
 
         info!("Installing the web app");
 
-        let config = SiteConfig {
-            name: self.name.clone(),
-            description: self.description.clone(),
-            categories: self.categories.clone(),
-            keywords: self.keywords.clone(),
-            document_url: match &self.document_url {
-                Some(url) => url.clone(),
-                None => self.manifest_url.join(".")?,
-            },
-            manifest_url: self.manifest_url.clone(),
-            start_url: self.start_url.clone(),
-            icon_url: self.icon_url.clone(),
-            enabled_url_handlers: vec![],
-            enabled_protocol_handlers: vec![],
-            custom_protocol_handlers: vec![],
-            launch_on_login: self.launch_on_login.unwrap_or(false),
-            launch_on_browser: self.launch_on_browser.unwrap_or(false),
+        let document_url = match &self.document_url {
+            Some(url) => url.clone(),
+            None => manifest_url.join(".")?,
         };
 
-        let client = construct_certificates_and_client(
-            self.client.user_agent.as_deref(),
-            &self.client.tls_root_certificates_der,
-            &self.client.tls_root_certificates_pem,
-            self.client.tls_danger_accept_invalid_certs,
-            self.client.tls_danger_accept_invalid_hostnames,
-        )?;
+        // URLs are compared normalized, so trailing fragments or reordered queries still match
+        let normalized = normalize_url(&document_url);
+        let duplicates: Vec<&Site> = storage
+            .sites
+            .values()
+            .filter(|site| {
+                normalize_url(&site.config.document_url) == normalized
+                    || normalize_url(&site.config.manifest_url) == *manifest_url
+            })
+            .collect();
+
+        if !duplicates.is_empty() {
+            for site in &duplicates {
+                warn!("Web app with the same URL is already installed: {site}");
+            }
+
+            if !self.force_duplicate {
+                bail!("Web app is already installed, use --force-duplicate to install it again");
+            }
+        }
+
+        let config = SiteConfigBuilder::new()
+            .manifest_url(manifest_url.as_str())
+            .document_url(document_url.as_str())
+            .name(self.name.clone())
+            .description(self.description.clone())
+            .start_url(self.start_url.clone())
+            .icon_url(self.icon_url.clone())
+            .categories(self.categories.clone())
+            .keywords(self.keywords.clone())
+            .launch_on_login(self.launch_on_login.unwrap_or(false))
+            .launch_on_browser(self.launch_on_browser.unwrap_or(false))
+            .single_instance(self.single_instance.unwrap_or(false))
+            .build()?;
 
-        let site = Site::new(profile.ulid, config, &client)?;
+        let client = self.client()?;
+        let headers = self.headers()?;
+        if let Some(progress) = progress {
+            progress.report(None, "Downloading the web app manifest")?;
+        }
+        let json = Site::download_manifest_with_headers(&config.manifest_url, &client, &headers)?;
+        // Without icons, the favicon lookup is also skipped, so it works offline
+        let mut site = if self.no_icon {
+            Site::from_manifest_without_icons(profile.ulid, config, &json)?
+        } else {
+            Site::from_manifest(profile.ulid, config, &json, &client)?
+        };
+        site.config.last_manifest_fetched = Some(SystemTime::now());
+        site.config.last_updated = site.config.last_manifest_fetched;
         let ulid = site.ulid;
 
+        if self.save_manifest {
+            site.save_manifest(&dirs, &json)?;
+        }
+
         if self.system_integration {
-            info!("Installing system integration");
+            Progress::stage(progress, "Installing system integration")?;
             integrations::install(&IntegrationInstallArgs {
                 site: &site,
                 dirs: &dirs,
@@ -227,10 +427,12 @@ impl SiteInstallCommand {
 
         if self.launch_now {
             let command = SiteLaunchCommand {
-                id: ulid,
+                id: Some(ulid),
+                document_url: None,
                 url: vec![],
                 protocol: None,
                 arguments: vec![],
+                runtime_arg: self.runtime_arg.clone(),
                 #[cfg(platform_macos)]
                 direct_launch: false,
             };
@@ -239,29 +441,183 @@ impl SiteInstallCommand {
 
         Ok(ulid)
     }
+
+    fn client(&self) -> Result<Client> {
+        construct_certificates_and_client(&ClientArgs::from(&self.client))
+    }
+
+    fn headers(&self) -> Result<HeaderMap> {
+        parse_headers(self.credentials.auth.as_deref(), &self.credentials.headers)
+    }
+
+    /// Asks for the manifest URL, profile, name and icon step by step and installs the web app.
+    ///
+    /// Values that were already passed as arguments are kept, and nothing is
+    /// changed until the final confirmation, including creating a new profile.
+    fn wizard(&self) -> Result<Ulid> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+        let client = self.client()?;
+        let headers = self.headers()?;
+        let mut command = self.clone();
+
+        let preview = loop {
+            let url = match Url::parse(&prompt("Manifest URL")?) {
+                Ok(url) => url,
+                Err(error) => {
+                    error!("Invalid manifest URL: {error}");
+                    continue;
+                }
+            };
+
+            if url.scheme() == "data" && self.document_url.is_none() {
+                error!("The document URL is required when the manifest URL is a data URL");
+                continue;
+            }
+
+            if url.scheme() == "file" && !self.allow_file_url {
+                error!("Local file URLs are only allowed with --allow-file-url");
+                continue;
+            }
+
+            let document_url = match &self.document_url {
+                Some(url) => url.clone(),
+                None => url.join(".")?,
+            };
+
+            let config = SiteConfigBuilder::new()
+                .manifest_url(url.as_str())
+                .document_url(document_url.as_str())
+                .name(self.name.clone())
+                .icon_url(self.icon_url.clone())
+                .build()?;
+
+            let preview = Site::download_manifest_with_headers(&url, &client, &headers)
+                .and_then(|json| Site::from_manifest(Ulid::nil(), config, &json, &client));
+
+            match preview {
+                Ok(site) => {
+                    command.manifest_url = Some(url);
+                    break site;
+                }
+                Err(error) => error!("{error:?}"),
+            }
+        };
+
+        let mut new_profile = None;
+
+        if self.profile.is_none() && self.profile_name.is_none() {
+            let profiles: Vec<_> = storage.profiles.values().collect();
+
+            println!("Available profiles:");
+            for (index, profile) in profiles.iter().enumerate() {
+                let name = profile.name.as_deref().unwrap_or("Unnamed");
+                println!("  {}: {name} ({})", index + 1, profile.ulid);
+            }
+            println!("  n: Create a new profile");
+
+            loop {
+                let answer = prompt("Profile [1]")?;
+
+                if answer.is_empty() {
+                    break;
+                }
+
+                if answer == "n" || answer == "N" {
+                    let name = prompt("Profile name")?;
+                    new_profile = Some(if name.is_empty() { None } else { Some(name) });
+                    break;
+                }
+
+                let index = answer.parse::<usize>().ok().and_then(|index| index.checked_sub(1));
+                match index.and_then(|index| profiles.get(index)) {
+                    Some(profile) => {
+                        command.profile = Some(profile.ulid);
+                        break;
+                    }
+                    None => error!("Invalid profile selection: {answer}"),
+                }
+            }
+        }
+
+        if self.name.is_none() {
+            let name = prompt(&format!("Name [{}]", preview.name()))?;
+            if !name.is_empty() {
+                command.name = Some(name);
+            }
+        }
+
+        if self.icon_url.is_none() && !self.no_icon {
+            let icon = preview.icons().into_iter().find_map(|icon| icon.src.try_into().ok());
+            let default = icon.map_or_else(|| "placeholder".into(), |url: Url| url.to_string());
+
+            loop {
+                let answer = prompt(&format!("Icon URL [{default}]"))?;
+
+                if answer.is_empty() {
+                    break;
+                }
+
+                match Url::parse(&answer) {
+                    Ok(url) => {
+                        command.icon_url = Some(url);
+                        break;
+                    }
+                    Err(error) => error!("Invalid icon URL: {error}"),
+                }
+            }
+        }
+
+        if !confirm()? {
+            bail!(CommandError::Cancelled);
+        }
+
+        let Some(name) = new_profile else {
+            return command._run();
+        };
+
+        let create = ProfileCreateCommand {
+            name,
+            description: None,
+            template: None,
+            template_exclude: vec![],
+            from: None,
+        };
+        command.profile = Some(create._run()?);
+
+        let result = command._run();
+        if result.is_err() {
+            // Do not leave behind an empty profile that was only created for this web app
+            let remove =
+                ProfileRemoveCommand { id: command.profile, name: None, exact: false, quiet: true };
+            if let Err(error) = remove.run() {
+                warn!("Failed to remove the new profile: {error:?}");
+            }
+        }
+        result
+    }
 }
 
 impl Run for SiteUninstallCommand {
     fn run(&self) -> Result<()> {
-        let dirs = ProjectDirs::new()?;
-        let mut storage = Storage::load(&dirs)?;
+        self._run_with(&ProjectDirs::new()?)
+    }
+}
+
+impl SiteUninstallCommand {
+    /// Uninstalls the web app from the given project directories.
+    pub fn _run_with(&self, dirs: &ProjectDirs) -> Result<()> {
+        let mut storage = Storage::load(dirs)?;
 
-        let site = storage.sites.get(&self.id).context("Web app does not exist")?;
+        let id = resolve_site(&storage, self.id, self.document_url.as_ref())?;
+        let site = storage.sites.get(&id).context(CommandError::SiteNotFound)?;
 
         if !self.quiet {
             warn!("This will remove the web app");
             warn!("Data will NOT be removed, remove them from the app browser");
 
-            print!("Do you want to continue (y/n)? ");
-            io::stdout().flush()?;
-
-            let mut confirm = String::new();
-            io::stdin().read_line(&mut confirm)?;
-            confirm = confirm.trim().into();
-
-            if confirm != "Y" && confirm != "y" {
-                info!("Aborting!");
-                return Ok(());
+            if !confirm()? {
+                bail!(CommandError::Cancelled);
             }
         }
 
@@ -271,59 +627,483 @@ impl Run for SiteUninstallCommand {
             .get_mut(&site.profile)
             .context("Web app with invalid profile")?
             .sites
-            .retain(|id| *id != self.id);
-        let site = storage.sites.remove(&self.id);
+            .retain(|site| *site != id);
+        let site = storage.sites.remove(&id);
+
+        // The manifest cache is not needed anymore
+        if let Some(site) = &site {
+            let _ = remove_file(site.manifest_path(dirs));
+        }
 
         if self.system_integration
             && let Some(site) = site
         {
+            if site.config.pinned
+                && let Err(error) = integrations::pin(&site, false)
+            {
+                warn!("Failed to unpin web app: {error:?}");
+            }
+
             info!("Uninstalling system integration");
-            integrations::uninstall(&IntegrationUninstallArgs { site: &site, dirs: &dirs })
+            integrations::uninstall(&IntegrationUninstallArgs { site: &site, dirs })
                 .context("Failed to uninstall system integration")?;
         }
 
-        storage.write(&dirs)?;
+        storage.write(dirs)?;
 
         info!("Web app uninstalled!");
         Ok(())
     }
 }
 
+impl SiteUpdateCommand {
+    /// Updates the web app manifest, using or filling the manifest cache if requested.
+    fn update_manifest(&self, site: &mut Site, dirs: &ProjectDirs, client: &Client) -> Result<()> {
+        const UPDATE_ERROR: &str = "Failed to update web app manifest";
+
+        if self.from_cache {
+            let json = site.load_manifest(dirs)?;
+            return site.update_from_manifest(&json, client).context(UPDATE_ERROR);
+        }
+
+        let headers = self.headers()?;
+
+        if self.save_manifest {
+            let url = &site.config.manifest_url;
+            let json = Site::download_manifest_with_headers(url, client, &headers)?;
+            site.update_from_manifest(&json, client).context(UPDATE_ERROR)?;
+            site.config.last_manifest_fetched = Some(SystemTime::now());
+            return site.save_manifest(dirs, &json);
+        }
+
+        site.update_with_headers(client, &headers).context(UPDATE_ERROR)
+    }
+
+    fn headers(&self) -> Result<HeaderMap> {
+        parse_headers(self.credentials.auth.as_deref(), &self.credentials.headers)
+    }
+
+    /// Refreshes icons of a single web app without changing any other properties.
+    fn update_icons_only(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let mut storage = Storage::load(&dirs)?;
+
+        let id = resolve_site(&storage, self.id, self.document_url.as_ref())?;
+        let site = storage.sites.get_mut(&id).context(CommandError::SiteNotFound)?;
+        let original = site.clone();
+
+        let client = construct_certificates_and_client(&ClientArgs::from(&self.client))?;
+
+        // The manifest is processed separately, so only its icons are taken
+        info!("Updating web app icons");
+        let mut updated = site.clone();
+        updated
+            .update_with_headers(&client, &self.headers()?)
+            .context("Failed to update web app manifest")?;
+        site.manifest.icons = updated.manifest.icons;
+        site.config.fallback_icon_url = updated.config.fallback_icon_url;
+
+        if self.dry_run {
+            print_changes(&original, site);
+            return Ok(());
+        }
+
+        // Icons are regenerated even if their URLs did not change, as the files might be broken
+        site.config.integration_hash = None;
+
+        info!("Updating system integration");
+        let hash = integrations::update(&IntegrationInstallArgs {
+            site,
+            dirs: &dirs,
+            client: Some(&client),
+            update_manifest: false,
+            update_icons: true,
+            old_name: Some(&original.name()),
+        })
+        .context("Failed to update system integration")?;
+
+        if hash.is_some() {
+            site.config.integration_hash = hash;
+        }
+
+        site.config.last_updated = Some(SystemTime::now());
+        storage.write(&dirs)?;
+
+        info!("Web app icons updated!");
+        Ok(())
+    }
+
+    fn update_all(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let mut storage = Storage::load(&dirs)?;
+
+        let client = construct_certificates_and_client(&ClientArgs::from(&self.client))?;
+
+        // Web apps without the update time are always stale, so they are updated
+        let stale = self.stale.as_deref().map(parse_duration).transpose()?;
+        let is_stale = |site: &Site| {
+            let updated = site.config.last_updated.or(site.config.last_manifest_fetched);
+            match (stale, updated) {
+                (Some(stale), Some(updated)) => updated.elapsed().is_ok_and(|age| age > stale),
+                _ => true,
+            }
+        };
+
+        // Manifests are fetched concurrently, while the system integration
+        // is updated sequentially to prevent conflicting system changes
+        let sites: Vec<Site> = filter_sites(&storage, self.pattern.as_deref(), self.profile)?
+            .into_iter()
+            .filter(|site| is_stale(site))
+            .cloned()
+            .collect();
+
+        // Dry runs do not change anything, so they do not need to be confirmed
+        print_selected(&sites);
+        if !sites.is_empty() && !self.quiet && !self.dry_run {
+            warn!("This will update manifests and system integration of the selected web apps");
+
+            if !confirm()? {
+                bail!(CommandError::Cancelled);
+            }
+        }
+
+        let manifests = run_parallel(&sites, self.jobs as usize, |site| -> Result<Site> {
+            let mut site = site.clone();
+            if self.reset {
+                site.config.reset_overrides();
+            }
+            if self.update_manifest {
+                info!("Updating web app manifest {site}");
+                self.update_manifest(&mut site, &dirs, &client)?;
+            }
+            Ok(site)
+        });
+
+        if self.dry_run {
+            let mut failed = 0;
+
+            for (original, updated) in sites.iter().zip(manifests) {
+                match updated {
+                    Ok(updated) => print_changes(original, &updated),
+                    Err(error) => {
+                        error!("{error:?}");
+                        failed += 1;
+                    }
+                }
+            }
+
+            if failed > 0 {
+                bail!("Failed to check {failed} web apps");
+            }
+
+            return Ok(());
+        }
+
+        let mut summary = vec![];
+
+        for (original, updated) in sites.iter().zip(manifests) {
+            let old_name = original.name();
+
+            // Wrapped into a closure to emulate currently unstable `try` blocks
+            let update = || -> Result<Site> {
+                let mut site = updated?;
+
+                if self.system_integration {
+                    let hash = integrations::update(&IntegrationInstallArgs {
+                        site: &site,
+                        dirs: &dirs,
+                        client: Some(&client),
+                        update_manifest: self.update_manifest,
+                        update_icons: self.update_icons,
+                        old_name: Some(&old_name),
+                    })
+                    .context("Failed to update system integration")?;
+
+                    if hash.is_some() {
+                        site.config.integration_hash = hash;
+                    }
+                }
+
+                site.config.last_updated = Some(SystemTime::now());
+                Ok(site)
+            };
+
+            // Report the error and continue with the remaining web apps
+            match update() {
+                Ok(site) => {
+                    summary.push((old_name, site.ulid, None));
+                    storage.sites.insert(site.ulid, site);
+                }
+                Err(error) => {
+                    error!("{error:?}");
+                    summary.push((old_name, original.ulid, Some(error)));
+                }
+            }
+        }
+
+        storage.write(&dirs)?;
+
+        println!("Summary:");
+        for (name, ulid, error) in &summary {
+            match error {
+                None => println!("- {name}: Updated ({ulid})"),
+                Some(error) => println!("- {name}: Failed: {error} ({ulid})"),
+            }
+        }
+
+        let failed = summary.iter().filter(|(_, _, error)| error.is_some()).count();
+
+        if self.notify {
+            let body = format!("{} succeeded, {} failed", summary.len() - failed, failed);
+            if let Err(error) = send_notification("Web apps updated", &body) {
+                warn!("{error:?}");
+            }
+        }
+
+        if failed > 0 {
+            bail!("Failed to update {failed} web apps");
+        }
+
+        info!("All web apps updated!");
+        Ok(())
+    }
+}
+
+/// Selects web apps for a bulk operation using the name or URL pattern and profile.
+///
+/// If any filter is used and no web apps match it, an error is returned.
+/// See [`print_selected`] for printing the selection before acting on it.
+pub(in crate::console) fn filter_sites<'a>(
+    storage: &'a Storage,
+    pattern: Option<&str>,
+    profile: Option<Ulid>,
+) -> Result<Vec<&'a Site>> {
+    if pattern.is_none() && profile.is_none() {
+        return Ok(storage.sites.values().collect());
+    }
+
+    if let Some(profile) = profile
+        && !storage.profiles.contains_key(&profile)
+    {
+        bail!(CommandError::ProfileNotFound);
+    }
+
+    let pattern = pattern.map(str::to_lowercase);
+    let sites: Vec<&Site> = storage
+        .sites
+        .values()
+        .filter(|site| profile.is_none_or(|profile| site.profile == profile))
+        .filter(|site| {
+            pattern.as_ref().is_none_or(|pattern| {
+                site.name().to_lowercase().contains(pattern)
+                    || site.config.document_url.as_str().to_lowercase().contains(pattern)
+            })
+        })
+        .collect();
+
+    if sites.is_empty() {
+        bail!("No web apps match the filters");
+    }
+
+    Ok(sites)
+}
+
+/// Prints web apps selected for a bulk operation.
+///
+/// Used before the operation starts, so it is clear which web apps will be affected.
+pub(in crate::console) fn print_selected<'a>(sites: impl IntoIterator<Item = &'a Site>) {
+    let mut sites = sites.into_iter().peekable();
+    if sites.peek().is_none() {
+        info!("No web apps selected");
+        return;
+    }
+
+    println!("Selected web apps:");
+    for site in sites {
+        println!("- {}: {} ({})", site.name(), site.config.document_url, site.ulid);
+    }
+    println!();
+}
+
+/// Prints changes between the stored and updated web app.
+///
+/// Added fields are marked with `+`, removed fields with `-`
+/// and changed fields with `~`.
+fn print_changes(original: &Site, updated: &Site) {
+    fn optional(value: String) -> Option<String> {
+        if value.is_empty() { None } else { Some(value) }
+    }
+
+    fn scope(site: &Site) -> Option<String> {
+        let url: Option<Url> = site.manifest.scope.clone().try_into().ok();
+        url.map(String::from)
+    }
+
+    fn icons(site: &Site) -> BTreeSet<String> {
+        site.icons()
+            .into_iter()
+            .filter_map(|icon| icon.src.try_into().ok())
+            .map(|url: Url| url.into())
+            .collect()
+    }
+
+    let mut changes = vec![];
+
+    let fields = [
+        ("name", optional(original.name()), optional(updated.name())),
+        ("description", optional(original.description()), optional(updated.description())),
+        ("scope", scope(original), scope(updated)),
+    ];
+
+    for (field, old, new) in fields {
+        match (old, new) {
+            (None, Some(new)) => changes.push(format!("+ {field}: {new}")),
+            (Some(old), None) => changes.push(format!("- {field}: {old}")),
+            (Some(old), Some(new)) if old != new => {
+                changes.push(format!("~ {field}: {old} -> {new}"))
+            }
+            _ => {}
+        }
+    }
+
+    let (old, new) = (icons(original), icons(updated));
+    changes.extend(new.difference(&old).map(|icon| format!("+ icon: {icon}")));
+    changes.extend(old.difference(&new).map(|icon| format!("- icon: {icon}")));
+
+    println!("{original}:");
+    if changes.is_empty() {
+        println!("  No changes");
+    }
+    for change in changes {
+        println!("  {change}");
+    }
+    println!();
+}
+
 impl Run for SiteUpdateCommand {
     fn run(&self) -> Result<()> {
+        if self.all {
+            return self.update_all();
+        }
+
+        if self.icon_only {
+            return self.update_icons_only();
+        }
+
         let dirs = ProjectDirs::new()?;
         let mut storage = Storage::load(&dirs)?;
 
-        let site = storage.sites.get_mut(&self.id).context("Web app does not exist")?;
+        let id = resolve_site(&storage, self.id, self.document_url.as_ref())?;
+        let site = storage.sites.get_mut(&id).context(CommandError::SiteNotFound)?;
+        let original = site.clone();
         let old_name = site.name();
 
+        // Values are validated when stored, so invalid values are reported before the checks
+        store_validated_value!(site.config.locale, self.locale, validate_locale);
+        if let Some(locale) = &mut site.config.locale {
+            // Store the canonical form of the language tag, as used by web content
+            *locale = locale.replace('_', "-");
+        }
+        store_validated_value!(site.config.proxy, self.proxy, validate_proxy);
+
+        if let Some(Some(_)) = &self.proxy {
+            // Proxy prefs apply to the whole profile, so other web apps would use it as well
+            let profile =
+                storage.profiles.get(&site.profile).context(CommandError::ProfileNotFound)?;
+            if profile.sites.len() > 1 {
+                bail!("Web app with a proxy must be the only web app in its profile");
+            }
+        }
+
+        if let Some(Some(_)) = &self.locale {
+            // Preferred languages apply to the whole profile, so other web apps would use them too
+            let profile =
+                storage.profiles.get(&site.profile).context(CommandError::ProfileNotFound)?;
+            if profile.sites.len() > 1 {
+                bail!("Web app with a locale must be the only web app in its profile");
+            }
+        }
+
         info!("Updating the web app");
+        if self.reset {
+            info!("Discarding custom properties");
+            site.config.reset_overrides();
+        }
+
         store_value!(site.config.name, self.name);
         store_value!(site.config.description, self.description);
+
+        if self.display_name.is_some() {
+            site.config.name_override.clone_from(&self.display_name);
+        } else if self.clear_display_name {
+            site.config.name_override = None;
+        }
+
         store_value!(site.config.start_url, self.start_url);
         store_value!(site.config.icon_url, self.icon_url);
+        store_value!(site.config.icon_override, self.icon.as_ref().map(icon_path).transpose()?);
         store_value_vec!(site.config.categories, self.categories);
+
+        if !self.add_category.is_empty() || !self.remove_category.is_empty() {
+            let categories =
+                site.config.categories.get_or_insert_with(|| site.manifest.categories.clone());
+            for category in &self.add_category {
+                append_value!(categories, category.to_owned());
+            }
+            for category in &self.remove_category {
+                remove_value!(categories, category.to_owned());
+            }
+        }
+
         store_value_vec!(site.config.keywords, self.keywords);
         store_value!(site.config.enabled_url_handlers, self.enabled_url_handlers);
         store_value!(site.config.enabled_protocol_handlers, self.enabled_protocol_handlers);
         store_value!(site.config.launch_on_login, self.launch_on_login);
         store_value!(site.config.launch_on_browser, self.launch_on_browser);
+        store_value!(site.config.single_instance, self.single_instance);
+
+        if let Some(size) = &self.size {
+            let size = size.as_deref().map(parse_size).transpose()?;
+            site.config.window.width = size.map(|(width, _)| width);
+            site.config.window.height = size.map(|(_, height)| height);
+        }
+
+        if let Some(position) = &self.position {
+            let position = position.as_deref().map(parse_position).transpose()?;
+            site.config.window.x = position.map(|(x, _)| x);
+            site.config.window.y = position.map(|(_, y)| y);
+        }
+
+        if let Some(maximized) = self.maximized {
+            site.config.window.maximized = maximized;
+        }
+        store_value!(site.config.notes, self.notes);
+
+        if let Some(path) = &self.notes_file {
+            let notes = read_to_string(path).context("Failed to read notes file")?;
+            let notes = notes.trim_end();
+            site.config.notes = if notes.is_empty() { None } else { Some(notes.into()) };
+        }
 
-        let client = construct_certificates_and_client(
-            self.client.user_agent.as_deref(),
-            &self.client.tls_root_certificates_der,
-            &self.client.tls_root_certificates_pem,
-            self.client.tls_danger_accept_invalid_certs,
-            self.client.tls_danger_accept_invalid_hostnames,
-        )?;
+        let client = construct_certificates_and_client(&ClientArgs::from(&self.client))?;
 
         if self.update_manifest {
-            site.update(&client).context("Failed to update web app manifest")?;
+            self.update_manifest(site, &dirs, &client)?;
+
+            let overridden = site.config.overridden_fields();
+            if !overridden.is_empty() {
+                info!("Keeping custom properties: {}", overridden.join(", "));
+            }
+        }
+
+        if self.dry_run {
+            print_changes(&original, site);
+            return Ok(());
         }
 
         if self.system_integration {
             info!("Updating system integration");
-            integrations::install(&IntegrationInstallArgs {
+            let hash = integrations::update(&IntegrationInstallArgs {
                 site,
                 dirs: &dirs,
                 client: Some(&client),
@@ -332,11 +1112,406 @@ impl Run for SiteUpdateCommand {
                 old_name: Some(&old_name),
             })
             .context("Failed to update system integration")?;
+
+            if hash.is_some() {
+                site.config.integration_hash = hash;
+            }
         }
 
+        site.config.last_updated = Some(SystemTime::now());
         storage.write(&dirs)?;
 
         info!("Web app updated!");
         Ok(())
     }
 }
+
+/// Validates the custom icon file and returns its absolute path.
+///
+/// Icons can be in any raster format supported by the `image` crate or in SVG.
+fn icon_path(path: &Option<PathBuf>) -> Result<Option<PathBuf>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+
+    let path = path.canonicalize().context("Failed to find the icon file")?;
+    if path.extension().is_none_or(|extension| extension != "svg") {
+        image::image_dimensions(&path).context("Failed to read the icon file")?;
+    }
+
+    Ok(Some(path))
+}
+
+impl Run for SiteVerifyCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let integrated: BTreeSet<Ulid> = integrations::list(&dirs)
+            .context("Failed to list system integration")?
+            .into_iter()
+            .collect();
+        let stored: BTreeSet<Ulid> = storage.sites.keys().copied().collect();
+
+        let orphaned: Vec<&Ulid> = stored.difference(&integrated).collect();
+        let stale: Vec<&Ulid> = integrated.difference(&stored).collect();
+
+        if orphaned.is_empty() && stale.is_empty() {
+            info!("System integration is consistent with storage");
+            return Ok(());
+        }
+
+        if !orphaned.is_empty() {
+            println!("Orphaned storage entries:");
+            for ulid in &orphaned {
+                let site = &storage.sites[*ulid];
+                println!("- {}: {} ({})", site.name(), site.config.document_url, ulid);
+            }
+            println!();
+        }
+
+        if !stale.is_empty() {
+            println!("Stale integrations:");
+            for ulid in &stale {
+                println!("- {ulid}");
+            }
+            println!();
+        }
+
+        if !self.fix {
+            bail!("Found {} inconsistencies", orphaned.len() + stale.len());
+        }
+
+        let client = construct_certificates_and_client(&ClientArgs::from(&self.client))?;
+
+        for ulid in orphaned {
+            info!("Installing system integration for web app {ulid}");
+            integrations::install(&IntegrationInstallArgs {
+                site: &storage.sites[ulid],
+                dirs: &dirs,
+                client: Some(&client),
+                update_manifest: false,
+                update_icons: true,
+                old_name: None,
+            })
+            .context("Failed to install system integration")?;
+        }
+
+        for ulid in stale {
+            info!("Removing stale system integration {ulid}");
+            integrations::purge(&dirs, ulid).context("Failed to remove system integration")?;
+        }
+
+        info!("System integration fixed!");
+        Ok(())
+    }
+}
+
+impl Run for SiteRepairCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let sites: Vec<&Site> = match self.id {
+            Some(id) => vec![storage.sites.get(&id).context(CommandError::SiteNotFound)?],
+            None => {
+                let sites = filter_sites(&storage, self.pattern.as_deref(), self.profile)?;
+                print_selected(sites.iter().copied());
+                sites
+            }
+        };
+
+        let client = construct_certificates_and_client(&ClientArgs::from(&self.client))?;
+
+        let mut failed = 0;
+
+        for site in sites {
+            info!("Repairing web app {site}");
+
+            // Uninstalling also removes cached icons, so they always need to be downloaded again
+            // Wrapped into a closure to emulate currently unstable `try` blocks
+            let repair = || -> Result<()> {
+                integrations::uninstall(&IntegrationUninstallArgs { site, dirs: &dirs })
+                    .context("Failed to uninstall system integration")?;
+
+                integrations::install(&IntegrationInstallArgs {
+                    site,
+                    dirs: &dirs,
+                    client: Some(&client),
+                    update_manifest: false,
+                    update_icons: true,
+                    old_name: None,
+                })
+                .context("Failed to install system integration")?;
+
+                // Shortcuts have been re-created, so they also need to be pinned again
+                if site.config.pinned {
+                    integrations::pin(site, true).context("Failed to pin web app")?;
+                }
+
+                Ok(())
+            };
+
+            // Report the error and continue with the remaining web apps
+            match repair() {
+                Ok(_) => info!("Web app repaired: {site}"),
+                Err(error) => {
+                    error!("{error:?}");
+                    failed += 1;
+                }
+            }
+        }
+
+        if failed > 0 {
+            bail!("Failed to repair {failed} web apps");
+        }
+
+        info!("System integration repaired!");
+        Ok(())
+    }
+}
+
+impl Run for SitePinCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let mut storage = Storage::load(&dirs)?;
+
+        let id = resolve_site(&storage, self.id, self.document_url.as_ref())?;
+        let site = storage.sites.get_mut(&id).context(CommandError::SiteNotFound)?;
+
+        info!("Pinning the web app");
+        integrations::pin(site, true).context("Failed to pin web app")?;
+
+        site.config.pinned = true;
+        storage.write(&dirs)?;
+
+        info!("Web app pinned!");
+        Ok(())
+    }
+}
+
+impl Run for SiteUnpinCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let mut storage = Storage::load(&dirs)?;
+
+        let id = resolve_site(&storage, self.id, self.document_url.as_ref())?;
+        let site = storage.sites.get_mut(&id).context(CommandError::SiteNotFound)?;
+
+        info!("Unpinning the web app");
+        integrations::pin(site, false).context("Failed to unpin web app")?;
+
+        site.config.pinned = false;
+        storage.write(&dirs)?;
+
+        info!("Web app unpinned!");
+        Ok(())
+    }
+}
+
+impl Run for SiteListCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        // Named groups are sorted alphabetically, and ungrouped web apps are listed last
+        let stale = Duration::from_secs(self.stale_days * 24 * 60 * 60);
+        let outdated = |site: &Site| match site.config.last_manifest_fetched {
+            Some(fetched) => fetched.elapsed().is_ok_and(|elapsed| elapsed > stale),
+            None => true,
+        };
+
+        let since = self.since.as_deref().map(parse_date).transpose()?;
+        let recent = |site: &Site| since.is_none_or(|since| site.ulid.datetime() >= since);
+
+        let mut groups: BTreeMap<Option<&str>, Vec<&Site>> = BTreeMap::new();
+        for site in storage.sites.values() {
+            let group = site.config.group.as_deref();
+            if (self.group.is_none() || self.group.as_deref() == group)
+                && (!self.outdated || outdated(site))
+                && recent(site)
+            {
+                groups.entry(group).or_default().push(site);
+            }
+        }
+
+        if self.count {
+            println!("{}", groups.values().map(Vec::len).sum::<usize>());
+            return Ok(());
+        }
+
+        if groups.is_empty() {
+            info!("No web apps found");
+            return Ok(());
+        }
+
+        let ungrouped = groups.remove(&None);
+        let groups = groups.into_iter().map(|(group, sites)| (group.unwrap(), sites));
+
+        for (group, mut sites) in groups.chain(ungrouped.map(|sites| ("* Ungrouped *", sites))) {
+            // Web apps are already in ULID order, and sorting statistics
+            // in descending order also puts web apps that were never launched last
+            match self.sort.as_str() {
+                "name" => sites.sort_by_cached_key(|site| site.name().to_lowercase()),
+                "last-launched" => sites.sort_by_key(|site| Reverse(site.config.last_launched)),
+                "launch-count" => sites.sort_by_key(|site| Reverse(site.config.launch_count)),
+                _ => {}
+            }
+
+            if self.reverse {
+                sites.reverse();
+            }
+
+            println!("{:=^60}", format!(" {} ", sanitize_string(group)));
+            for site in sites {
+                println!("- {}: {} ({})", site.name(), site.config.document_url, site.ulid);
+
+                if self.verbose {
+                    let last = site.config.last_launched.map_or("never".into(), format_elapsed);
+                    println!("  Last launched: {last}");
+                    println!("  Launch count: {}", site.config.launch_count);
+
+                    let fetched =
+                        site.config.last_manifest_fetched.map_or("never".into(), format_elapsed);
+                    println!("  Manifest fetched: {fetched}");
+
+                    if let Some(icon) = &site.config.icon_override {
+                        println!("  Icon override: {}", icon.display());
+                    }
+                }
+            }
+            println!();
+        }
+
+        Ok(())
+    }
+}
+
+impl Run for SiteShowCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let site = storage.sites.get(&self.id).context(CommandError::SiteNotFound)?;
+        let created = site.ulid.datetime();
+        let directory = dirs.userdata.join("profiles").join(site.profile.to_string());
+
+        if self.json {
+            let mut output = serde_json::to_value(site)?;
+            output["created"] = serde_json::to_value(created)?;
+            output["profile_directory"] = serde_json::to_value(&directory)?;
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+
+        let time = |time: Option<SystemTime>| time.map_or("never".into(), format_elapsed);
+        let list = |values: &[String]| match values {
+            [] => "none".into(),
+            values => sanitize_string(&values.join(", ")),
+        };
+
+        let scope: Option<Url> = site.manifest.scope.clone().try_into().ok();
+        let display = serde_json::to_value(&site.manifest.display)?;
+        let icons: Vec<String> = site
+            .icons()
+            .into_iter()
+            .filter_map(|icon| icon.src.try_into().ok())
+            .map(|url: Url| url.into())
+            .collect();
+
+        println!("Name: {}", sanitize_string(&site.name()));
+        println!("Description: {}", sanitize_string(&site.description()));
+        println!("ID: {}", site.ulid);
+        println!("Created: {}", format_elapsed(created));
+        println!();
+
+        println!("Profile: {}", site.profile);
+        println!("Profile directory: {}", directory.display());
+        println!("Group: {}", sanitize_string(site.config.group.as_deref().unwrap_or("none")));
+        println!();
+
+        println!("Document URL: {}", site.config.document_url);
+        println!("Manifest URL: {}", site.config.manifest_url);
+        println!("Start URL: {}", site.url());
+        println!("Scope: {}", scope.map_or("none".into(), String::from));
+        println!("Display mode: {}", display.as_str().unwrap_or("unknown"));
+        println!("Icons: {}", list(&icons));
+        println!("Categories: {}", list(&site.categories()));
+        println!("Keywords: {}", list(&site.keywords()));
+        println!();
+
+        let overridden: Vec<String> =
+            site.config.overridden_fields().into_iter().map(String::from).collect();
+        println!("Custom properties: {}", list(&overridden));
+        println!("Locale: {}", site.config.locale.as_deref().unwrap_or("system"));
+        println!("Proxy: {}", site.config.proxy.as_ref().map_or("none", Url::as_str));
+
+        let window = &site.config.window;
+        let size = match (window.width, window.height) {
+            (Some(width), Some(height)) => format!("{width}x{height}"),
+            _ => "default".into(),
+        };
+        let position = match (window.x, window.y) {
+            (Some(x), Some(y)) => format!("{x},{y}"),
+            _ => "default".into(),
+        };
+        println!("Window size: {size}");
+        println!("Window position: {position}");
+        println!("Window maximized: {}", window.maximized);
+
+        println!("URL handlers: {}", list(&site.config.enabled_url_handlers));
+        println!("Protocol handlers: {}", list(&site.config.enabled_protocol_handlers));
+        println!("Launch on login: {}", site.config.launch_on_login);
+        println!("Launch on browser: {}", site.config.launch_on_browser);
+        println!("Single instance: {}", site.config.single_instance);
+        println!("Pinned: {}", site.config.pinned);
+        println!();
+
+        println!("Last launched: {}", time(site.config.last_launched));
+        println!("Launch count: {}", site.config.launch_count);
+        println!("Manifest fetched: {}", time(site.config.last_manifest_fetched));
+        println!("Last updated: {}", time(site.config.last_updated));
+
+        if let Some(notes) = &site.config.notes {
+            println!();
+            println!("Notes:");
+            for line in notes.lines() {
+                println!("  {}", sanitize_string(line));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Run for SiteGroupCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let mut storage = Storage::load(&dirs)?;
+
+        if self.group.trim().is_empty() {
+            bail!("Group name cannot be empty, use `site ungroup` to remove the group");
+        }
+
+        let site = storage.sites.get_mut(&self.id).context(CommandError::SiteNotFound)?;
+        site.config.group = Some(self.group.trim().into());
+        storage.write(&dirs)?;
+
+        info!("Web app added to the group!");
+        Ok(())
+    }
+}
+
+impl Run for SiteUngroupCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let mut storage = Storage::load(&dirs)?;
+
+        let site = storage.sites.get_mut(&self.id).context(CommandError::SiteNotFound)?;
+        site.config.group = None;
+        storage.write(&dirs)?;
+
+        info!("Web app removed from the group!");
+        Ok(())
+    }
+}
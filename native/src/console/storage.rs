@@ -0,0 +1,332 @@
+use std::fs::read;
+
+use anyhow::{Context, Result, bail};
+use log::{error, info, warn};
+use ulid::Ulid;
+
+use crate::components::bundle;
+use crate::components::runtime::Runtime;
+use crate::console::app::{
+    StorageConfigPathCommand,
+    StorageExportCommand,
+    StorageImportCommand,
+    StoragePathCommand,
+    StorageShowCommand,
+    StorageVerifyCommand,
+};
+use crate::console::defaults::Defaults;
+use crate::console::site::{filter_sites, print_selected};
+use crate::console::{Run, confirm};
+use crate::directories::ProjectDirs;
+use crate::error::CommandError;
+use crate::integrations;
+use crate::integrations::{IntegrationInstallArgs, IntegrationUninstallArgs};
+use crate::storage::Storage;
+use crate::utils::{ClientArgs, construct_certificates_and_client};
+
+impl Run for StorageVerifyCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let mut storage = Storage::load(&dirs)?;
+
+        // Profiles that reference web apps which do not exist
+        let mut dangling: Vec<(Ulid, Ulid)> = vec![];
+
+        // Web apps that are not listed in their profile
+        let mut unlisted: Vec<(Ulid, Ulid)> = vec![];
+
+        // Web apps whose profile does not exist
+        let mut orphaned: Vec<Ulid> = vec![];
+
+        // Web apps with a local icon file that does not exist
+        let mut icons: Vec<Ulid> = vec![];
+
+        // Web apps with an icon override file that does not exist
+        let mut overrides: Vec<Ulid> = vec![];
+
+        for (ulid, profile) in &storage.profiles {
+            for site in &profile.sites {
+                if !storage.sites.contains_key(site) {
+                    dangling.push((*ulid, *site));
+                }
+            }
+
+            // Profile directories are only created when a web app is launched for the first time
+            let directory = dirs.userdata.join("profiles").join(ulid.to_string());
+            if !profile.sites.is_empty() && !directory.is_dir() {
+                warn!("Profile {ulid} does not have a directory yet");
+            }
+        }
+
+        for (ulid, site) in &storage.sites {
+            match storage.profiles.get(&site.profile) {
+                Some(profile) if !profile.sites.contains(ulid) => {
+                    unlisted.push((site.profile, *ulid))
+                }
+                Some(_) => {}
+                None => orphaned.push(*ulid),
+            }
+
+            if let Some(url) = &site.config.icon_url
+                && url.scheme() == "file"
+                && url.to_file_path().is_ok_and(|path| !path.exists())
+            {
+                icons.push(*ulid);
+            }
+
+            if let Some(path) = &site.config.icon_override
+                && !path.exists()
+            {
+                overrides.push(*ulid);
+            }
+        }
+
+        let total =
+            dangling.len() + unlisted.len() + orphaned.len() + icons.len() + overrides.len();
+        if total == 0 {
+            info!("Storage is consistent");
+            return Ok(());
+        }
+
+        if !dangling.is_empty() {
+            println!("Profiles with missing web apps:");
+            for (profile, site) in &dangling {
+                println!("- {profile}: {site}");
+            }
+            println!();
+        }
+
+        if !unlisted.is_empty() {
+            println!("Web apps missing from their profile:");
+            for (profile, site) in &unlisted {
+                println!("- {site}: {profile}");
+            }
+            println!();
+        }
+
+        if !orphaned.is_empty() {
+            println!("Web apps with missing profile:");
+            for ulid in &orphaned {
+                let site = &storage.sites[ulid];
+                println!("- {}: {} ({})", site.name(), site.profile, ulid);
+            }
+            println!();
+        }
+
+        if !icons.is_empty() {
+            println!("Web apps with missing icon file:");
+            for ulid in &icons {
+                let site = &storage.sites[ulid];
+                let icon = site.config.icon_url.as_ref().unwrap();
+                println!("- {}: {} ({})", site.name(), icon, ulid);
+            }
+            println!();
+        }
+
+        if !overrides.is_empty() {
+            println!("Web apps with missing icon override file:");
+            for ulid in &overrides {
+                let site = &storage.sites[ulid];
+                let icon = site.config.icon_override.as_ref().unwrap();
+                println!("- {}: {} ({})", site.name(), icon.display(), ulid);
+            }
+            println!();
+        }
+
+        if !self.fix {
+            bail!("Found {total} inconsistencies");
+        }
+
+        for (profile, site) in dangling {
+            info!("Removing missing web app {site} from profile {profile}");
+            storage.profiles.get_mut(&profile).unwrap().sites.retain(|id| *id != site);
+        }
+
+        for (profile, site) in unlisted {
+            info!("Adding web app {site} to profile {profile}");
+            storage.profiles.get_mut(&profile).unwrap().sites.push(site);
+        }
+
+        for ulid in icons {
+            info!("Resetting missing icon of web app {ulid}");
+            storage.sites.get_mut(&ulid).unwrap().config.icon_url = None;
+        }
+
+        for ulid in overrides {
+            info!("Resetting missing icon override of web app {ulid}");
+            storage.sites.get_mut(&ulid).unwrap().config.icon_override = None;
+        }
+
+        if self.remove_orphaned_sites {
+            for ulid in orphaned {
+                info!("Removing orphaned web app {ulid}");
+                let site = storage.sites.remove(&ulid).unwrap();
+
+                if let Err(error) =
+                    integrations::uninstall(&IntegrationUninstallArgs { site: &site, dirs: &dirs })
+                {
+                    warn!("Failed to uninstall system integration: {error:?}");
+                }
+            }
+        } else if !orphaned.is_empty() {
+            warn!("Orphaned web apps were kept, use --remove-orphaned-sites to remove them");
+        }
+
+        storage.write(&dirs)?;
+
+        info!("Storage fixed!");
+        Ok(())
+    }
+}
+
+impl Run for StorageExportCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let storage = Storage::load(&dirs)?;
+
+        let selection = match (&self.pattern, self.profile) {
+            (None, None) => None,
+            (pattern, profile) => {
+                let selected = filter_sites(&storage, pattern.as_deref(), profile)?;
+                print_selected(selected.iter().copied());
+                let selected: Vec<Ulid> = selected.into_iter().map(|site| site.ulid).collect();
+
+                if !self.quiet {
+                    warn!("Only the selected web apps and their profiles will be exported");
+                    warn!("Profile data is shared, so it may also contain data of other web apps");
+
+                    if !confirm()? {
+                        bail!(CommandError::Cancelled);
+                    }
+                }
+
+                // The default profile is always kept, so the imported storage is valid
+                let mut selection = storage.clone();
+                selection.sites.retain(|ulid, _| selected.contains(ulid));
+                selection.profiles.retain(|ulid, profile| {
+                    profile.sites.retain(|site| selected.contains(site));
+                    *ulid == Ulid::nil() || !profile.sites.is_empty()
+                });
+                Some(selection)
+            }
+        };
+
+        info!("Exporting the installation");
+        bundle::export(&dirs, &self.path, selection.as_ref())
+            .context("Failed to export the installation")?;
+
+        info!("Installation exported: {}", self.path.display());
+        Ok(())
+    }
+}
+
+impl Run for StorageImportCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        let previous = Storage::load(&dirs)?;
+
+        if !previous.sites.is_empty() && !self.force {
+            bail!("Installation with existing web apps cannot be overwritten without --force");
+        }
+
+        info!("Importing the installation");
+        let metadata =
+            bundle::import(&dirs, &self.path).context("Failed to import the installation")?;
+        let storage = Storage::load(&dirs)?;
+
+        // Remove system integration of overwritten web apps that are not in the bundle
+        for site in previous.sites.values() {
+            if !storage.sites.contains_key(&site.ulid)
+                && let Err(error) =
+                    integrations::uninstall(&IntegrationUninstallArgs { site, dirs: &dirs })
+            {
+                warn!("Failed to uninstall system integration: {error:?}");
+            }
+        }
+
+        let client = construct_certificates_and_client(&ClientArgs::from(&self.client))?;
+
+        let mut failed = 0;
+
+        for site in storage.sites.values() {
+            info!("Installing system integration for web app {site}");
+
+            let result = integrations::install(&IntegrationInstallArgs {
+                site,
+                dirs: &dirs,
+                client: Some(&client),
+                update_manifest: false,
+                update_icons: true,
+                old_name: None,
+            });
+
+            if let Err(error) = result.context("Failed to install system integration") {
+                error!("{error:?}");
+                failed += 1;
+            }
+        }
+
+        let runtime = Runtime::new(&dirs)?;
+        match (&runtime.version, &metadata.runtime) {
+            (None, _) => {
+                warn!("Runtime is not installed");
+                warn!("Install it with `firefoxpwa runtime install` before launching web apps");
+            }
+            (Some(current), Some(exported)) if current != exported => {
+                warn!("Bundle was created with runtime {exported}, but {current} is installed");
+            }
+            _ => {}
+        }
+
+        if failed > 0 {
+            bail!("Failed to install system integration of {failed} web apps");
+        }
+
+        info!("Installation imported!");
+        Ok(())
+    }
+}
+
+impl Run for StorageShowCommand {
+    fn run(&self) -> Result<()> {
+        const PREVIEW_LENGTH: usize = 1000;
+
+        let dirs = ProjectDirs::new()?;
+        let path = Storage::path(&dirs);
+
+        if !path.exists() {
+            bail!("Storage file does not exist: {}", path.display());
+        }
+
+        let data = read(&path).context("Failed to read storage")?;
+        match serde_json::from_slice::<serde_json::Value>(&data) {
+            Ok(value) => {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+                Ok(())
+            }
+            Err(error) => {
+                // Print the beginning of the raw file to help with debugging
+                let preview = &data[..data.len().min(PREVIEW_LENGTH)];
+                println!("{}", String::from_utf8_lossy(preview));
+                Err(error).context("Failed to parse storage")
+            }
+        }
+    }
+}
+
+impl Run for StoragePathCommand {
+    fn run(&self) -> Result<()> {
+        let dirs = ProjectDirs::new()?;
+        println!("{}", Storage::path(&dirs).display());
+        Ok(())
+    }
+}
+
+impl Run for StorageConfigPathCommand {
+    fn run(&self) -> Result<()> {
+        // Prefer the config file that was actually applied, which may come from `--config`
+        let path = Defaults::applied().file.or_else(Defaults::path);
+        println!("{}", path.context("Failed to determine the config file path")?.display());
+        Ok(())
+    }
+}
@@ -100,20 +100,25 @@ pub struct ProjectDirs {
 }
 
 impl ProjectDirs {
-    pub fn new() -> Result<Self> {
-        // We need base directories to get the user directory (for expanding tilde)
-        // and the app data directory (for a default user data location)
-        let base = BaseDirs::new().context("Failed to determine base system directories")?;
-
+    /// Whether run-time environment variables are ignored when determining directories.
+    pub fn static_only() -> bool {
         // Provide a way to prevent using run-time environment variables to change directories
         // This should only be used in specific circumstances (like some packaging requirements)
         // To enable this, set a `FFPWA_STATIC_DIRS` build-time environment variable to `1`
         // Note that the user might still be able to change some directories through other means
         // To prevent this, explicitly set all `FFPWA_` build-time directory variables
-        let static_only_dirs = match option_env!("FFPWA_STATIC_DIRS") {
+        match option_env!("FFPWA_STATIC_DIRS") {
             Some(var) => var == "1",
             None => false,
-        };
+        }
+    }
+
+    pub fn new() -> Result<Self> {
+        // We need base directories to get the user directory (for expanding tilde)
+        // and the app data directory (for a default user data location)
+        let base = BaseDirs::new().context("Failed to determine base system directories")?;
+
+        let static_only_dirs = Self::static_only();
 
         cfg_if! {
             // On Windows, executables and system data are in the same directory
@@ -212,4 +217,20 @@ impl ProjectDirs {
 
         Ok(Self { executables, sysdata, userdata })
     }
+
+    /// Creates all directories inside the base directory.
+    ///
+    /// Build- and run-time environment variables are ignored, so this can be used
+    /// by tests and other tools that must not touch the real installation or user data.
+    pub fn new_with_base<P: AsRef<Path>>(base: P) -> Result<Self> {
+        let base = base.as_ref();
+
+        let executables = base.join("executables");
+        let sysdata = base.join("sysdata");
+        let userdata = base.join("userdata");
+
+        create_dir_all(&userdata).context("Failed to create user data directory")?;
+
+        Ok(Self { executables, sysdata, userdata })
+    }
 }
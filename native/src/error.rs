@@ -0,0 +1,37 @@
+use std::fmt;
+
+/// A common reason why a command failed.
+///
+/// Used as an error context, so it can be found in the error chain
+/// and mapped to a distinct exit code of the console program.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum CommandError {
+    /// The requested profile does not exist.
+    ProfileNotFound,
+
+    /// The requested web app does not exist.
+    SiteNotFound,
+
+    /// The runtime is not installed.
+    RuntimeNotInstalled,
+
+    /// 7-Zip is not installed.
+    SevenZipNotInstalled,
+
+    /// The user or another process cancelled the operation.
+    Cancelled,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ProfileNotFound => write!(f, "Profile does not exist"),
+            Self::SiteNotFound => write!(f, "Web app does not exist"),
+            Self::RuntimeNotInstalled => write!(f, "Runtime not installed"),
+            Self::SevenZipNotInstalled => write!(f, "7-Zip is currently not installed"),
+            Self::Cancelled => write!(f, "Operation has been cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
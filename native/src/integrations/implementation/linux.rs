@@ -10,11 +10,13 @@ use glob::glob;
 use image::GenericImageView;
 use log::{debug, error, warn};
 use reqwest::blocking::Client;
+use ulid::Ulid;
 use url::Url;
 use web_app_manifest::resources::IconResource;
 use web_app_manifest::types::{ImagePurpose, ImageSize};
 
 use crate::components::site::Site;
+use crate::directories::ProjectDirs;
 use crate::integrations::categories::XDG_CATEGORIES;
 use crate::integrations::utils::{download_icon, normalize_category_name, store_icon};
 use crate::integrations::{IntegrationInstallArgs, IntegrationUninstallArgs};
@@ -353,16 +355,57 @@ pub fn install(args: &IntegrationInstallArgs) -> Result<()> {
 
 #[inline]
 pub fn uninstall(args: &IntegrationUninstallArgs) -> Result<()> {
-    let ids = SiteIds::create_for(args.site);
+    purge(args.dirs, &args.site.ulid)
+}
+
+#[inline]
+pub fn list(_dirs: &ProjectDirs) -> Result<Vec<Ulid>> {
+    let base = directories::BaseDirs::new().context(BASE_DIRECTORIES_ERROR)?;
+    let directory = base.data_dir().display().to_string();
+    let pattern = format!("{directory}/applications/FFPWA-*.desktop");
+
+    let ulids = glob(&pattern)
+        .context("Failed to search application entries")?
+        .filter_map(Result::ok)
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?;
+            Ulid::from_string(stem.strip_prefix("FFPWA-")?).ok()
+        })
+        .collect();
+
+    Ok(ulids)
+}
+
+#[inline]
+pub fn purge(_dirs: &ProjectDirs, ulid: &Ulid) -> Result<()> {
+    let classid = format!("FFPWA-{ulid}");
 
     let base = directories::BaseDirs::new().context(BASE_DIRECTORIES_ERROR)?;
     let data = &base.data_dir().to_owned();
     let config = &base.config_dir().to_owned();
 
-    remove_icons(&ids.classid, data);
-    remove_desktop_entry(&ids.classid, data);
-    remove_startup_entry(&ids.classid, config);
+    remove_icons(&classid, data);
+    remove_desktop_entry(&classid, data);
+    remove_startup_entry(&classid, config);
     update_application_cache(data);
 
     Ok(())
 }
+
+#[inline]
+pub fn dump_icons(site: &Site, target: &Path, client: &Client) -> Result<()> {
+    let ids = SiteIds::create_for(site);
+
+    store_icons(&ids.classid, &ids.name, &site.icons(), target, client)
+        .context("Failed to store web app icons")?;
+
+    for (i, shortcut) in site.manifest.shortcuts.iter().enumerate() {
+        let name = sanitize_string(&shortcut.name);
+        let icon = format!("{}-{}", ids.classid, i);
+
+        store_icons(&icon, &name, &shortcut.icons, target, client)
+            .context("Failed to store shortcut icons")?;
+    }
+
+    Ok(())
+}
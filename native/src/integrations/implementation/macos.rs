@@ -1,8 +1,8 @@
 use std::cmp::Ordering;
 use std::convert::TryInto;
-use std::fs::{File, create_dir_all, remove_dir_all, rename, write};
+use std::fs::{File, create_dir_all, read_dir, read_to_string, remove_dir_all, rename, write};
 use std::io::{BufWriter, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 
 use anyhow::{Context, Result, bail};
@@ -13,11 +13,13 @@ use image::{DynamicImage, Rgba, RgbaImage};
 use log::{debug, error, warn};
 use reqwest::blocking::Client;
 use resvg::{tiny_skia, usvg};
+use ulid::Ulid;
 use url::Url;
 use web_app_manifest::resources::IconResource;
 use web_app_manifest::types::{ImagePurpose, ImageSize, Url as ManifestUrl};
 
 use crate::components::site::Site;
+use crate::directories::ProjectDirs;
 use crate::integrations::categories::MACOS_CATEGORIES;
 use crate::integrations::utils::{
     download_icon,
@@ -26,7 +28,7 @@ use crate::integrations::utils::{
     sanitize_name,
 };
 use crate::integrations::{IntegrationInstallArgs, IntegrationUninstallArgs};
-use crate::utils::sanitize_string;
+use crate::utils::{sanitize_string, temp_dir};
 
 const BASE_DIRECTORIES_ERROR: &str = "Failed to determine base system directories";
 const CONVERT_ICON_URL_ERROR: &str = "Failed to convert icon URL";
@@ -381,6 +383,36 @@ fn verify_app_is_pwa(app_bundle: &Path, app_id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Find all web app bundles in the applications directory.
+///
+/// Bundles are identified by their `PkgInfo` file, which contains
+/// the web app ID, so this also works for bundles whose web apps
+/// were renamed or do not exist anymore.
+fn find_app_bundles() -> Result<Vec<(Ulid, PathBuf)>> {
+    let directory = directories::BaseDirs::new()
+        .context(BASE_DIRECTORIES_ERROR)?
+        .home_dir()
+        .join("Applications");
+
+    if !directory.exists() {
+        return Ok(vec![]);
+    }
+
+    let bundles = read_dir(directory)
+        .context("Failed to read applications directory")?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "app"))
+        .filter_map(|path| {
+            let pkg_info = read_to_string(path.join("Contents/PkgInfo")).ok()?;
+            let ulid = Ulid::from_string(pkg_info.strip_prefix("APPLFFPWA-")?).ok()?;
+            Some((ulid, path))
+        })
+        .collect();
+
+    Ok(bundles)
+}
+
 //////////////////////////////
 // Implementation
 //////////////////////////////
@@ -492,7 +524,7 @@ task.waitUntilExit()
         let mut loader_source_file = tempfile::Builder::new()
             .prefix("firefoxpwa-loader-")
             .suffix(".swift")
-            .tempfile()
+            .tempfile_in(temp_dir())
             .context(CREATE_TEMP_FILE_ERROR)?;
 
         loader_source_file
@@ -567,6 +599,108 @@ pub fn uninstall(args: &IntegrationUninstallArgs) -> Result<()> {
     Ok(())
 }
 
+#[inline]
+pub fn list(_dirs: &ProjectDirs) -> Result<Vec<Ulid>> {
+    let ulids = find_app_bundles()?.into_iter().map(|(ulid, _)| ulid).collect();
+    Ok(ulids)
+}
+
+#[inline]
+pub fn purge(_dirs: &ProjectDirs, ulid: &Ulid) -> Result<()> {
+    for (_, bundle) in find_app_bundles()?.into_iter().filter(|(id, _)| id == ulid) {
+        let _ = remove_dir_all(bundle);
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn pin(site: &Site, pinned: bool) -> Result<()> {
+    const DOCK_DOMAIN: &str = "com.apple.dock";
+    const DOCK_READ_ERROR: &str = "Failed to read Dock preferences";
+    const DOCK_WRITE_ERROR: &str = "Failed to write Dock preferences";
+
+    let ulid = site.ulid.to_string();
+    let bundle = directories::BaseDirs::new()
+        .context(BASE_DIRECTORIES_ERROR)?
+        .home_dir()
+        .join("Applications")
+        .join(format!("{}.app", sanitize_name(&site.name(), &ulid)));
+
+    if !bundle.exists() {
+        bail!("Application bundle does not exist");
+    }
+
+    let url = Url::from_directory_path(&bundle)
+        .map_err(|_| anyhow::anyhow!("Invalid application bundle path"))?
+        .to_string();
+
+    // Preferences are modified through `defaults`, so they are not overwritten by its cache
+    let output = Command::new("defaults")
+        .args(["export", DOCK_DOMAIN, "-"])
+        .output()
+        .context(DOCK_READ_ERROR)?;
+    let mut preferences =
+        plist::Value::from_reader(std::io::Cursor::new(output.stdout)).context(DOCK_READ_ERROR)?;
+
+    let dictionary = preferences.as_dictionary_mut().context(DOCK_READ_ERROR)?;
+    if !dictionary.contains_key("persistent-apps") {
+        dictionary.insert("persistent-apps".into(), plist::Value::Array(vec![]));
+    }
+
+    let apps = dictionary
+        .get_mut("persistent-apps")
+        .and_then(plist::Value::as_array_mut)
+        .context(DOCK_READ_ERROR)?;
+
+    let is_bundle = |app: &plist::Value| {
+        app.as_dictionary()
+            .and_then(|app| app.get("tile-data")?.as_dictionary()?.get("file-data"))
+            .and_then(|data| data.as_dictionary()?.get("_CFURLString")?.as_string())
+            .is_some_and(|string| string == url)
+    };
+
+    apps.retain(|app| !is_bundle(app));
+
+    if pinned {
+        let mut data = plist::Dictionary::new();
+        data.insert("_CFURLString".into(), plist::Value::String(url.clone()));
+        data.insert("_CFURLStringType".into(), plist::Value::Integer(15.into()));
+
+        let mut tile = plist::Dictionary::new();
+        tile.insert("file-data".into(), plist::Value::Dictionary(data));
+
+        let mut app = plist::Dictionary::new();
+        app.insert("tile-data".into(), plist::Value::Dictionary(tile));
+        app.insert("tile-type".into(), plist::Value::String("file-tile".into()));
+
+        apps.push(plist::Value::Dictionary(app));
+    }
+
+    let mut serialized = vec![];
+    preferences.to_writer_xml(&mut serialized).context(DOCK_WRITE_ERROR)?;
+
+    let mut import = Command::new("defaults")
+        .args(["import", DOCK_DOMAIN, "-"])
+        .stdin(Stdio::piped())
+        .spawn()
+        .context(DOCK_WRITE_ERROR)?;
+    import.stdin.take().unwrap().write_all(&serialized).context(DOCK_WRITE_ERROR)?;
+    if !import.wait().context(DOCK_WRITE_ERROR)?.success() {
+        bail!(DOCK_WRITE_ERROR);
+    }
+
+    // Dock needs to be restarted to apply the changes
+    let _ = Command::new("killall").arg("Dock").status();
+
+    Ok(())
+}
+
+#[inline]
+pub fn dump_icons(site: &Site, target: &Path, client: &Client) -> Result<()> {
+    store_icons(target, &site.name(), &site.icons(), client).context(STORE_ICONS_ERROR)
+}
+
 #[inline]
 pub fn launch(site: &Site, urls: &[Url], arguments: &[String]) -> Result<Child> {
     let name = site.name();
@@ -1,14 +1,23 @@
-use anyhow::Result;
+use std::path::Path;
+
+use anyhow::{Context, Result};
 use cfg_if::cfg_if;
+use log::info;
+use reqwest::blocking::Client;
+use sha2::{Digest, Sha256};
+use ulid::Ulid;
+use url::Url;
 
-#[rustfmt::skip]
 #[cfg(platform_macos)]
-use {crate::components::site::Site, std::process::Child, url::Url};
+use std::process::Child;
 
+use crate::components::site::Site;
+use crate::directories::ProjectDirs;
+use crate::integrations::utils::download_icon;
 use crate::integrations::{IntegrationInstallArgs, IntegrationUninstallArgs};
 
 #[cfg(all(platform_windows, not(feature = "portable")))]
-mod windows;
+pub mod windows;
 
 #[cfg(any(platform_linux, platform_bsd))]
 mod linux;
@@ -19,8 +28,21 @@ mod macos;
 #[cfg(all(platform_windows, feature = "portable"))]
 mod portableapps;
 
+/// Check whether system integration is disabled using the `FIREFOXPWA_NO_INTEGRATIONS` variable.
+///
+/// Useful for containerized and CI environments, where creating integration
+/// files or registry entries is either pointless or impossible.
+fn disabled() -> bool {
+    std::env::var("FIREFOXPWA_NO_INTEGRATIONS").is_ok()
+}
+
 #[inline]
 pub fn install(args: &IntegrationInstallArgs) -> Result<()> {
+    if disabled() {
+        info!("System integration is disabled, skipping installation");
+        return Ok(());
+    }
+
     cfg_if! {
         if #[cfg(all(platform_windows, not(feature = "portable")))] {
             windows::install(args)
@@ -38,6 +60,11 @@ pub fn install(args: &IntegrationInstallArgs) -> Result<()> {
 
 #[inline]
 pub fn uninstall(args: &IntegrationUninstallArgs) -> Result<()> {
+    if disabled() {
+        info!("System integration is disabled, skipping uninstallation");
+        return Ok(());
+    }
+
     cfg_if! {
         if #[cfg(all(platform_windows, not(feature = "portable")))] {
             windows::uninstall(args)
@@ -53,6 +80,166 @@ pub fn uninstall(args: &IntegrationUninstallArgs) -> Result<()> {
     }
 }
 
+/// Compute a hash of all web app metadata that affect its system integration.
+///
+/// Uses SHA-256, so the hash stays the same between builds and toolchain versions.
+/// Icons are hashed separately by [`hash_icons`], because they are only checked
+/// when icons are updated.
+fn hash(site: &Site) -> Result<String> {
+    let data = serde_json::to_vec(&(
+        site.name(),
+        site.description(),
+        site.domain(),
+        site.url(),
+        site.categories(),
+        site.keywords(),
+        &site.manifest.shortcuts,
+        &site.manifest.protocol_handlers,
+        &site.config.enabled_protocol_handlers,
+        &site.config.custom_protocol_handlers,
+        site.config.launch_on_login,
+        &site.config.locale,
+    ))?;
+
+    Ok(format!("{:x}", Sha256::digest(data)))
+}
+
+/// Compute a hash of the contents of all web app and shortcut icons.
+///
+/// Icons are downloaded and hashed by their contents, so a changed icon behind
+/// the same URL is also detected. Icons that cannot be downloaded are hashed by
+/// their URL instead, so they are retried once they are available again.
+fn hash_icons(site: &Site, client: &Client) -> String {
+    let mut hasher = Sha256::new();
+
+    let icons = site.icons();
+    let shortcuts = site.manifest.shortcuts.iter().flat_map(|shortcut| &shortcut.icons);
+
+    for icon in icons.iter().chain(shortcuts) {
+        let Ok(url): Result<Url, _> = icon.src.clone().try_into() else { continue };
+
+        match download_icon(url.clone(), client) {
+            Ok((bytes, _)) => hasher.update(bytes),
+            Err(_) => hasher.update(url.as_str()),
+        }
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// Update system integration of a web app, but only if anything has changed.
+///
+/// The integration hash, consisting of the metadata hash and the icon hash, is
+/// compared against the one stored in the web app config. When icons are not
+/// updated, their previous hash is kept, so only the metadata is compared.
+///
+/// Returns the new hash that should be stored if the integration was rewritten,
+/// or `None` if it was unchanged and the write was skipped.
+pub fn update(args: &IntegrationInstallArgs) -> Result<Option<String>> {
+    if disabled() {
+        info!("System integration is disabled, skipping update");
+        return Ok(None);
+    }
+
+    let stored = args.site.config.integration_hash.as_deref();
+    let metadata = hash(args.site).context("Failed to compute integration hash")?;
+
+    let icons = match (args.update_icons, args.client) {
+        (true, Some(client)) => hash_icons(args.site, client),
+        _ => match stored.and_then(|hash| hash.split_once('-')) {
+            Some((_, icons)) => icons.to_owned(),
+            None => String::new(),
+        },
+    };
+
+    let hash = format!("{metadata}-{icons}");
+    if stored == Some(hash.as_str()) {
+        info!("Integration unchanged, skipping");
+        return Ok(None);
+    }
+
+    install(args)?;
+    Ok(Some(hash))
+}
+
+/// List IDs of all web apps that have system integration installed.
+#[inline]
+pub fn list(dirs: &ProjectDirs) -> Result<Vec<Ulid>> {
+    cfg_if! {
+        if #[cfg(all(platform_windows, not(feature = "portable")))] {
+            windows::list(dirs)
+        } else if #[cfg(all(platform_windows, feature = "portable"))] {
+            portableapps::list(dirs)
+        } else if #[cfg(any(platform_linux, platform_bsd))] {
+            linux::list(dirs)
+        } else if #[cfg(platform_macos)] {
+            macos::list(dirs)
+        } else {
+            compile_error!("Unknown operating system");
+        }
+    }
+}
+
+/// Remove system integration of a web app that only exists as an ID.
+///
+/// Used for stale integrations whose web apps are no longer in storage,
+/// so everything needs to be located based on the web app ID alone.
+#[inline]
+pub fn purge(dirs: &ProjectDirs, ulid: &Ulid) -> Result<()> {
+    cfg_if! {
+        if #[cfg(all(platform_windows, not(feature = "portable")))] {
+            windows::purge(dirs, ulid)
+        } else if #[cfg(all(platform_windows, feature = "portable"))] {
+            portableapps::purge(dirs, ulid)
+        } else if #[cfg(any(platform_linux, platform_bsd))] {
+            linux::purge(dirs, ulid)
+        } else if #[cfg(platform_macos)] {
+            macos::purge(dirs, ulid)
+        } else {
+            compile_error!("Unknown operating system");
+        }
+    }
+}
+
+/// Pin or unpin a web app to the taskbar (on Windows) or the Dock (on macOS).
+///
+/// Other platforms do not provide a common way to pin applications,
+/// so an error is returned there.
+#[inline]
+pub fn pin(site: &Site, pinned: bool) -> Result<()> {
+    cfg_if! {
+        if #[cfg(all(platform_windows, not(feature = "portable")))] {
+            windows::pin(site, pinned)
+        } else if #[cfg(platform_macos)] {
+            macos::pin(site, pinned)
+        } else {
+            let _ = (site, pinned);
+            anyhow::bail!("Pinning web apps is not supported on this platform")
+        }
+    }
+}
+
+/// Generate all web app and shortcut icons into a directory without installing them.
+///
+/// Runs the same icon pipeline as the platform integration, so the generated files
+/// (ICO, PNG or ICNS) can be inspected when debugging icon problems.
+#[inline]
+pub fn dump_icons(site: &Site, target: &Path, client: &Client) -> Result<()> {
+    cfg_if! {
+        if #[cfg(all(platform_windows, not(feature = "portable")))] {
+            windows::dump_icons(site, target, client)
+        } else if #[cfg(all(platform_windows, feature = "portable"))] {
+            portableapps::dump_icons(site, target, client)
+        } else if #[cfg(any(platform_linux, platform_bsd))] {
+            linux::dump_icons(site, target, client)
+        } else if #[cfg(platform_macos)] {
+            macos::dump_icons(site, target, client)
+        } else {
+            compile_error!("Unknown operating system");
+        }
+    }
+}
+
 #[cfg(platform_macos)]
 #[inline]
 pub fn launch(site: &Site, urls: &[Url], arguments: &[String]) -> Result<Child> {
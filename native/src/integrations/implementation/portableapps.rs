@@ -1,13 +1,17 @@
 use std::ffi::OsStr;
-use std::fs::{File, create_dir_all, remove_dir_all};
+use std::fs::{File, create_dir_all, read_dir, remove_dir_all};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use configparser::ini::Ini;
 use log::warn;
+use reqwest::blocking::Client;
+use ulid::Ulid;
 use web_app_manifest::types::ImageSize;
 
+use crate::components::site::Site;
+use crate::directories::ProjectDirs;
 use crate::integrations::categories::PORTABLEAPPS_CATEGORIES;
 use crate::integrations::utils::{normalize_category_name, store_icon, store_multisize_icon};
 use crate::integrations::{IntegrationInstallArgs, IntegrationUninstallArgs};
@@ -88,7 +92,7 @@ fn get_portable_apps_directory(executables: &Path) -> Option<PathBuf> {
 /// be parsed, the next available  icon is attempted. In case no icons are available,
 /// an icon is generate from the web app name.
 ///
-fn store_icons(args: &IntegrationInstallArgs, path: &Path) -> Result<()> {
+fn store_icons(site: &Site, path: &Path, client: &Client) -> Result<()> {
     let required = [
         PortableAppIcon { size: 16, format: "png" },
         PortableAppIcon { size: 32, format: "png" },
@@ -97,9 +101,8 @@ fn store_icons(args: &IntegrationInstallArgs, path: &Path) -> Result<()> {
         PortableAppIcon { size: 256, format: "png" },
     ];
 
-    let icons = &args.site.icons();
-    let fallback = &args.site.name();
-    let client = args.client.unwrap();
+    let icons = &site.icons();
+    let fallback = &site.name();
 
     for icon in required {
         store_icon(icons, fallback, &icon.size(), &path.join(icon.filename()), client)?;
@@ -202,7 +205,8 @@ pub fn install(args: &IntegrationInstallArgs) -> Result<()> {
     create_dir_all(&contents).context("Failed to create application directory")?;
 
     if args.update_icons {
-        store_icons(args, &contents).context("Failed to store web app icons")?;
+        store_icons(args.site, &contents, args.client.unwrap())
+            .context("Failed to store web app icons")?;
     }
 
     let appinfo = contents.join("appinfo.ini");
@@ -231,3 +235,36 @@ pub fn uninstall(args: &IntegrationUninstallArgs) -> Result<()> {
     let _ = remove_dir_all(package);
     Ok(())
 }
+
+#[inline]
+pub fn list(dirs: &ProjectDirs) -> Result<Vec<Ulid>> {
+    let directory = match get_portable_apps_directory(&dirs.executables) {
+        Some(directory) => directory,
+        None => return Ok(vec![]),
+    };
+
+    let ulids = read_dir(directory)
+        .context("Failed to read portable apps directory")?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let appid = entry.file_name().into_string().ok()?;
+            Ulid::from_string(appid.strip_prefix("FFPWA-")?).ok()
+        })
+        .collect();
+
+    Ok(ulids)
+}
+
+#[inline]
+pub fn purge(dirs: &ProjectDirs, ulid: &Ulid) -> Result<()> {
+    if let Some(directory) = get_portable_apps_directory(&dirs.executables) {
+        let _ = remove_dir_all(directory.join(format!("FFPWA-{ulid}")));
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn dump_icons(site: &Site, target: &Path, client: &Client) -> Result<()> {
+    store_icons(site, target, client).context("Failed to store web app icons")
+}
@@ -1,9 +1,10 @@
 use std::fs::{copy, create_dir_all, remove_dir_all, remove_file, rename};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use log::warn;
 use reqwest::blocking::Client;
+use ulid::Ulid;
 use url::Url;
 use web_app_manifest::resources::IconResource;
 use windows::Win32::Storage::EnhancedStorage::{PKEY_AppUserModel_ID, PKEY_Title};
@@ -23,13 +24,18 @@ use windows::Win32::UI::Shell::{
     EnumerableObjectCollection,
     ICustomDestinationList,
     IShellLinkW,
+    SEE_MASK_INVOKEIDLIST,
+    SEE_MASK_NOASYNC,
+    SHELLEXECUTEINFOW,
+    ShellExecuteExW,
     ShellLink,
 };
-use windows::Win32::UI::WindowsAndMessaging::SW_SHOWMINNOACTIVE;
-use windows::core::{GUID, HSTRING, Interface, PCWSTR, Result as WindowsResult};
+use windows::Win32::UI::WindowsAndMessaging::{SW_SHOWMINNOACTIVE, SW_SHOWNORMAL};
+use windows::core::{GUID, HSTRING, Interface, PCWSTR, Result as WindowsResult, w};
 use windows_registry::{CURRENT_USER, Key};
 
 use crate::components::site::Site;
+use crate::directories::ProjectDirs;
 use crate::integrations::utils::{sanitize_name, store_multisize_icon};
 use crate::integrations::{IntegrationInstallArgs, IntegrationUninstallArgs};
 use crate::utils::sanitize_string;
@@ -74,9 +80,16 @@ impl SiteIds {
         let description = site.description();
         let ulid = site.ulid.to_string();
         let regid = format!("FFPWA-{ulid}");
-        let appid = format!("filips.firefoxpwa.{ulid}");
+        let appid = site.app_user_model_id();
         Self { name, description, ulid, regid, appid }
     }
+
+    pub fn create_for_ulid(ulid: &Ulid, name: String) -> Self {
+        let ulid = ulid.to_string();
+        let regid = format!("FFPWA-{ulid}");
+        let appid = format!("filips.firefoxpwa.{ulid}");
+        Self { name, description: String::new(), ulid, regid, appid }
+    }
 }
 
 /// Obtain and process the best available app/shortcut icon from the icon list.
@@ -127,7 +140,7 @@ fn create_menu_shortcut(
     exe: &str,
     icon: &str,
     data: &Path,
-) -> Result<()> {
+) -> Result<PathBuf> {
     let start_menu_dir = data.join(START_MENU_PROGRAMS_PATH);
 
     // Sanitize the name to prevent overflows and invalid filenames
@@ -168,7 +181,7 @@ fn create_menu_shortcut(
         persist.Save(&HSTRING::from(filename.display().to_string()), true)?;
     }
 
-    Ok(())
+    Ok(filename)
 }
 
 fn create_shell_startup_shortcut(
@@ -347,10 +360,78 @@ fn register_protocol_handlers(
     Ok(())
 }
 
+fn remove_integration(dirs: &ProjectDirs, ids: &SiteIds) -> Result<()> {
+    // Sanitize the name to prevent overflows and invalid filenames
+    let name = sanitize_name(&ids.name, &ids.ulid);
+
+    // Remove icons
+    let icons_directory = dirs.userdata.join("icons").join(&ids.ulid);
+    let _ = remove_dir_all(icons_directory);
+
+    // Remove ARP entry
+    let _ = CURRENT_USER.remove_tree(format!(r"{ADD_REMOVE_PROGRAMS_KEY}\{}", &ids.regid));
+
+    let data = directories::BaseDirs::new()
+        .context("Failed to determine base system directories")?
+        .data_dir()
+        .to_owned();
+
+    // Remove start menu shortcut
+    let start_menu_shortcut = data.join(START_MENU_PROGRAMS_PATH).join(&name).with_extension("lnk");
+    let _ = remove_file(start_menu_shortcut);
+
+    // Remove startup shortcut
+    let startup_shortcut = data.join(STARTUP_PROGRAMS_PATH).join(&name).with_extension("lnk");
+    let _ = remove_file(startup_shortcut);
+
+    // Remove jump list tasks
+    unsafe {
+        initialize_windows()?;
+        let list: ICustomDestinationList = create_instance(&DestinationList)?;
+        let _ = list.DeleteList(&HSTRING::from(&ids.appid));
+    }
+
+    // Remove protocol handlers
+    if let Ok(key) = CURRENT_USER.create(REGISTERED_APPLICATIONS_KEY) {
+        let _ = key.remove_value(&ids.regid);
+    }
+    let _ = CURRENT_USER.remove_tree(format!(r"Software\FirefoxPWA\{}", ids.regid));
+    let _ = CURRENT_USER.remove_tree(format!(r"Software\Classes\{}", ids.regid));
+
+    Ok(())
+}
+
 //////////////////////////////
 // Interface
 //////////////////////////////
 
+/// Create only the start menu shortcut of a web app inside a custom data directory.
+///
+/// Used by tests to inspect the shortcut properties without
+/// touching the real start menu or the registry.
+#[doc(hidden)]
+pub fn create_menu_shortcut_in(site: &Site, dirs: &ProjectDirs, data: &Path) -> Result<PathBuf> {
+    let args = IntegrationInstallArgs {
+        site,
+        dirs,
+        client: None,
+        update_manifest: false,
+        update_icons: false,
+        old_name: None,
+    };
+    let ids = SiteIds::create_for(site);
+
+    let exe_path = dirs.executables.join("firefoxpwa.exe").display().to_string();
+    let icon_path = dirs.userdata.join("icons").join(&ids.ulid).join("site.ico");
+    let icon_path = icon_path.display().to_string();
+
+    create_dir_all(data.join(START_MENU_PROGRAMS_PATH))
+        .context("Failed to create start menu directory")?;
+
+    initialize_windows()?;
+    create_menu_shortcut(&args, &ids, &exe_path, &icon_path, data)
+}
+
 #[inline]
 pub fn install(args: &IntegrationInstallArgs) -> Result<()> {
     let ids = SiteIds::create_for(args.site);
@@ -396,43 +477,86 @@ pub fn install(args: &IntegrationInstallArgs) -> Result<()> {
 #[inline]
 pub fn uninstall(args: &IntegrationUninstallArgs) -> Result<()> {
     let ids = SiteIds::create_for(args.site);
+    remove_integration(args.dirs, &ids)
+}
 
-    // Sanitize the name to prevent overflows and invalid filenames
-    let name = sanitize_name(&ids.name, &ids.ulid);
+#[inline]
+pub fn list(_dirs: &ProjectDirs) -> Result<Vec<Ulid>> {
+    let key = match CURRENT_USER.open(ADD_REMOVE_PROGRAMS_KEY) {
+        Ok(key) => key,
+        Err(_) => return Ok(vec![]),
+    };
 
-    // Remove icons
-    let icons_directory = args.dirs.userdata.join("icons").join(&ids.ulid);
-    let _ = remove_dir_all(icons_directory);
+    let ulids = key
+        .keys()
+        .context("Failed to enumerate ARP list entries")?
+        .filter_map(|regid| Ulid::from_string(regid.strip_prefix("FFPWA-")?).ok())
+        .collect();
 
-    // Remove ARP entry
-    let _ = CURRENT_USER.remove_tree(format!(r"{ADD_REMOVE_PROGRAMS_KEY}\{}", &ids.regid));
+    Ok(ulids)
+}
+
+#[inline]
+pub fn purge(dirs: &ProjectDirs, ulid: &Ulid) -> Result<()> {
+    // Shortcut filenames depend on the web app name, which is only stored in the ARP entry
+    let name = CURRENT_USER
+        .open(format!(r"{ADD_REMOVE_PROGRAMS_KEY}\FFPWA-{ulid}"))
+        .and_then(|key| key.get_string("DisplayName"))
+        .unwrap_or_default();
+
+    let ids = SiteIds::create_for_ulid(ulid, name);
+    remove_integration(dirs, &ids)
+}
+
+#[inline]
+pub fn pin(site: &Site, pinned: bool) -> Result<()> {
+    let ids = SiteIds::create_for(site);
+    let name = sanitize_name(&ids.name, &ids.ulid);
 
     let data = directories::BaseDirs::new()
         .context("Failed to determine base system directories")?
         .data_dir()
         .to_owned();
 
-    // Remove start menu shortcut
-    let start_menu_shortcut = data.join(START_MENU_PROGRAMS_PATH).join(&name).with_extension("lnk");
-    let _ = remove_file(start_menu_shortcut);
+    let shortcut = data.join(START_MENU_PROGRAMS_PATH).join(name).with_extension("lnk");
+    if !shortcut.exists() {
+        bail!("Start menu shortcut does not exist");
+    }
 
-    // Remove startup shortcut
-    let startup_shortcut = data.join(STARTUP_PROGRAMS_PATH).join(&name).with_extension("lnk");
-    let _ = remove_file(startup_shortcut);
+    // Pinning uses the same shell verbs as the shortcut context menu
+    // Newer Windows versions might refuse to execute them programmatically
+    let file = HSTRING::from(shortcut.as_os_str());
+    let mut sei = SHELLEXECUTEINFOW {
+        cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+        fMask: SEE_MASK_INVOKEIDLIST | SEE_MASK_NOASYNC,
+        lpVerb: if pinned { w!("taskbarpin") } else { w!("taskbarunpin") },
+        lpFile: PCWSTR(file.as_ptr()),
+        nShow: SW_SHOWNORMAL.0,
+        ..Default::default()
+    };
 
-    // Remove jump list tasks
     unsafe {
         initialize_windows()?;
-        let list: ICustomDestinationList = create_instance(&DestinationList)?;
-        let _ = list.DeleteList(&HSTRING::from(ids.appid));
+        ShellExecuteExW(&mut sei)?;
     }
 
-    // Remove protocol handlers
-    if let Ok(key) = CURRENT_USER.create(REGISTERED_APPLICATIONS_KEY) {
-        let _ = key.remove_value(&ids.regid);
+    Ok(())
+}
+
+#[inline]
+pub fn dump_icons(site: &Site, target: &Path, client: &Client) -> Result<()> {
+    let ids = SiteIds::create_for(site);
+
+    store_icon(&ids.name, &site.icons(), &target.join("site.ico"), client)
+        .context("Failed to store web app icon")?;
+
+    for (i, shortcut) in site.manifest.shortcuts.iter().enumerate() {
+        let name = sanitize_string(&shortcut.name);
+        let icon = target.join(format!("shortcut{i}.ico"));
+
+        store_icon(&name, &shortcut.icons, &icon, client)
+            .context("Failed to store shortcut icon")?;
     }
-    let _ = CURRENT_USER.remove_tree(format!(r"Software\FirefoxPWA\{}", ids.regid));
-    let _ = CURRENT_USER.remove_tree(format!(r"Software\Classes\{}", ids.regid));
 
     Ok(())
 }
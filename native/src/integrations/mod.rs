@@ -4,12 +4,13 @@ use crate::components::site::Site;
 use crate::directories::ProjectDirs;
 
 mod categories;
-mod implementation;
+pub mod implementation;
 mod utils;
 
 #[cfg(platform_macos)]
 pub use implementation::launch;
-pub use implementation::{install, uninstall};
+pub use implementation::{dump_icons, install, list, pin, purge, uninstall, update};
+pub use utils::normalize_icons;
 
 #[derive(Debug, Clone)]
 pub struct IntegrationInstallArgs<'a> {
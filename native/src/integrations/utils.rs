@@ -3,7 +3,7 @@
 use std::path::Path;
 
 use ab_glyph::{Font, FontRef, PxScale};
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
 use data_url::DataUrl;
 use image::imageops::Lanczos3;
 use image::{ImageBuffer, Rgb, RgbImage, RgbaImage};
@@ -14,6 +14,8 @@ use url::Url;
 use web_app_manifest::resources::IconResource;
 use web_app_manifest::types::{ImagePurpose, ImageSize, Url as ManifestUrl};
 
+use crate::utils::network_error;
+
 //////////////////////////////
 // Public
 //////////////////////////////
@@ -50,13 +52,22 @@ pub fn normalize_category_name(category: &str) -> String {
 
 /// Download the icon from the URL.
 ///
-/// Icon can be downloaded from the network using the `reqwest` crate
-/// or decoded from a data URL. Once downloaded, the function returns
-/// the icon bytes and its content type.
+/// Icon can be downloaded from the network using the `reqwest` crate,
+/// decoded from a data URL or read from a local file. Once downloaded,
+/// the function returns the icon bytes and its content type.
 pub fn download_icon(url: Url, client: &Client) -> Result<(Vec<u8>, String)> {
+    // Read from a local file, where only SVG icons need a specific type
+    if url.scheme() == "file" {
+        let path = url.to_file_path().map_err(|_| anyhow!("Invalid file URL: {url}"))?;
+        let r#type = match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("svg") => "image/svg+xml",
+            _ => "application/octet-stream",
+        };
+        Ok((std::fs::read(path)?, r#type.into()))
+
     // Download using `reqwest`
-    if url.scheme() != "data" {
-        let response = client.get(url).send()?;
+    } else if url.scheme() != "data" {
+        let response = client.get(url).send().map_err(network_error)?;
         let r#type = match response.headers().get(reqwest::header::CONTENT_TYPE) {
             Some(r#type) => r#type.to_str()?.into(),
             None => "application/octet-stream".into(),
@@ -233,24 +244,6 @@ pub fn generate_fallback_icon(letter: char, size: &ImageSize) -> Result<RgbImage
     Ok(image)
 }
 
-//////////////////////////////
-// Internal
-//////////////////////////////
-
-/// Check if the icon is supported.
-///
-/// Supported icons must contain "any" purpose and must only have absolute URLs.
-/// Other icons cannot / should not be parsed and need to be ignored.
-fn is_icon_supported(icon: &&IconResource) -> bool {
-    // Normal icons must contain "any" purpose
-    if !icon.purpose.contains(&ImagePurpose::Any) {
-        return false;
-    }
-
-    // Only icons with absolute URLs can be used
-    matches!(&icon.src, ManifestUrl::Absolute(_))
-}
-
 /// Filter out all incompatible icons and sort them.
 ///
 /// Icons are first filtered to remove icons without the `any` purpose and
@@ -260,7 +253,10 @@ fn is_icon_supported(icon: &&IconResource) -> bool {
 /// by icons with the `any` size. Next are icons larger than the target size
 /// in the ascending order, and finally, icons smaller than the target size
 /// in the descending order. Any icons without known sizes are placed last.
-fn normalize_icons<'a>(icons: &'a [IconResource], size: &'a ImageSize) -> Vec<&'a IconResource> {
+pub fn normalize_icons<'a>(
+    icons: &'a [IconResource],
+    size: &'a ImageSize,
+) -> Vec<&'a IconResource> {
     let mut icons: Vec<&IconResource> = icons.iter().filter(is_icon_supported).collect();
 
     let key = |icon: &IconResource| {
@@ -283,6 +279,24 @@ fn normalize_icons<'a>(icons: &'a [IconResource], size: &'a ImageSize) -> Vec<&'
     icons
 }
 
+//////////////////////////////
+// Internal
+//////////////////////////////
+
+/// Check if the icon is supported.
+///
+/// Supported icons must contain "any" purpose and must only have absolute URLs.
+/// Other icons cannot / should not be parsed and need to be ignored.
+fn is_icon_supported(icon: &&IconResource) -> bool {
+    // Normal icons must contain "any" purpose
+    if !icon.purpose.contains(&ImagePurpose::Any) {
+        return false;
+    }
+
+    // Only icons with absolute URLs can be used
+    matches!(&icon.src, ManifestUrl::Absolute(_))
+}
+
 /// Process the icon and stores it to a file.
 ///
 /// Icon can be downloaded from the network or from a data URL using
@@ -2,6 +2,7 @@ pub mod components;
 pub mod connector;
 pub mod console;
 pub mod directories;
+pub mod error;
 pub mod integrations;
 pub mod storage;
 pub mod utils;
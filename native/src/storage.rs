@@ -1,20 +1,66 @@
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Read};
+use std::path::PathBuf;
+use std::{fmt, io};
 
-use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use smart_default::SmartDefault;
+use tempfile::NamedTempFile;
 use ulid::Ulid;
+use url::Url;
 
 use crate::components::profile::Profile;
-use crate::components::site::Site;
+use crate::components::site::{Site, SiteConfig, SiteConfigParseError};
 use crate::directories::ProjectDirs;
+use crate::utils::normalize_url;
 
 const STORAGE_OPEN_ERROR: &str = "Failed to open storage";
 const STORAGE_LOAD_ERROR: &str = "Failed to load storage";
 const STORAGE_SAVE_ERROR: &str = "Failed to save storage";
 
+/// An error that occurred while loading or saving the storage.
+#[derive(Debug)]
+pub enum StorageError {
+    /// The storage file could not be opened or created.
+    Open(io::Error),
+
+    /// The storage file could not be read or parsed.
+    Load(serde_json::Error),
+
+    /// The storage file could not be serialized or written.
+    Save(serde_json::Error),
+
+    /// The storage file contains a web app with invalid config fields.
+    InvalidSite(String, Vec<SiteConfigParseError>),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Open(_) => write!(f, "{STORAGE_OPEN_ERROR}"),
+            Self::Load(_) => write!(f, "{STORAGE_LOAD_ERROR}"),
+            Self::Save(_) => write!(f, "{STORAGE_SAVE_ERROR}"),
+            Self::InvalidSite(site, errors) => {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{STORAGE_LOAD_ERROR}, web app {site} has invalid config: ")?;
+                write!(f, "{}", errors.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Open(error) => Some(error),
+            Self::Load(error) | Self::Save(error) => Some(error),
+            Self::InvalidSite(..) => None,
+        }
+    }
+}
+
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, SmartDefault)]
 #[serde(default)]
@@ -60,10 +106,15 @@ pub struct Config {
 #[serde(default)]
 pub struct Storage {
     /// A map of profiles and their IDs.
+    ///
+    /// Iterating over it yields profiles sorted by their IDs, which
+    /// are time-ordered, so the output of commands is deterministic.
     #[default([(Ulid::nil(), Profile::default())].iter().cloned().collect())]
     pub profiles: BTreeMap<Ulid, Profile>,
 
     /// A map of web apps and their IDs.
+    ///
+    /// Same as profiles, web apps are always iterated in order of their IDs.
     pub sites: BTreeMap<Ulid, Site>,
 
     /// Arguments to be passed to the Firefox runtime.
@@ -77,30 +128,118 @@ pub struct Storage {
 }
 
 impl Storage {
-    pub fn load(dirs: &ProjectDirs) -> Result<Self> {
-        let filename = dirs.userdata.join("config.json");
+    /// Returns the path to the storage file.
+    pub fn path(dirs: &ProjectDirs) -> PathBuf {
+        dirs.userdata.join("config.json")
+    }
+
+    pub fn load(dirs: &ProjectDirs) -> Result<Self, StorageError> {
+        let filename = Self::path(dirs);
 
         if !filename.exists() {
             return Ok(Self::default());
         }
 
-        let file = File::open(filename).context(STORAGE_OPEN_ERROR)?;
+        let file = File::open(filename).map_err(StorageError::Open)?;
         let mut reader = BufReader::new(file);
         let mut data = String::new();
 
-        reader.read_to_string(&mut data).context(STORAGE_LOAD_ERROR)?;
-        serde_json::from_str(&data).context(STORAGE_LOAD_ERROR)
+        reader
+            .read_to_string(&mut data)
+            .map_err(|error| StorageError::Load(serde_json::Error::io(error)))?;
+        serde_json::from_str(&data)
+            .map_err(|error| Self::find_invalid_site(&data).unwrap_or(StorageError::Load(error)))
     }
 
-    pub fn write(&self, dirs: &ProjectDirs) -> Result<()> {
-        let filename = dirs.userdata.join("config.json");
-        let file = File::create(filename).context(STORAGE_OPEN_ERROR)?;
-        let writer = BufWriter::new(file);
+    /// Finds the web app whose config prevents the storage from being parsed.
+    ///
+    /// Lists all invalid config fields, which is easier to fix than
+    /// the position of the first error reported by the parser.
+    fn find_invalid_site(data: &str) -> Option<StorageError> {
+        let value: Value = serde_json::from_str(data).ok()?;
+
+        for (ulid, site) in value.get("sites")?.as_object()? {
+            let config = site.get("config").cloned().unwrap_or_default();
+            if let Err(errors) = SiteConfig::try_from(config) {
+                return Some(StorageError::InvalidSite(ulid.to_owned(), errors));
+            }
+        }
+
+        None
+    }
+
+    /// Writes the storage file atomically.
+    ///
+    /// The storage is first written into a temporary file in the same directory,
+    /// which then replaces the storage file. Concurrent commands, such as multiple
+    /// web apps launched at login, therefore never read a partially written file.
+    pub fn write(&self, dirs: &ProjectDirs) -> Result<(), StorageError> {
+        let save = |error: io::Error| StorageError::Save(serde_json::Error::io(error));
+
+        let filename = Self::path(dirs);
+        let file = NamedTempFile::new_in(&dirs.userdata).map_err(StorageError::Open)?;
+        let mut writer = BufWriter::new(file);
 
         if cfg!(debug_assertions) {
-            serde_json::to_writer_pretty(writer, &self).context(STORAGE_SAVE_ERROR)
+            serde_json::to_writer_pretty(&mut writer, &self).map_err(StorageError::Save)?;
         } else {
-            serde_json::to_writer(writer, &self).context(STORAGE_SAVE_ERROR)
+            serde_json::to_writer(&mut writer, &self).map_err(StorageError::Save)?;
         }
+
+        let file = writer.into_inner().map_err(|error| save(error.into_error()))?;
+        file.as_file().sync_all().map_err(save)?;
+        file.persist(filename).map_err(|error| save(error.error))?;
+        Ok(())
+    }
+
+    /// Finds all profiles with the given name.
+    ///
+    /// Names are compared case-insensitively unless `exact` is set.
+    #[must_use = "finding profiles has no side effects"]
+    pub fn find_profiles_by_name(&self, name: &str, exact: bool) -> Vec<(Ulid, &Profile)> {
+        let name = if exact { name.to_owned() } else { name.to_lowercase() };
+
+        self.profiles
+            .iter()
+            .filter(|(_, profile)| match &profile.name {
+                Some(other) if exact => *other == name,
+                Some(other) => other.to_lowercase() == name,
+                None => false,
+            })
+            .map(|(ulid, profile)| (*ulid, profile))
+            .collect()
+    }
+
+    /// Finds the web app with the given document URL.
+    ///
+    /// Returns `None` if no web app or more than one web app matches the URL.
+    pub fn find_site_by_url(&self, url: &Url) -> Option<(Ulid, &Site)> {
+        match self.find_sites_by_url(url).as_slice() {
+            [site] => Some(*site),
+            _ => None,
+        }
+    }
+
+    /// Finds all web apps with the given document URL.
+    ///
+    /// Web apps are first matched by the origin of their document URL. If
+    /// any of them also match the full normalized URL, only those are returned.
+    #[must_use = "finding web apps has no side effects"]
+    pub fn find_sites_by_url(&self, url: &Url) -> Vec<(Ulid, &Site)> {
+        let sites: Vec<_> = self
+            .sites
+            .iter()
+            .filter(|(_, site)| site.config.document_url.origin() == url.origin())
+            .map(|(ulid, site)| (*ulid, site))
+            .collect();
+
+        let url = normalize_url(url);
+        let exact: Vec<_> = sites
+            .iter()
+            .copied()
+            .filter(|(_, site)| normalize_url(&site.config.document_url) == url)
+            .collect();
+
+        if exact.is_empty() { sites } else { exact }
     }
 }
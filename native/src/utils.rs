@@ -1,18 +1,47 @@
-use std::fs::File;
-use std::io::Read;
-use std::path::PathBuf;
+use std::fs::{File, create_dir_all, remove_file};
+use std::io::{Read, Seek, Write};
+use std::net::ToSocketAddrs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-use anyhow::{Context, Result};
-use log::warn;
-use reqwest::Certificate;
+use anyhow::{Context, Result, bail};
+use cfg_if::cfg_if;
+use log::{info, warn};
+use reqwest::{Certificate, Proxy};
 use reqwest::blocking::Client;
 use reqwest::header::{HeaderMap, HeaderValue};
+use url::Url;
 
-const APP_USER_AGENT: &str =
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:140.0) Gecko/20100101 Firefox/140.0";
+use crate::directories::ProjectDirs;
+use crate::error::CommandError;
+
+const APP_USER_AGENT: &str = concat!(
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:140.0) Gecko/20100101 Firefox/140.0 FirefoxPWA/",
+    env!("CARGO_PKG_VERSION"),
+);
+
+/// Get the default user-agent header for requests made by the HTTP client.
+///
+/// Can be overwritten by the `FIREFOXPWA_USER_AGENT` variable. By default, it mimics Firefox
+/// and identifies the project, because some sites block or serve stripped-down manifests
+/// to unknown clients.
+fn get_user_agent() -> String {
+    std::env::var("FIREFOXPWA_USER_AGENT")
+        .ok()
+        .filter(|user_agent| !user_agent.is_empty())
+        .unwrap_or_else(|| APP_USER_AGENT.into())
+}
+
+const NO_CONNECTION_ERROR: &str = "No internet connection";
+const TEMP_DIR_ERROR: &str = "Failed to prepare the temporary directory";
 
 /// Load DER and PEM certificates from files.
 ///
+/// PEM files may contain multiple certificates. Certificates from a PEM file
+/// specified in the `FIREFOXPWA_CAFILE` environment variable are also loaded.
+///
 /// # Parameters
 ///
 /// - `certificates_der` - A list of paths to DER certificate files.
@@ -37,14 +66,16 @@ pub fn load_certificates(
         certs.push(cert);
     }
 
-    for path in certificates_pem.iter().flatten() {
+    let cafile = std::env::var_os("FIREFOXPWA_CAFILE").map(PathBuf::from);
+
+    for path in certificates_pem.iter().flatten().chain(cafile.as_ref()) {
         let mut buf = vec![];
         File::open(path)
             .context(CERT_READ_ERROR)?
             .read_to_end(&mut buf)
             .context(CERT_READ_ERROR)?;
-        let cert = Certificate::from_pem(&buf).context(CERT_PARSE_ERROR)?;
-        certs.push(cert);
+        let bundle = Certificate::from_pem_bundle(&buf).context(CERT_PARSE_ERROR)?;
+        certs.extend(bundle);
     }
 
     Ok(certs)
@@ -54,7 +85,7 @@ pub fn load_certificates(
 ///
 /// # Parameters
 ///
-/// - `user_agent` - A custom user-agent header.
+/// - `user_agent` - A custom user-agent header, see [`get_user_agent`] for the default.
 /// - `root_certificates` - A list of additional root certificates.
 /// - `danger_accept_invalid_certs` - Whether the client accepts invalid certs (dangerous).
 /// - `danger_accept_invalid_hostnames` - Whether the client accepts invalid hostnames (dangerous).
@@ -69,8 +100,13 @@ pub fn construct_client(
     headers.insert("Sec-Fetch-Site", HeaderValue::from_static("none"));
     headers.insert("Sec-Fetch-Dest", HeaderValue::from_static("manifest"));
 
+    let user_agent = match user_agent {
+        Some(user_agent) => user_agent.to_owned(),
+        None => get_user_agent(),
+    };
+
     let mut builder = Client::builder()
-        .user_agent(user_agent.unwrap_or(APP_USER_AGENT))
+        .user_agent(user_agent)
         .default_headers(headers)
         .danger_accept_invalid_certs(danger_accept_invalid_certs)
         .danger_accept_invalid_hostnames(danger_accept_invalid_hostnames);
@@ -84,34 +120,495 @@ pub fn construct_client(
         builder = builder.add_root_certificate(certificate);
     }
 
+    if let Some(proxy) = get_proxy() {
+        builder = builder.proxy(Proxy::all(proxy)?);
+    }
+
     builder.build()
 }
 
+/// Options of the HTTP client that are specified by the user.
+///
+/// See [load_certificates] and [construct_client] for more details.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ClientArgs<'a> {
+    pub user_agent: Option<&'a str>,
+    pub certificates_der: &'a Option<Vec<PathBuf>>,
+    pub certificates_pem: &'a Option<Vec<PathBuf>>,
+    pub danger_accept_invalid_certs: bool,
+    pub danger_accept_invalid_hostnames: bool,
+}
+
 /// Load certificates from files and constructs a HTTP client with them.
 ///
 /// See [load_certificates] and [construct_client] for more
-/// details and description of function parameters.
+/// details and description of client options.
 ///
-pub(crate) fn construct_certificates_and_client(
-    user_agent: Option<&str>,
-    certificates_der: &Option<Vec<PathBuf>>,
-    certificates_pem: &Option<Vec<PathBuf>>,
-    danger_accept_invalid_certs: bool,
-    danger_accept_invalid_hostnames: bool,
-) -> Result<Client> {
+pub(crate) fn construct_certificates_and_client(args: &ClientArgs) -> Result<Client> {
     const CLIENT_CERT_ERROR: &str = "Failed to load HTTP client certificates";
     const CLIENT_CONSTRUCT_ERROR: &str = "Failed to construct HTTP client";
 
     construct_client(
-        user_agent,
-        load_certificates(certificates_der, certificates_pem).context(CLIENT_CERT_ERROR)?,
-        danger_accept_invalid_certs,
-        danger_accept_invalid_hostnames,
+        args.user_agent,
+        load_certificates(args.certificates_der, args.certificates_pem)
+            .context(CLIENT_CERT_ERROR)?,
+        args.danger_accept_invalid_certs,
+        args.danger_accept_invalid_hostnames,
     )
     .context(CLIENT_CONSTRUCT_ERROR)
 }
 
+/// Parse credentials and custom headers into a header map.
+///
+/// Credentials in the `USER:PASSWORD` format are converted into the HTTP Basic
+/// `Authorization` header, and headers need to be in the `NAME: VALUE` format.
+/// All values are marked as sensitive, so they are never printed in logs.
+/// They are meant to be sent only with the manifest request, never with other requests.
+///
+/// # Parameters
+///
+/// - `auth` - HTTP Basic credentials.
+/// - `headers` - A list of custom headers.
+///
+pub fn parse_headers(auth: Option<&str>, headers: &[String]) -> Result<HeaderMap> {
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use reqwest::header::{AUTHORIZATION, HeaderName};
+
+    let mut map = HeaderMap::new();
+
+    if let Some(auth) = auth {
+        if !auth.contains(':') {
+            bail!("Invalid credentials, expected the USER:PASSWORD format");
+        }
+
+        let mut value = HeaderValue::try_from(format!("Basic {}", STANDARD.encode(auth)))
+            .context("Invalid credentials")?;
+        value.set_sensitive(true);
+        map.insert(AUTHORIZATION, value);
+    }
+
+    for header in headers {
+        let Some((name, value)) = header.split_once(':') else {
+            bail!("Invalid header, expected the NAME: VALUE format");
+        };
+
+        let name = HeaderName::try_from(name.trim()).context("Invalid header name")?;
+        let mut value = HeaderValue::try_from(value.trim()).context("Invalid header value")?;
+        value.set_sensitive(true);
+        map.append(name, value);
+    }
+
+    Ok(map)
+}
+
+/// Construct a HTTP client for downloading the runtime and other components.
+///
+/// Unlike [construct_client], it does not send any browser-like headers,
+/// but it still trusts certificates from the `FIREFOXPWA_CAFILE` variable.
+///
+pub(crate) fn construct_download_client() -> Result<Client> {
+    const CLIENT_CERT_ERROR: &str = "Failed to load HTTP client certificates";
+    const CLIENT_CONSTRUCT_ERROR: &str = "Failed to construct HTTP client";
+
+    let mut builder = Client::builder();
+
+    for certificate in load_certificates(&None, &None).context(CLIENT_CERT_ERROR)? {
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    if let Some(proxy) = get_proxy() {
+        builder = builder.proxy(Proxy::all(proxy).context(CLIENT_CONSTRUCT_ERROR)?);
+    }
+
+    builder.build().context(CLIENT_CONSTRUCT_ERROR)
+}
+
+/// Get the proxy URL from the `FIREFOXPWA_PROXY` environment variable.
+///
+/// The proxy is used for all requests made by the program, but not by the runtime.
+/// If not set, the system proxy from the standard environment variables is used.
+fn get_proxy() -> Option<String> {
+    std::env::var("FIREFOXPWA_PROXY").ok().filter(|proxy| !proxy.is_empty())
+}
+
+/// A cancellation signal for a long-running operation.
+///
+/// Connector requests are processed in separate processes, so the request that
+/// cancels an operation cannot reach it directly. Instead, the cancellation is
+/// signalled with a marker file in the user data directory, which the operation
+/// periodically checks.
+#[derive(Debug, Clone)]
+pub struct Cancellation {
+    marker: PathBuf,
+}
+
+impl Cancellation {
+    pub fn new(dirs: &ProjectDirs, id: &str) -> Result<Self> {
+        // The ID is used as a file name, so it must not contain any path separators
+        if id.is_empty() || !id.chars().all(|char| char.is_ascii_alphanumeric() || char == '-') {
+            bail!("Invalid operation ID: {id}");
+        }
+
+        let marker = dirs.userdata.join("operations").join(format!("{id}.cancel"));
+        Ok(Self { marker })
+    }
+
+    /// Signal the operation to stop.
+    pub fn cancel(&self) -> Result<()> {
+        const CANCEL_ERROR: &str = "Failed to cancel the operation";
+
+        if let Some(parent) = self.marker.parent() {
+            create_dir_all(parent).context(CANCEL_ERROR)?;
+        }
+
+        File::create(&self.marker).context(CANCEL_ERROR)?;
+        Ok(())
+    }
+
+    /// Return an error if the operation has been cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.marker.exists() {
+            bail!(CommandError::Cancelled);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the cancellation signal once the operation has finished.
+    pub fn clear(&self) {
+        let _ = remove_file(&self.marker);
+    }
+}
+
+/// A progress callback of a long-running operation.
+///
+/// Connector requests forward progress to the extension, so it can display a progress
+/// bar. Every report contains a completion percentage of the current stage, if it can
+/// be determined, and a human-readable description of the stage.
+pub struct Progress<'a> {
+    callback: Box<dyn Fn(Option<u8>, &str) -> Result<()> + 'a>,
+}
+
+impl<'a> Progress<'a> {
+    pub fn new(callback: impl Fn(Option<u8>, &str) -> Result<()> + 'a) -> Self {
+        Self { callback: Box::new(callback) }
+    }
+
+    /// Report the progress of the current stage.
+    pub fn report(&self, percent: Option<u8>, message: &str) -> Result<()> {
+        (self.callback)(percent, message)
+    }
+
+    /// Log the start of a new stage and report it, if the operation has a progress callback.
+    pub fn stage(progress: Option<&Self>, message: &str) -> Result<()> {
+        info!("{message}");
+        progress.map_or(Ok(()), |progress| progress.report(None, message))
+    }
+}
+
+/// Download a file, retrying on failures.
+///
+/// The file is truncated before every attempt, so it never contains data from
+/// a failed attempt. When the operation is cancelled, the download is stopped
+/// immediately and not retried. Removing the partially downloaded file is the
+/// responsibility of the caller, which usually uses a temporary file.
+///
+/// If the server sends the content length, the completion percentage is
+/// reported to the progress callback every time it changes.
+pub fn download(
+    client: &Client,
+    url: &str,
+    file: &mut File,
+    cancellation: Option<&Cancellation>,
+    progress: Option<&Progress>,
+) -> Result<()> {
+    const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+    let check = || cancellation.map_or(Ok(()), Cancellation::check);
+
+    for attempt in 1..=DOWNLOAD_ATTEMPTS {
+        // Wrapped into a closure to emulate currently unstable `try` blocks
+        let mut download = || -> Result<()> {
+            file.set_len(0)?;
+            file.rewind()?;
+
+            let mut response = client.get(url).send()?.error_for_status()?;
+            let mut buffer = vec![0; 64 * 1024];
+
+            let total = response.content_length().filter(|total| *total > 0);
+            let mut downloaded = 0;
+            let mut reported = None;
+
+            loop {
+                check()?;
+
+                let read = response.read(&mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+
+                file.write_all(&buffer[..read])?;
+                downloaded += read as u64;
+
+                if let (Some(progress), Some(total)) = (progress, total) {
+                    let percent = (downloaded * 100 / total).min(100) as u8;
+                    if reported != Some(percent) {
+                        progress.report(Some(percent), &format!("Downloaded {percent}%"))?;
+                        reported = Some(percent);
+                    }
+                }
+            }
+
+            Ok(())
+        };
+
+        match download() {
+            Ok(()) => return Ok(()),
+            Err(error) if attempt < DOWNLOAD_ATTEMPTS && check().is_ok() => {
+                warn!("Download failed, retrying ({attempt}/{DOWNLOAD_ATTEMPTS}): {error}");
+            }
+            Err(error) => return Err(network_error(error)),
+        }
+    }
+
+    unreachable!()
+}
+
+/// Check whether the network error was caused by a missing internet connection.
+///
+/// Errors from connecting to a server are usually confusing DNS or connection
+/// errors. If the error is such an error and a well-known host also cannot be
+/// resolved, it is wrapped into a clear error with the original one as its cause.
+/// The check only happens after a failure, as some servers may be on an intranet.
+pub fn network_error(error: impl Into<anyhow::Error>) -> anyhow::Error {
+    let error = error.into();
+
+    let connection = error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|cause| cause.is_connect() || cause.is_timeout());
+
+    // Resolving a host is a lightweight way to check the connection without sending requests
+    let online =
+        || ("firefox.com", 443).to_socket_addrs().is_ok_and(|mut addrs| addrs.next().is_some());
+
+    if connection && !online() { error.context(NO_CONNECTION_ERROR) } else { error }
+}
+
+/// Get the directory for temporary files.
+///
+/// Can be overwritten by the `FIREFOXPWA_TMPDIR` variable. Otherwise, the system
+/// temporary directory is used, which respects the `TMPDIR` variable on Unix and
+/// the `TMP` and `TEMP` variables on Windows.
+pub fn temp_dir() -> PathBuf {
+    match std::env::var_os("FIREFOXPWA_TMPDIR") {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => std::env::temp_dir(),
+    }
+}
+
+/// Prepare the directory for temporary files before a large download or extraction.
+///
+/// Checks that the directory is writable and has at least the required amount of
+/// free space, so the operation does not fail in the middle. If the free space
+/// cannot be determined, only a warning is logged.
+pub fn prepare_temp_dir(required: u64) -> Result<PathBuf> {
+    let directory = temp_dir();
+    create_dir_all(&directory).context(TEMP_DIR_ERROR)?;
+    tempfile::tempfile_in(&directory)
+        .with_context(|| format!("Directory {} is not writable", directory.display()))
+        .context(TEMP_DIR_ERROR)?;
+
+    ensure_free_space(&directory, required)?;
+    Ok(directory)
+}
+
+/// Normalize the URL, so different spellings of the same URL compare equal.
+///
+/// For HTTP(S) URLs, the host is lowercased, default ports and the fragment are removed,
+/// and query parameters are sorted without re-encoding them. Other URLs are returned as-is,
+/// because their fragments or queries might be meaningful.
+#[must_use = "the URL is returned normalized instead of being modified in place"]
+pub fn normalize_url(url: &Url) -> Url {
+    let mut url = url.clone();
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return url;
+    }
+
+    // Hosts are already lowercased and default ports removed when HTTP(S) URLs are parsed
+    url.set_fragment(None);
+
+    let query = url.query().map(|query| {
+        let mut params: Vec<&str> = query.split('&').filter(|param| !param.is_empty()).collect();
+        params.sort_unstable();
+        params.join("&")
+    });
+    url.set_query(query.as_deref().filter(|query| !query.is_empty()));
+
+    url
+}
+
+/// Compute the SHA-256 checksum of a file.
+pub fn compute_checksum(path: &Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Check that the disk with the path has at least the required amount of free space.
+///
+/// If the path does not exist yet, its closest existing ancestor is checked.
+/// If the free space cannot be determined, only a warning is logged.
+pub fn ensure_free_space(path: &Path, required: u64) -> Result<()> {
+    const MIB: u64 = 1024 * 1024;
+
+    let directory = path.ancestors().find(|path| path.exists()).unwrap_or(path);
+    match free_space(directory) {
+        Some(available) if available < required => bail!(
+            "Not enough free space in {}: {} MiB required, {} MiB available",
+            directory.display(),
+            required.div_ceil(MIB),
+            available / MIB
+        ),
+        Some(_) => {}
+        None => warn!("Failed to determine free space in {}", directory.display()),
+    }
+
+    Ok(())
+}
+
+/// Get the free space available to the current user on the disk with the path.
+fn free_space(path: &Path) -> Option<u64> {
+    cfg_if! {
+        if #[cfg(platform_windows)] {
+            use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+            use windows::core::HSTRING;
+
+            let mut available = 0u64;
+            let path = HSTRING::from(path.as_os_str());
+            unsafe { GetDiskFreeSpaceExW(&path, Some(&raw mut available), None, None).ok()? };
+            Some(available)
+        } else {
+            // The POSIX output format is stable, with available space in KiB in the fourth column
+            let output = Command::new("df").arg("-Pk").arg(path).output().ok()?;
+            let output = String::from_utf8(output.stdout).ok()?;
+            let available: u64 = output.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+            Some(available * 1024)
+        }
+    }
+}
+
+/// Run a job for each item using a bounded pool of worker threads.
+///
+/// Results are returned in the same order as the items, regardless of the
+/// order in which the jobs complete. Jobs should report failures through
+/// their results, so a failed job does not affect the other jobs.
+///
+/// # Parameters
+///
+/// - `items` - A list of items to process.
+/// - `jobs` - A maximum number of concurrently running jobs.
+/// - `job` - A function that processes a single item.
+///
+pub fn run_parallel<T, R, F>(items: &[T], jobs: usize, job: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let workers = jobs.clamp(1, items.len().max(1));
+
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else { break };
+                        results.push((index, job(item)));
+                    }
+                    results
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Worker thread panicked"))
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Show a native desktop notification.
+///
+/// Uses toast notifications through PowerShell on Windows, `notify-send` on Linux
+/// and BSD, and `osascript` on macOS. The title and body are passed as environment
+/// variables, so they do not need to be escaped for the scripts.
+///
+/// # Parameters
+///
+/// - `title` - A notification title.
+/// - `body` - A notification body.
+///
+pub fn send_notification(title: &str, body: &str) -> Result<()> {
+    const NOTIFICATION_ERROR: &str = "Failed to show notification";
+
+    let mut command;
+
+    cfg_if! {
+        if #[cfg(platform_windows)] {
+            const SCRIPT: &str = r#"
+                $manager = [Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime]
+                $template = $manager::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+                $text = $template.GetElementsByTagName('text')
+                $text.Item(0).AppendChild($template.CreateTextNode($env:FFPWA_NOTIFICATION_TITLE)) > $null
+                $text.Item(1).AppendChild($template.CreateTextNode($env:FFPWA_NOTIFICATION_BODY)) > $null
+                $notifier = $manager::CreateToastNotifier('{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell1.0\powershell.exe')
+                $notifier.Show([Windows.UI.Notifications.ToastNotification]::new($template))
+            "#;
+
+            command = Command::new("powershell");
+            command.args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT]);
+        } else if #[cfg(platform_macos)] {
+            const SCRIPT: &str = r#"
+                display notification (system attribute "FFPWA_NOTIFICATION_BODY") ¬
+                    with title (system attribute "FFPWA_NOTIFICATION_TITLE")
+            "#;
+
+            command = Command::new("osascript");
+            command.args(["-e", SCRIPT]);
+        } else {
+            command = Command::new("notify-send");
+            command.args(["--app-name", "PWAsForFirefox", title, body]);
+        }
+    }
+
+    let status = command
+        .env("FFPWA_NOTIFICATION_TITLE", title)
+        .env("FFPWA_NOTIFICATION_BODY", body)
+        .status()
+        .context(NOTIFICATION_ERROR)?;
+
+    if !status.success() {
+        bail!(NOTIFICATION_ERROR);
+    }
+
+    Ok(())
+}
+
 /// Remove all control characters from the string.
+#[must_use = "the string is returned sanitized instead of being modified in place"]
 pub fn sanitize_string(string: &str) -> String {
     string.chars().filter(|char| !char.is_control()).collect()
 }
@@ -0,0 +1,91 @@
+//! Tests for the "clone, modify and create new" patterns used by the console commands.
+
+use firefoxpwa::components::profile::{Profile, ProfileBuilder};
+use firefoxpwa::components::site::{Site, SiteConfig, SiteConfigBuilder};
+use ulid::Ulid;
+use url::Url;
+
+mod common;
+
+fn config() -> SiteConfig {
+    let mut config = common::config();
+    config.name = Some("Example".into());
+    config.categories = Some(vec!["Utility".into()]);
+    config
+}
+
+fn site() -> Site {
+    common::site_with(Ulid::nil(), config())
+}
+
+#[test]
+fn site_config_clone_is_independent() {
+    let original = config();
+
+    let mut modified = original.clone();
+    modified.name = Some("Modified".into());
+    modified.categories.get_or_insert_with(Vec::new).push("Development".into());
+    modified.window.maximized = true;
+
+    assert_eq!(original.name.as_deref(), Some("Example"));
+    assert_eq!(original.categories, Some(vec!["Utility".into()]));
+    assert!(!original.window.maximized);
+    assert_ne!(original, modified);
+}
+
+#[test]
+fn site_config_builder_clone_builds_separate_configs() {
+    let builder = SiteConfigBuilder::new()
+        .manifest_url("https://example.com/manifest.json")
+        .document_url("https://example.com/");
+
+    let first = builder.clone().name(Some("First".into())).build().unwrap();
+    let second = builder.name(Some("Second".into())).build().unwrap();
+
+    assert_eq!(first.name.as_deref(), Some("First"));
+    assert_eq!(second.name.as_deref(), Some("Second"));
+    assert_eq!(first.document_url, second.document_url);
+}
+
+#[test]
+fn site_clone_with_new_config() {
+    let original = site();
+
+    let mut config = original.config.clone();
+    config.start_url = Some(Url::parse("https://example.com/start").unwrap());
+
+    let mut modified = original.clone();
+    modified.config = config;
+
+    assert_eq!(original.config.start_url, None);
+    assert_eq!(modified.url(), "https://example.com/start");
+    assert_eq!(modified.ulid, original.ulid);
+    assert_eq!(modified.manifest, original.manifest);
+}
+
+#[test]
+fn profile_clone_is_independent() {
+    let original = ProfileBuilder::new().name(Some("Work".into())).tags(vec!["a".into()]).build();
+
+    let mut modified = original.clone();
+    modified.name = Some("Personal".into());
+    modified.tags.push("b".into());
+    modified.sites.push(Ulid::new());
+
+    assert_eq!(original.name.as_deref(), Some("Work"));
+    assert_eq!(original.tags, vec!["a".to_string()]);
+    assert!(original.sites.is_empty());
+    assert_eq!(modified.ulid, original.ulid);
+}
+
+#[test]
+fn profile_default_is_shared_profile() {
+    let shared = Profile::default();
+
+    let mut copy = shared.clone();
+    copy.sites.push(Ulid::new());
+
+    assert!(shared.ulid.is_nil());
+    assert!(shared.sites.is_empty());
+    assert_eq!(copy.ulid, shared.ulid);
+}
@@ -0,0 +1,35 @@
+//! Fixtures shared by the integration tests and benchmarks.
+//!
+//! Benchmarks include this module with `#[path = "../tests/common/mod.rs"]`.
+
+#![allow(dead_code)]
+
+use firefoxpwa::components::site::{Site, SiteConfig, SiteConfigBuilder};
+use reqwest::blocking::Client;
+use ulid::Ulid;
+
+pub const MANIFEST: &str = r#"{
+    "name": "Example",
+    "start_url": "/",
+    "icons": [{ "src": "/icon.png", "sizes": "192x192" }]
+}"#;
+
+/// Creates the config of a web app served from `https://example.com/`.
+pub fn config() -> SiteConfig {
+    SiteConfigBuilder::new()
+        .manifest_url("https://example.com/manifest.json")
+        .document_url("https://example.com/")
+        .build()
+        .unwrap()
+}
+
+/// Creates a web app in the profile from [`MANIFEST`] and the provided config.
+pub fn site_with(profile: Ulid, config: SiteConfig) -> Site {
+    // The manifest contains a usable icon, so no fallback icon is downloaded
+    Site::from_manifest(profile, config, MANIFEST, &Client::new()).unwrap()
+}
+
+/// Creates a web app in the profile from [`MANIFEST`] and the default [`config`].
+pub fn site(profile: Ulid) -> Site {
+    site_with(profile, config())
+}
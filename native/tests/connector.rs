@@ -0,0 +1,29 @@
+//! Tests for the connector protocol handling.
+//!
+//! Connections are served over in-memory socket pairs, so the tests do not need the browser.
+
+#![cfg(not(platform_windows))]
+
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use firefoxpwa::connector::{Connection, ConnectionError};
+use firefoxpwa::directories::ProjectDirs;
+use tempfile::TempDir;
+
+#[test]
+fn incomplete_request_times_out() {
+    let temp = TempDir::new().unwrap();
+    let dirs = ProjectDirs::new_with_base(temp.path()).unwrap();
+
+    let (mut client, server) = UnixStream::pair().unwrap();
+    server.set_read_timeout(Some(Duration::from_millis(100))).unwrap();
+
+    // Only a part of the message size is sent, so the request is never complete
+    client.write_all(&[1]).unwrap();
+
+    let output = server.try_clone().unwrap();
+    let result = Connection::serve(&dirs, false, &server, output);
+    assert!(matches!(result, Err(ConnectionError::Timeout)));
+}
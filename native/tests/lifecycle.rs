@@ -0,0 +1,268 @@
+//! End-to-end tests for the profile and web app lifecycle.
+//!
+//! Each test uses its own temporary directory for all project directories, so the tests
+//! never touch the real user data and do not require the runtime or 7-Zip to be installed.
+
+use std::fs::{read_to_string, write};
+
+use firefoxpwa::components::site::Site;
+use firefoxpwa::console::app::{
+    ProfileCreateCommand,
+    ProfileRemoveCommand,
+    ProfileUpdateCommand,
+    SiteUninstallCommand,
+};
+use firefoxpwa::directories::ProjectDirs;
+use firefoxpwa::storage::{Storage, StorageError};
+use reqwest::blocking::Client;
+use serde_json::Value;
+use tempfile::TempDir;
+use ulid::Ulid;
+use url::Url;
+
+mod common;
+
+const UPDATED_MANIFEST: &str = r#"{
+    "name": "Example Updated",
+    "start_url": "/app/",
+    "icons": [{ "src": "/icon.png", "sizes": "192x192" }]
+}"#;
+
+fn setup() -> (TempDir, ProjectDirs) {
+    let temp = TempDir::new().unwrap();
+    let dirs = ProjectDirs::new_with_base(temp.path()).unwrap();
+    (temp, dirs)
+}
+
+fn reload(dirs: &ProjectDirs) -> Storage {
+    Storage::load(dirs).unwrap()
+}
+
+#[test]
+fn project_dirs_are_inside_base() {
+    let (temp, dirs) = setup();
+
+    assert!(dirs.executables.starts_with(temp.path()));
+    assert!(dirs.sysdata.starts_with(temp.path()));
+    assert!(dirs.userdata.starts_with(temp.path()));
+    assert!(dirs.userdata.is_dir());
+    assert!(Storage::path(&dirs).starts_with(temp.path()));
+}
+
+#[test]
+fn missing_storage_contains_only_default_profile() {
+    let (_temp, dirs) = setup();
+    let storage = reload(&dirs);
+
+    assert_eq!(storage.profiles.len(), 1);
+    assert!(storage.profiles.contains_key(&Ulid::nil()));
+    assert!(storage.sites.is_empty());
+}
+
+#[test]
+fn profile_and_site_lifecycle() {
+    let (_temp, dirs) = setup();
+    let client = Client::new();
+
+    // Create profile
+    let command = ProfileCreateCommand {
+        name: Some("Work".into()),
+        description: None,
+        template: None,
+        template_exclude: vec![],
+        from: None,
+    };
+    let profile_id = command._run_with(&dirs).unwrap();
+
+    let storage = reload(&dirs);
+    assert_eq!(storage.profiles.len(), 2);
+    assert_eq!(storage.profiles[&profile_id].name.as_deref(), Some("Work"));
+    let found: Vec<_> =
+        storage.find_profiles_by_name("work", false).into_iter().map(|(ulid, _)| ulid).collect();
+    assert_eq!(found, [profile_id]);
+
+    // Update profile
+    let command = ProfileUpdateCommand {
+        id: None,
+        profile_name: Some("work".into()),
+        exact: false,
+        name: None,
+        description: Some(Some("Work apps".into())),
+        template: None,
+        template_exclude: vec![],
+        add_tag: vec!["office".into()],
+        remove_tag: vec![],
+        template_strategy: "overwrite".into(),
+    };
+    command._run_with(&dirs).unwrap();
+
+    let storage = reload(&dirs);
+    assert_eq!(storage.profiles[&profile_id].name.as_deref(), Some("Work"));
+    assert_eq!(storage.profiles[&profile_id].description.as_deref(), Some("Work apps"));
+    assert_eq!(storage.profiles[&profile_id].tags, ["office"]);
+
+    // Create site
+    let mut storage = storage;
+    let site = common::site(profile_id);
+    let site_id = site.ulid;
+    site.save_manifest(&dirs, common::MANIFEST).unwrap();
+    storage.profiles.get_mut(&profile_id).unwrap().sites.push(site_id);
+    storage.sites.insert(site_id, site);
+    storage.write(&dirs).unwrap();
+
+    let storage = reload(&dirs);
+    assert_eq!(storage.sites.len(), 1);
+    assert_eq!(storage.profiles[&profile_id].sites, vec![site_id]);
+    assert_eq!(storage.sites[&site_id].profile, profile_id);
+    assert_eq!(storage.sites[&site_id].name(), "Example");
+    assert_eq!(storage.sites[&site_id].load_manifest(&dirs).unwrap(), common::MANIFEST);
+
+    // Update site
+    let mut storage = storage;
+    let site = storage.sites.get_mut(&site_id).unwrap();
+    site.update_from_manifest(UPDATED_MANIFEST, &client).unwrap();
+    site.config.categories = Some(vec!["Utility".into()]);
+    storage.write(&dirs).unwrap();
+
+    let storage = reload(&dirs);
+    let site = &storage.sites[&site_id];
+    assert_eq!(site.name(), "Example Updated");
+    assert_eq!(site.url(), "https://example.com/app/");
+    assert_eq!(site.categories(), vec!["Utility".to_string()]);
+    assert_eq!(site.ulid, site_id);
+
+    // List sites
+    let url = Url::parse("https://example.com/").unwrap();
+    let found: Vec<_> = storage.find_sites_by_url(&url).into_iter().map(|(ulid, _)| ulid).collect();
+    assert_eq!(found, vec![site_id]);
+
+    let listed: Vec<_> = storage.sites.values().filter(|site| site.profile == profile_id).collect();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].ulid, site_id);
+
+    // Remove site
+    let manifest = site.manifest_path(&dirs);
+    assert!(manifest.exists());
+
+    let command = SiteUninstallCommand {
+        id: Some(site_id),
+        document_url: None,
+        quiet: true,
+        system_integration: false,
+    };
+    command._run_with(&dirs).unwrap();
+
+    let storage = reload(&dirs);
+    assert!(storage.sites.is_empty());
+    assert!(storage.profiles[&profile_id].sites.is_empty());
+    assert!(storage.find_sites_by_url(&url).is_empty());
+    assert!(!manifest.exists());
+
+    // Remove profile
+    let command =
+        ProfileRemoveCommand { id: Some(profile_id), name: None, exact: false, quiet: true };
+    command._run_with(&dirs).unwrap();
+
+    let storage = reload(&dirs);
+    assert!(!dirs.userdata.join("profiles").join(profile_id.to_string()).exists());
+    assert_eq!(storage.profiles.len(), 1);
+    assert!(storage.profiles.contains_key(&Ulid::nil()));
+    assert!(storage.find_profiles_by_name("Work", false).is_empty());
+}
+
+#[test]
+fn invalid_site_config_is_reported_per_field() {
+    let (_temp, dirs) = setup();
+
+    let mut storage = reload(&dirs);
+    let site = common::site(Ulid::nil());
+    let site_id = site.ulid;
+    storage.sites.insert(site_id, site);
+    storage.write(&dirs).unwrap();
+
+    // Break two fields directly in the storage file
+    let path = Storage::path(&dirs);
+    let mut data: Value = serde_json::from_str(&read_to_string(&path).unwrap()).unwrap();
+    let config = &mut data["sites"][site_id.to_string()]["config"];
+    config["launch_on_login"] = "yes".into();
+    config["manifest_url"] = Value::Null;
+    write(&path, data.to_string()).unwrap();
+
+    match Storage::load(&dirs) {
+        Err(StorageError::InvalidSite(site, errors)) => {
+            let fields: Vec<_> = errors.iter().map(|error| error.field.as_str()).collect();
+            assert_eq!(site, site_id.to_string());
+            assert_eq!(fields, ["manifest_url", "launch_on_login"]);
+        }
+        result => panic!("Expected an invalid web app error, got {result:?}"),
+    }
+}
+
+#[test]
+fn app_user_model_id_is_stored_and_stable() {
+    let (_temp, dirs) = setup();
+    let client = Client::new();
+
+    let mut storage = reload(&dirs);
+    let site = common::site(Ulid::nil());
+    let site_id = site.ulid;
+    let expected = format!("filips.firefoxpwa.{site_id}");
+    assert_eq!(site.config.app_user_model_id.as_deref(), Some(expected.as_str()));
+    storage.sites.insert(site_id, site);
+    storage.write(&dirs).unwrap();
+
+    // Renaming the web app must not change the ID used by existing shortcuts
+    let mut storage = reload(&dirs);
+    let site = storage.sites.get_mut(&site_id).unwrap();
+    site.update_from_manifest(UPDATED_MANIFEST, &client).unwrap();
+    site.config.name_override = Some("Renamed".into());
+    storage.write(&dirs).unwrap();
+
+    let mut storage = reload(&dirs);
+    assert_eq!(storage.sites[&site_id].app_user_model_id(), expected);
+
+    // Web apps installed before the ID was stored derive the same ID
+    storage.sites.get_mut(&site_id).unwrap().config.app_user_model_id = None;
+    assert_eq!(storage.sites[&site_id].app_user_model_id(), expected);
+}
+
+#[cfg(all(platform_windows, not(feature = "portable")))]
+#[test]
+fn shortcut_carries_app_user_model_id() {
+    use firefoxpwa::integrations::implementation::windows::create_menu_shortcut_in;
+    use windows::Win32::Storage::EnhancedStorage::PKEY_AppUserModel_ID;
+    use windows::Win32::System::Com::{COINIT_MULTITHREADED, CoInitializeEx};
+    use windows::Win32::UI::Shell::PropertiesSystem::{
+        GPS_DEFAULT,
+        IPropertyStore,
+        SHGetPropertyStoreFromParsingName,
+    };
+    use windows::core::HSTRING;
+
+    let (temp, dirs) = setup();
+    let site = common::site(Ulid::nil());
+
+    // The shortcut is created in the temporary directory instead of the real start menu
+    let shortcut = create_menu_shortcut_in(&site, &dirs, &temp.path().join("data")).unwrap();
+    assert!(shortcut.is_file());
+
+    let id = unsafe {
+        CoInitializeEx(None, COINIT_MULTITHREADED).ok().unwrap();
+        let path = HSTRING::from(shortcut.display().to_string());
+        let store: IPropertyStore =
+            SHGetPropertyStoreFromParsingName(&path, None, GPS_DEFAULT).unwrap();
+        store.GetValue(&PKEY_AppUserModel_ID).unwrap().to_string()
+    };
+    assert_eq!(id, site.app_user_model_id());
+}
+
+#[test]
+fn site_without_icons_has_no_icons() {
+    let site =
+        Site::from_manifest_without_icons(Ulid::nil(), common::config(), common::MANIFEST).unwrap();
+
+    assert!(site.manifest.icons.is_empty());
+    assert_eq!(site.config.icon_url, None);
+    assert_eq!(site.config.fallback_icon_url, None);
+    assert!(site.icons().is_empty());
+}
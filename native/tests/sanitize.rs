@@ -0,0 +1,49 @@
+//! Property-based tests for sanitizing strings from web app manifests.
+
+use firefoxpwa::utils::sanitize_string;
+use proptest::prelude::*;
+
+/// Strings that mix arbitrary characters with ones that commonly appear
+/// in hostile or unusual manifests, such as emoji, bidirectional overrides,
+/// null bytes and other ASCII control characters.
+fn unusual_string() -> impl Strategy<Value = String> {
+    let char = prop_oneof![
+        any::<char>(),
+        Just('\0'),
+        Just('\u{7F}'),
+        (0u8..0x20).prop_map(char::from),
+        Just('\u{202E}'),
+        Just('\u{2067}'),
+        Just('\u{200F}'),
+        Just('\u{1F600}'),
+        Just('\u{1F469}'),
+        Just('\u{200D}'),
+        Just('\u{05D0}'),
+        Just('\u{0627}'),
+    ];
+
+    prop::collection::vec(char, 0..64).prop_map(String::from_iter)
+}
+
+fn check_properties(input: &str) -> Result<(), TestCaseError> {
+    let output = sanitize_string(input);
+
+    prop_assert!(!output.chars().any(|char| char.is_ascii_control()));
+    prop_assert!(output.chars().count() <= input.chars().count());
+    prop_assert_eq!(sanitize_string(&output), output.clone());
+    prop_assert!(std::str::from_utf8(output.as_bytes()).is_ok());
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn sanitize_arbitrary_strings(input in any::<String>()) {
+        check_properties(&input)?;
+    }
+
+    #[test]
+    fn sanitize_unusual_strings(input in unusual_string()) {
+        check_properties(&input)?;
+    }
+}
@@ -39,6 +39,52 @@ function readConfig () {
   return JSON.parse(configJson);
 }
 
+/**
+ * Applies the custom window size and position of the web app to the window.
+ *
+ * The geometry is applied once the browser window finishes its startup, so it is not overwritten
+ * by the size and position that Firefox restores for new windows. If the saved position is not
+ * on any connected screen, for example after disconnecting a monitor, the window is moved to the
+ * nearest visible area instead.
+ *
+ * @param {ChromeWindow&Window} win - The window to apply the geometry to.
+ * @param {object} siteConfig - The web app config.
+ */
+function applyWindowGeometry (win, siteConfig) {
+  const geometry = siteConfig.config.window;
+  if (!geometry) return;
+
+  const { width, height, x, y, maximized } = geometry;
+  if (width == null && height == null && x == null && y == null && !maximized) return;
+
+  const topic = 'browser-delayed-startup-finished';
+  const observer = (subject) => {
+    if (subject !== win) return;
+    Services.obs.removeObserver(observer, topic);
+
+    if (width != null && height != null) {
+      win.resizeTo(width, height);
+    }
+
+    if (x != null && y != null) {
+      // Clamp the position to the available area of the screen nearest to the saved position
+      const screen = Services.screenManager.screenForRect(x, y, win.outerWidth, win.outerHeight);
+      const left = {}, top = {}, availWidth = {}, availHeight = {};
+      screen.GetAvailRectDisplayPix(left, top, availWidth, availHeight);
+
+      const maxX = left.value + Math.max(availWidth.value - win.outerWidth, 0);
+      const maxY = top.value + Math.max(availHeight.value - win.outerHeight, 0);
+      win.moveTo(Math.min(Math.max(x, left.value), maxX), Math.min(Math.max(y, top.value), maxY));
+    }
+
+    if (maximized) {
+      win.maximize();
+    }
+  };
+
+  Services.obs.addObserver(observer, topic);
+}
+
 /**
  * Launches a web app with the provided URL list and sets its config.
  *
@@ -53,6 +99,22 @@ function readConfig () {
  * @returns {ChromeWindow&Window} - The new window.
  */
 function launchSite (siteConfig, urlList, isStartup) {
+  // Focus the existing window if the web app only allows a single instance
+  if (siteConfig.config.single_instance) {
+    for (const win of Services.wm.getEnumerator('navigator:browser')) {
+      if (win.gFFPWASiteConfig?.ulid === siteConfig.ulid) {
+        win.focus();
+        return win;
+      }
+    }
+  }
+
+  // Use the web app locale as the preferred language for web content
+  // Set on the default branch, so it does not overwrite user preferences
+  if (siteConfig.config.locale) {
+    Services.prefs.getDefaultBranch(null).setStringPref('intl.accept_languages', siteConfig.config.locale);
+  }
+
   // Handle launching a web app when the same web app is already opened
   // We have to specify pref directly as we cannot access ChromeLoader yet
   const launchType = Services.prefs.getIntPref('firefoxpwa.launchType', 0);
@@ -94,6 +156,7 @@ function launchSite (siteConfig, urlList, isStartup) {
     if (win) {
       // Apply the system integration and set the site config
       applySystemIntegration(win, siteConfig);
+      applyWindowGeometry(win, siteConfig);
       win.gFFPWASiteConfig = siteConfig;
 
       // Remove the window type of blank window so that we don't close it later
@@ -117,6 +180,7 @@ function launchSite (siteConfig, urlList, isStartup) {
 
   // Apply the system integration and set the site config
   applySystemIntegration(win, siteConfig);
+  applyWindowGeometry(win, siteConfig);
   win.gFFPWASiteConfig = siteConfig;
 
   return win;
@@ -94,7 +94,7 @@ class PwaBrowser {
     if (siteIcon) tabIconImage.setAttribute('src', siteIcon.icon.src);
 
     const siteScope = window.gFFPWASiteConfig?.manifest.scope ? new URL(window.gFFPWASiteConfig.manifest.scope).host : null;
-    const siteName = sanitizeString(window.gFFPWASiteConfig?.config.name || window.gFFPWASiteConfig?.manifest.name || window.gFFPWASiteConfig?.manifest.short_name) || siteScope;
+    const siteName = sanitizeString(window.gFFPWASiteConfig?.config.name_override || window.gFFPWASiteConfig?.config.name || window.gFFPWASiteConfig?.manifest.name || window.gFFPWASiteConfig?.manifest.short_name) || siteScope;
     tabLabel.replaceChildren(siteName);
     document.title = siteName;
 
@@ -193,7 +193,7 @@ function setWindowColors (window, site) {
 export function applySystemIntegration (window, site) {
   // Set title only on the main browser chrome window
   if (window.location.href === lazy.AppConstants.BROWSER_CHROME_URL) {
-    const name = lazy.sanitizeString(site.config.name || site.manifest.name || site.manifest.short_name);
+    const name = lazy.sanitizeString(site.config.name_override || site.config.name || site.manifest.name || site.manifest.short_name);
     window.document.title = name || new URL(site.manifest.scope).host;
   }
 
@@ -202,7 +202,8 @@ export function applySystemIntegration (window, site) {
   window.document.documentElement.setAttribute('windowname', `FFPWA-${site.ulid}`);
 
   if (lazy.AppConstants.platform === 'win') {
-    lazy.WinTaskbar.setGroupIdForWindow(window, `filips.firefoxpwa.${site.ulid}`);
+    const appUserModelId = site.config.app_user_model_id || `filips.firefoxpwa.${site.ulid}`;
+    lazy.WinTaskbar.setGroupIdForWindow(window, appUserModelId);
     setWindowIcons(window, site);
   }
 